@@ -0,0 +1,56 @@
+extern crate kiss3d;
+extern crate nalgebra as na;
+
+use kiss3d::camera::FirstPerson;
+use kiss3d::event::{camera_actions, ActionMap, AxisBinding, Binding, Key, Modifiers, MouseButton};
+use kiss3d::light::Light;
+use kiss3d::window::Window;
+use na::Point3;
+
+fn main() {
+    let mut window = Window::new("Kiss3d: action map");
+    let mut camera = FirstPerson::new(Point3::new(0.0, 0.0, -5.0), Point3::origin());
+
+    let mut c = window.add_cube(1.0, 1.0, 1.0);
+    c.set_color(1.0, 0.0, 0.0);
+
+    window.set_light(Light::StickToCamera);
+
+    // All the bindings of the application in one place. The action map could also be loaded
+    // from a configuration file, since it implements `Deserialize`.
+    let mut actions = ActionMap::new();
+    actions.bind(
+        camera_actions::ROTATE,
+        Binding::MouseButton(MouseButton::Button1, Some(Modifiers::Shift)),
+    );
+    actions.bind(
+        camera_actions::PAN,
+        Binding::MouseButton(MouseButton::Button2, None),
+    );
+    actions.bind_axis(
+        camera_actions::MOVE_FORWARD,
+        AxisBinding::Buttons(Binding::Key(Key::S, None), Binding::Key(Key::W, None)),
+    );
+    actions.bind_axis(
+        camera_actions::MOVE_RIGHT,
+        AxisBinding::Buttons(Binding::Key(Key::A, None), Binding::Key(Key::D, None)),
+    );
+    actions.bind_axis(camera_actions::ZOOM, AxisBinding::ScrollY);
+    actions.bind("paint", Binding::Key(Key::Space, None));
+    actions.bind("paint", Binding::MouseButton(MouseButton::Button3, None));
+    window.set_action_map(actions);
+
+    let mut red = true;
+
+    while window.render_with_camera(&mut camera) {
+        if window.action_map().just_activated("paint") {
+            red = !red;
+        }
+
+        if red {
+            c.set_color(1.0, 0.0, 0.0);
+        } else {
+            c.set_color(0.0, 0.0, 1.0);
+        }
+    }
+}
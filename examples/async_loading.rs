@@ -0,0 +1,49 @@
+extern crate kiss3d;
+extern crate nalgebra as na;
+
+use kiss3d::light::Light;
+use kiss3d::resource::AsyncLoader;
+use kiss3d::text::Font;
+use kiss3d::window::Window;
+use na::{Point2, Point3, Translation3, UnitQuaternion, Vector3};
+use std::path::Path;
+
+fn main() {
+    let mut window = Window::new("Kiss3d: async loading");
+
+    // The teapot is empty until it is loaded, while the window keeps rendering.
+    let obj_path = Path::new("media/teapot/teapot.obj");
+    let mtl_path = Path::new("media/teapot");
+    let mut teapot = window.add_obj_async(&obj_path, &mtl_path, Vector3::new(0.001, 0.001, 0.001));
+    teapot.append_translation(&Translation3::new(0.0, -0.05, -0.2));
+
+    let font = Font::new_async(Path::new("media/font/Inconsolata.otf"));
+
+    window.set_light(Light::StickToCamera);
+
+    let rot = UnitQuaternion::from_axis_angle(&Vector3::y_axis(), 0.014);
+
+    while window.render() {
+        teapot.prepend_to_local_rotation(&rot);
+
+        for error in AsyncLoader::get_global_loader(|loader| loader.take_errors()) {
+            println!("Loading failed: {}", error);
+        }
+
+        let pending = AsyncLoader::get_global_loader(|loader| loader.num_pending());
+        let font = font.get().unwrap_or_else(Font::default);
+        let text = if pending == 0 {
+            "Loaded.".to_string()
+        } else {
+            format!("Loading {} resources...", pending)
+        };
+
+        window.draw_text(
+            &text,
+            &Point2::origin(),
+            60.0,
+            &font,
+            &Point3::new(1.0, 1.0, 1.0),
+        );
+    }
+}
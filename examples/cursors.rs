@@ -0,0 +1,67 @@
+extern crate image;
+extern crate kiss3d;
+extern crate nalgebra as na;
+
+use image::{ImageBuffer, Rgba};
+use kiss3d::event::{Action, Key, WindowEvent};
+use kiss3d::light::Light;
+use kiss3d::window::{CursorIcon, Window};
+use na::{UnitQuaternion, Vector3};
+
+const ICONS: [CursorIcon; 8] = [
+    CursorIcon::Default,
+    CursorIcon::Hand,
+    CursorIcon::Crosshair,
+    CursorIcon::Text,
+    CursorIcon::Grab,
+    CursorIcon::Grabbing,
+    CursorIcon::EwResize,
+    CursorIcon::NwseResize,
+];
+
+fn main() {
+    let mut window = Window::new("Kiss3d: cursors");
+    let mut c = window.add_cube(1.0, 1.0, 1.0);
+    c.set_color(1.0, 0.0, 0.0);
+
+    window.set_light(Light::StickToCamera);
+
+    println!("Space: next cursor icon, I: custom cursor image (web only), H: hide the cursor");
+
+    // A red disk, with the hotspot at its center.
+    let disk = ImageBuffer::from_fn(16, 16, |x, y| {
+        let (dx, dy) = (x as f32 - 7.5, y as f32 - 7.5);
+
+        if dx * dx + dy * dy <= 56.0 {
+            Rgba([255u8, 0, 0, 255])
+        } else {
+            Rgba([0, 0, 0, 0])
+        }
+    });
+
+    let rot = UnitQuaternion::from_axis_angle(&Vector3::y_axis(), 0.014);
+    let mut icon = 0;
+    let mut hidden = false;
+
+    while window.render() {
+        c.prepend_to_local_rotation(&rot);
+
+        for event in window.events().iter() {
+            if let WindowEvent::Key(key, Action::Press, _) = event.value {
+                match key {
+                    Key::Space => {
+                        icon = (icon + 1) % ICONS.len();
+                        println!("Cursor icon: {:?}", ICONS[icon]);
+                        window.set_cursor_icon(ICONS[icon])
+                    }
+                    Key::I => window.set_cursor_image(disk.clone(), (8, 8)),
+                    Key::H => {
+                        hidden = !hidden;
+                        window.hide_cursor(hidden)
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+}
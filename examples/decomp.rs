@@ -57,7 +57,7 @@ fn main() {
      */
     let obj_path = Path::new(path);
     let mtl_path = Path::new("none");
-    let teapot = obj::parse_file(&obj_path, &mtl_path, "none").unwrap();
+    let (teapot, _) = obj::parse_file(&obj_path, &mtl_path, "none").unwrap();
 
     let mut m = window.add_obj(&obj_path, &mtl_path, scale);
     m.set_surface_rendering_activation(false);
@@ -77,7 +77,7 @@ fn main() {
     // println!("objs: {}", teapot.len());
 
     let mut total_time = 0.0f64;
-    for &(_, ref mesh, _) in teapot.iter() {
+    for &(_, ref mesh, _, _) in teapot.iter() {
         match mesh.to_trimesh() {
             Some(mut trimesh) => {
                 trimesh.split_index_buffer(true);
@@ -0,0 +1,92 @@
+extern crate image;
+extern crate kiss3d;
+extern crate nalgebra as na;
+
+use std::path::Path;
+
+use image::DynamicImage;
+use kiss3d::event::{Action, DroppedFile, Key, Modifiers, WindowEvent};
+use kiss3d::light::Light;
+use kiss3d::resource::TextureManager;
+use kiss3d::scene::SceneNode;
+use kiss3d::window::Window;
+use na::{UnitQuaternion, Vector3};
+
+fn is_image(name: &str) -> bool {
+    let name = name.to_lowercase();
+    name.ends_with(".png") || name.ends_with(".jpg") || name.ends_with(".jpeg")
+}
+
+fn set_texture(node: &mut SceneNode, image: image::ImageResult<DynamicImage>, name: &str) {
+    match image {
+        Ok(image) => {
+            let texture = TextureManager::get_global_manager(|tm| tm.add_image(image, name));
+            node.set_texture(texture);
+            node.set_color(1.0, 1.0, 1.0);
+        }
+        Err(e) => println!("Failed to load {}: {}", name, e),
+    }
+}
+
+fn main() {
+    let mut window = Window::new("Kiss3d: drop files");
+    let mut c = window.add_cube(1.0, 1.0, 1.0);
+    c.set_color(1.0, 0.0, 0.0);
+
+    window.set_light(Light::StickToCamera);
+
+    println!("Drop an image onto the window to texture the cube, or an obj file to load it.");
+    println!("Ctrl+C: copy the name of the last dropped file, Ctrl+V: print the clipboard");
+
+    let rot = UnitQuaternion::from_axis_angle(&Vector3::y_axis(), 0.014);
+    let mut last_file = String::new();
+
+    while window.render() {
+        c.prepend_to_local_rotation(&rot);
+
+        for event in window.events().iter() {
+            match event.value {
+                WindowEvent::HoveredFile => println!("Hovering {:?}", window.hovered_files()),
+                WindowEvent::HoveredFileCancelled => println!("Hovering cancelled"),
+                WindowEvent::DroppedFile => {
+                    for file in window.take_dropped_files() {
+                        match file {
+                            DroppedFile::Path(path) => {
+                                let name = path.to_string_lossy().into_owned();
+
+                                if is_image(&name) {
+                                    set_texture(&mut c, image::open(&path), &name);
+                                } else if name.to_lowercase().ends_with(".obj") {
+                                    let mtl_dir = path.parent().unwrap_or_else(|| Path::new("."));
+                                    let scale = Vector3::new(0.1, 0.1, 0.1);
+
+                                    match window.try_add_obj(&path, mtl_dir, scale) {
+                                        Ok(_) => println!("Loaded {}", name),
+                                        Err(e) => println!("Failed to load {}: {}", name, e),
+                                    }
+                                }
+
+                                last_file = name;
+                            }
+                            // Dropped files are only available as bytes on web platforms.
+                            DroppedFile::Data(name, data) => {
+                                if is_image(&name) {
+                                    set_texture(&mut c, image::load_from_memory(&data), &name);
+                                }
+
+                                last_file = name;
+                            }
+                        }
+                    }
+                }
+                WindowEvent::Key(Key::C, Action::Press, Modifiers::Control) => {
+                    window.set_clipboard_text(&last_file)
+                }
+                WindowEvent::Key(Key::V, Action::Press, Modifiers::Control) => {
+                    println!("Clipboard: {:?}", window.clipboard_text())
+                }
+                _ => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,47 @@
+extern crate kiss3d;
+extern crate nalgebra as na;
+
+use kiss3d::light::Light;
+use kiss3d::resource::{TextureFormat, TextureManager};
+use kiss3d::window::Window;
+use na::{UnitQuaternion, Vector3};
+
+const SIZE: u32 = 128;
+
+fn main() {
+    let mut window = Window::new("Kiss3d: dynamic texture");
+
+    let heat_map = TextureManager::get_global_manager(|tm| {
+        tm.add_dynamic("heat_map", SIZE, SIZE, TextureFormat::Rgba8)
+    });
+
+    let mut c = window.add_cube(1.0, 1.0, 1.0);
+    c.set_texture_with_name("heat_map");
+
+    window.set_light(Light::StickToCamera);
+
+    let rot = UnitQuaternion::from_axis_angle(&Vector3::y_axis(), 0.014);
+    let mut pixels = vec![0u8; (SIZE * SIZE * 4) as usize];
+    let mut time = 0.0f32;
+
+    while window.render() {
+        c.prepend_to_local_rotation(&rot);
+        time += 0.05;
+
+        for y in 0..SIZE {
+            for x in 0..SIZE {
+                let dx = x as f32 / SIZE as f32 - 0.5;
+                let dy = y as f32 / SIZE as f32 - 0.5;
+                let heat = ((dx * dx + dy * dy).sqrt() * 20.0 - time).sin() * 0.5 + 0.5;
+                let i = ((y * SIZE + x) * 4) as usize;
+
+                pixels[i] = (heat * 255.0) as u8;
+                pixels[i + 1] = 0;
+                pixels[i + 2] = ((1.0 - heat) * 255.0) as u8;
+                pixels[i + 3] = 255;
+            }
+        }
+
+        heat_map.update(&pixels);
+    }
+}
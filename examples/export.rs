@@ -0,0 +1,36 @@
+extern crate kiss3d;
+extern crate nalgebra as na;
+
+use kiss3d::exporter::{gltf, obj};
+use kiss3d::light::Light;
+use kiss3d::window::Window;
+use na::{Translation3, UnitQuaternion, Vector3};
+use std::path::Path;
+
+fn main() {
+    let mut window = Window::new("Kiss3d: export");
+    let mut group = window.add_group();
+
+    let mut c = group.add_cube(0.2, 0.2, 0.2);
+    c.set_texture_from_file(&Path::new("./examples/media/kitten.png"), "kitten");
+    c.set_local_translation(Translation3::new(-0.2, 0.0, 0.0));
+
+    let mut s = group.add_sphere(0.1);
+    s.set_color(1.0, 0.5, 0.0);
+    s.set_local_translation(Translation3::new(0.2, 0.0, 0.0));
+    s.set_local_scale(1.0, 2.0, 1.0);
+
+    // Both files can be opened in Blender.
+    let dir = std::env::temp_dir();
+    obj::export_file(&group, &dir.join("kiss3d_export.obj")).unwrap();
+    gltf::export_file(&group, &dir.join("kiss3d_export.glb")).unwrap();
+    println!("Scene exported to {}.", dir.display());
+
+    window.set_light(Light::StickToCamera);
+
+    let rot = UnitQuaternion::from_axis_angle(&Vector3::y_axis(), 0.014);
+
+    while window.render() {
+        group.prepend_to_local_rotation(&rot);
+    }
+}
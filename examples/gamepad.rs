@@ -0,0 +1,41 @@
+extern crate kiss3d;
+extern crate nalgebra as na;
+
+use kiss3d::camera::FirstPerson;
+use kiss3d::event::{Action, WindowEvent};
+use kiss3d::light::Light;
+use kiss3d::window::Window;
+use na::Point3;
+
+// Run with `cargo run --example gamepad --features gamepad`.
+fn main() {
+    let mut window = Window::new("Kiss3d: gamepad");
+    let mut camera = FirstPerson::new(Point3::new(0.0, 0.0, -5.0), Point3::origin());
+
+    let mut c = window.add_cube(1.0, 1.0, 1.0);
+    c.set_color(1.0, 0.0, 0.0);
+
+    window.set_light(Light::StickToCamera);
+
+    while window.render_with_camera(&mut camera) {
+        for event in window.events().iter() {
+            match event.value {
+                WindowEvent::GamepadConnected(id) => {
+                    println!("Gamepad {} connected.", id);
+
+                    // Move and look around with the first gamepad connected.
+                    if camera.gamepad().is_none() {
+                        camera.rebind_gamepad(Some(id));
+                    }
+                }
+                WindowEvent::GamepadDisconnected(id) => {
+                    println!("Gamepad {} disconnected.", id);
+                }
+                WindowEvent::GamepadButton(id, button, Action::Press) => {
+                    println!("You pressed the button {:?} of gamepad {}.", button, id);
+                }
+                _ => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,49 @@
+extern crate kiss3d;
+extern crate nalgebra as na;
+
+use kiss3d::event::{Action, Key, Modifiers, MouseButton, WindowEvent};
+use kiss3d::light::Light;
+use kiss3d::window::Window;
+
+fn main() {
+    let mut window = Window::new("Kiss3d: input injection");
+
+    let mut c = window.add_cube(1.0, 1.0, 1.0);
+    c.set_color(1.0, 0.0, 0.0);
+
+    window.set_light(Light::StickToCamera);
+
+    // Drag the default camera around with a scripted left mouse button drag.
+    window.inject_event(WindowEvent::CursorPos(400.0, 300.0, Modifiers::empty()));
+    window.inject_event(WindowEvent::MouseButton(
+        MouseButton::Button1,
+        Action::Press,
+        Modifiers::empty(),
+    ));
+    assert_eq!(window.get_mouse_button(MouseButton::Button1), Action::Press);
+
+    let mut frame = 0;
+
+    while window.render() {
+        frame += 1;
+
+        if frame <= 100 {
+            let x = 400.0 + frame as f64 * 2.0;
+            window.inject_event(WindowEvent::CursorPos(x, 300.0, Modifiers::empty()));
+        } else if frame == 101 {
+            window.inject_event(WindowEvent::MouseButton(
+                MouseButton::Button1,
+                Action::Release,
+                Modifiers::empty(),
+            ));
+            window.clear_input_overrides();
+        }
+
+        // Overridden keys are seen by the application like real ones.
+        window.override_key(Key::Space, Some(Action::Press));
+        if window.get_key(Key::Space) == Action::Press && frame % 60 == 0 {
+            println!("Frame {}: cursor at {:?}", frame, window.cursor_pos());
+        }
+        window.override_key(Key::Space, None);
+    }
+}
@@ -0,0 +1,33 @@
+extern crate kiss3d;
+extern crate nalgebra as na;
+
+use kiss3d::light::Light;
+use kiss3d::window::Window;
+use na::{UnitQuaternion, Vector3};
+use std::path::Path;
+
+fn main() {
+    let mut window1 = Window::new("Kiss3d: multi windows 1");
+    let mut window2 = Window::new("Kiss3d: multi windows 2");
+
+    // The texture is loaded once and used by both windows.
+    let texture = window1.add_texture(Path::new("./examples/media/kitten.png"), "kitten");
+
+    let mut c1 = window1.add_cube(1.0, 1.0, 1.0);
+    c1.set_texture(texture.clone());
+
+    let mut c2 = window2.add_sphere(0.5);
+    c2.set_texture(texture);
+
+    window1.set_light(Light::StickToCamera);
+    window2.set_light(Light::StickToCamera);
+    window2.set_background_color(0.2, 0.2, 0.3);
+
+    let rot = UnitQuaternion::from_axis_angle(&Vector3::y_axis(), 0.014);
+
+    // Each call to `render` processes the events of every window.
+    while window1.render() && window2.render() {
+        c1.prepend_to_local_rotation(&rot);
+        c2.prepend_to_local_rotation(&rot);
+    }
+}
@@ -0,0 +1,83 @@
+extern crate kiss3d;
+extern crate nalgebra as na;
+
+use kiss3d::camera::{ArcBall, Camera};
+use kiss3d::planar_camera::PlanarCamera;
+use kiss3d::post_processing::{EyeDomeLighting, PostProcessingEffect};
+use kiss3d::renderer::{OctreePointCloud, PlanarRenderer, PointShape, Renderer};
+use kiss3d::text::Font;
+use kiss3d::window::{CustomWindow, ExtendedState};
+use na::{Point2, Point3};
+
+// An octree point cloud only keeps on the GPU the points required by the current point of view.
+// In this example, we generate a terrain with several millions of points, write it to a file,
+// then stream it from that file.
+
+struct AppState {
+    camera: ArcBall,
+    point_cloud: OctreePointCloud,
+    edl: EyeDomeLighting,
+}
+
+impl ExtendedState for AppState {
+    fn step(&mut self, window: &mut CustomWindow) {
+        for error in self.point_cloud.take_errors() {
+            println!("{}", error);
+        }
+
+        let text = format!("Loaded points: {}", self.point_cloud.num_loaded_points());
+        window.draw_text(
+            &text,
+            &Point2::new(0.0, 20.0),
+            60.0,
+            &Font::default(),
+            &Point3::new(1.0, 1.0, 1.0),
+        );
+    }
+
+    fn cameras_and_effect_and_renderers(
+        &mut self,
+    ) -> (
+        Option<&mut dyn Camera>,
+        Option<&mut dyn PlanarCamera>,
+        Option<&mut dyn Renderer>,
+        Option<&mut dyn PlanarRenderer>,
+        Option<&mut dyn PostProcessingEffect>,
+    ) {
+        (
+            Some(&mut self.camera),
+            None,
+            Some(&mut self.point_cloud),
+            None,
+            Some(&mut self.edl),
+        )
+    }
+}
+
+fn main() {
+    let path = std::env::temp_dir().join("kiss3d_octree_point_cloud.bin");
+    let mut points = Vec::new();
+    let mut colors = Vec::new();
+
+    for _ in 0..4_000_000 {
+        let (x, z): (f32, f32) = rand::random();
+        let y = ((x * 20.0).sin() + (z * 15.0).cos()) * 0.05;
+        points.push(Point3::new(x - 0.5, y, z - 0.5));
+        colors.push(Point3::new(x, 0.5 + y * 5.0, z));
+    }
+
+    OctreePointCloud::build(&path, &points, &colors, 20_000).unwrap();
+
+    let window = CustomWindow::new("Kiss3d: octree_point_cloud");
+    let mut point_cloud = OctreePointCloud::open(&path).unwrap();
+    point_cloud.set_point_shape(PointShape::Round);
+    point_cloud.set_point_budget(2_000_000);
+
+    let app = AppState {
+        camera: ArcBall::new(Point3::new(0.0f32, 1.0, 1.0), Point3::new(0.0f32, 0.0, 0.0)),
+        point_cloud,
+        edl: EyeDomeLighting::new(1.0, 1.5),
+    };
+
+    window.render_loop(app)
+}
@@ -1,22 +1,19 @@
 extern crate kiss3d;
 extern crate nalgebra as na;
 
-use kiss3d::renderer::PlanarRenderer;
-use kiss3d::camera::{Camera, ArcBall};
-use kiss3d::context::Context;
+use kiss3d::camera::{ArcBall, Camera};
 use kiss3d::planar_camera::PlanarCamera;
+#[cfg(not(target_arch = "wasm32"))]
+use kiss3d::post_processing::EyeDomeLighting;
 use kiss3d::post_processing::PostProcessingEffect;
-use kiss3d::renderer::Renderer;
-use kiss3d::resource::{
-    AllocationType, BufferType, Effect, GPUVec, ShaderAttribute, ShaderUniform,
-};
+use kiss3d::renderer::{PersistentPointRenderer, PlanarRenderer, PointShape, Renderer};
 use kiss3d::text::Font;
-use kiss3d::window::{ExtendedState, CustomWindow};
-use na::{Matrix4, Point2, Point3, Vector3};
+use kiss3d::window::{CustomWindow, ExtendedState};
+use na::{Point2, Point3, Vector3};
 
 // Custom renderers are used to allow rendering objects that are not necessarily
 // represented as meshes. In this example, we will render a large, growing, point cloud
-// with a color associated to each point.
+// with a color and a size associated to each point.
 
 // Writing a custom renderer requires the main loop to be
 // handled by the `State` trait instead of a `while window.render()`
@@ -24,7 +21,10 @@ use na::{Matrix4, Point2, Point3, Vector3};
 
 struct AppState {
     camera: ArcBall,
-    point_cloud_renderer: PointCloudRenderer,
+    point_cloud_renderer: PersistentPointRenderer,
+    // Eye-dome lighting is not available on web platforms.
+    #[cfg(not(target_arch = "wasm32"))]
+    edl: EyeDomeLighting,
 }
 
 impl ExtendedState for AppState {
@@ -34,8 +34,12 @@ impl ExtendedState for AppState {
             for _ in 0..1_000 {
                 let (x, y, z): (f32, f32, f32) = rand::random();
                 let point = Point3::<f32>::new(x, y, z);
-                self.point_cloud_renderer
-                    .push((point - Vector3::repeat(0.5)) * 0.5, point);
+                let size = 0.002 + rand::random::<f32>() * 0.004;
+                self.point_cloud_renderer.draw_point_with_size(
+                    (point - Vector3::repeat(0.5)) * 0.5,
+                    point,
+                    size,
+                );
             }
         }
 
@@ -61,105 +65,35 @@ impl ExtendedState for AppState {
         Option<&mut dyn PlanarRenderer>,
         Option<&mut dyn PostProcessingEffect>,
     ) {
-        (Some(&mut self.camera), None, Some(&mut self.point_cloud_renderer), None, None)
+        #[cfg(not(target_arch = "wasm32"))]
+        let effect = Some(&mut self.edl as &mut dyn PostProcessingEffect);
+        #[cfg(target_arch = "wasm32")]
+        let effect = None;
+
+        (
+            Some(&mut self.camera),
+            None,
+            Some(&mut self.point_cloud_renderer),
+            None,
+            effect,
+        )
     }
 }
 
 fn main() {
     let window = CustomWindow::new("Kiss3d: persistent_point_cloud");
+    let mut point_cloud_renderer = PersistentPointRenderer::new();
+
+    // Point sizes are expressed in world units, so closer points appear bigger.
+    point_cloud_renderer.set_point_shape(PointShape::Round);
+    point_cloud_renderer.set_size_attenuation(true);
+
     let app = AppState {
-        camera: ArcBall::new(na::Point3::new(1.0f32, 0.0, 0.0), na::Point3::new(0.0f32, 0.0, 0.0)),
-        point_cloud_renderer: PointCloudRenderer::new(4.0),
+        camera: ArcBall::new(Point3::new(1.0f32, 0.0, 0.0), Point3::new(0.0f32, 0.0, 0.0)),
+        point_cloud_renderer,
+        #[cfg(not(target_arch = "wasm32"))]
+        edl: EyeDomeLighting::new(1.0, 1.5),
     };
 
     window.render_loop(app)
 }
-
-/// Structure which manages the display of long-living points.
-struct PointCloudRenderer {
-    shader: Effect,
-    pos: ShaderAttribute<Point3<f32>>,
-    color: ShaderAttribute<Point3<f32>>,
-    proj: ShaderUniform<Matrix4<f32>>,
-    view: ShaderUniform<Matrix4<f32>>,
-    colored_points: GPUVec<Point3<f32>>,
-    point_size: f32,
-}
-
-impl PointCloudRenderer {
-    /// Creates a new points renderer.
-    fn new(point_size: f32) -> PointCloudRenderer {
-        let mut shader = Effect::new_from_str(VERTEX_SHADER_SRC, FRAGMENT_SHADER_SRC);
-
-        shader.use_program();
-
-        PointCloudRenderer {
-            colored_points: GPUVec::new(Vec::new(), BufferType::Array, AllocationType::StreamDraw),
-            pos: shader.get_attrib::<Point3<f32>>("position").unwrap(),
-            color: shader.get_attrib::<Point3<f32>>("color").unwrap(),
-            proj: shader.get_uniform::<Matrix4<f32>>("proj").unwrap(),
-            view: shader.get_uniform::<Matrix4<f32>>("view").unwrap(),
-            shader,
-            point_size,
-        }
-    }
-
-    fn push(&mut self, point: Point3<f32>, color: Point3<f32>) {
-        if let Some(colored_points) = self.colored_points.data_mut() {
-            colored_points.push(point);
-            colored_points.push(color);
-        }
-    }
-
-    fn num_points(&self) -> usize {
-        self.colored_points.len() / 2
-    }
-}
-
-impl Renderer for PointCloudRenderer {
-    /// Actually draws the points.
-    fn render(&mut self, pass: usize, camera: &mut dyn Camera) {
-        if self.colored_points.len() == 0 {
-            return;
-        }
-
-        self.shader.use_program();
-        self.pos.enable();
-        self.color.enable();
-
-        camera.upload(pass, &mut self.proj, &mut self.view);
-
-        self.color.bind_sub_buffer(&mut self.colored_points, 1, 1);
-        self.pos.bind_sub_buffer(&mut self.colored_points, 1, 0);
-
-        let ctxt = Context::get();
-        ctxt.point_size(self.point_size);
-        ctxt.draw_arrays(Context::POINTS, 0, (self.colored_points.len() / 2) as i32);
-
-        self.pos.disable();
-        self.color.disable();
-    }
-}
-
-const VERTEX_SHADER_SRC: &'static str = "#version 100
-    attribute vec3 position;
-    attribute vec3 color;
-    varying   vec3 Color;
-    uniform   mat4 proj;
-    uniform   mat4 view;
-    void main() {
-        gl_Position = proj * view * vec4(position, 1.0);
-        Color = color;
-    }";
-
-const FRAGMENT_SHADER_SRC: &'static str = "#version 100
-#ifdef GL_FRAGMENT_PRECISION_HIGH
-   precision highp float;
-#else
-   precision mediump float;
-#endif
-
-    varying vec3 Color;
-    void main() {
-        gl_FragColor = vec4(Color, 1.0);
-    }";
@@ -0,0 +1,49 @@
+extern crate kiss3d;
+extern crate nalgebra as na;
+
+use kiss3d::light::Light;
+use kiss3d::window::Window;
+use na::{UnitQuaternion, Vector3};
+use std::fs;
+
+// A colored square pyramid, in the ASCII PLY format.
+const PYRAMID: &'static str = "ply
+format ascii 1.0
+comment Vertex colors are stored as bytes.
+element vertex 5
+property float x
+property float y
+property float z
+property uchar red
+property uchar green
+property uchar blue
+element face 5
+property list uchar int vertex_indices
+end_header
+-0.5 0 -0.5 255 0 0
+0.5 0 -0.5 0 255 0
+0.5 0 0.5 0 0 255
+-0.5 0 0.5 255 255 0
+0 0.8 0 255 255 255
+4 3 2 1 0
+3 0 1 4
+3 1 2 4
+3 2 3 4
+3 3 0 4
+";
+
+fn main() {
+    let path = std::env::temp_dir().join("kiss3d_pyramid.ply");
+    fs::write(&path, PYRAMID).unwrap();
+
+    let mut window = Window::new("Kiss3d: ply");
+    let mut pyramid = window.add_ply(&path, Vector3::new(0.3, 0.3, 0.3)).unwrap();
+
+    window.set_light(Light::StickToCamera);
+
+    let rot = UnitQuaternion::from_axis_angle(&Vector3::y_axis(), 0.014);
+
+    while window.render() {
+        pyramid.prepend_to_local_rotation(&rot);
+    }
+}
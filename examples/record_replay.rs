@@ -0,0 +1,46 @@
+extern crate kiss3d;
+extern crate nalgebra as na;
+
+use kiss3d::event::{Action, EventRecording, Key, WindowEvent};
+use kiss3d::light::Light;
+use kiss3d::window::Window;
+use std::path::Path;
+
+fn main() {
+    let mut window = Window::new("Kiss3d: record and replay");
+    let mut c = window.add_cube(1.0, 1.0, 1.0);
+    c.set_color(1.0, 0.0, 0.0);
+    window.set_light(Light::StickToCamera);
+
+    let path = Path::new("events.txt");
+
+    println!("Press R to start or stop recording the camera moves, and P to replay them.");
+
+    while window.render() {
+        for event in window.events().iter() {
+            match event.value {
+                // The replayed events include the key that stopped the recording.
+                WindowEvent::Key(Key::R, Action::Release, _) if !window.is_replaying() => {
+                    if let Some(recording) = window.stop_recording() {
+                        recording.save(path).unwrap();
+                        println!(
+                            "Saved {} events to {}.",
+                            recording.events().len(),
+                            path.display()
+                        );
+                    } else {
+                        window.start_recording();
+                        println!("Recording...");
+                    }
+                }
+                WindowEvent::Key(Key::P, Action::Release, _) if !window.is_recording() => {
+                    match EventRecording::load(path) {
+                        Ok(recording) => window.replay(recording),
+                        Err(e) => println!("Failed to load {}: {}", path.display(), e),
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,37 @@
+extern crate kiss3d;
+extern crate nalgebra as na;
+
+use kiss3d::camera::ArcBall;
+use kiss3d::light::Light;
+use kiss3d::resource::FramebufferManager;
+use kiss3d::window::Window;
+use na::{Point3, Translation3, UnitQuaternion, Vector3};
+
+fn main() {
+    let mut window = Window::new("Kiss3d: render to texture");
+
+    let mut c = window.add_cube(1.0, 1.0, 1.0);
+    c.set_color(1.0, 0.0, 0.0);
+
+    // A monitor showing the cube seen from behind.
+    let mut security_camera = ArcBall::new(Point3::new(0.0, 1.0, -5.0), Point3::origin());
+    let target = FramebufferManager::new_render_target(256, 256, false);
+
+    let mut monitor = window.add_quad(2.0, 2.0, 1, 1);
+    monitor.append_translation(&Translation3::new(4.0, 0.0, 0.0));
+    monitor.set_texture(target.texture().unwrap());
+
+    window.set_light(Light::StickToCamera);
+
+    let rot = UnitQuaternion::from_axis_angle(&Vector3::y_axis(), 0.014);
+
+    loop {
+        c.prepend_to_local_rotation(&rot);
+
+        window.render_to_texture(&mut security_camera, &target);
+
+        if !window.render() {
+            break;
+        }
+    }
+}
@@ -0,0 +1,36 @@
+extern crate kiss3d;
+extern crate nalgebra as na;
+
+use kiss3d::exporter::stl;
+use kiss3d::light::Light;
+use kiss3d::window::Window;
+use na::{Translation3, UnitQuaternion, Vector3};
+
+fn main() {
+    let mut window = Window::new("Kiss3d: stl");
+    let mut group = window.add_group();
+    let mut c = group.add_cube(0.2, 0.2, 0.2);
+    let mut s = group.add_sphere(0.1);
+
+    c.set_local_translation(Translation3::new(0.0, 0.2, 0.0));
+    s.set_local_translation(Translation3::new(0.0, -0.2, 0.0));
+    group.set_local_translation(Translation3::new(-0.3, 0.0, 0.0));
+
+    // Export the group, with its transformations baked in, then load it back.
+    let path = std::env::temp_dir().join("kiss3d_export.stl");
+    stl::export_file(&group, &path, true).unwrap();
+
+    let mut loaded = window
+        .add_stl(&path, true, Vector3::from_element(1.0))
+        .unwrap();
+    loaded.set_color(1.0, 0.5, 0.0);
+    loaded.set_local_translation(Translation3::new(0.6, 0.0, 0.0));
+
+    window.set_light(Light::StickToCamera);
+
+    let rot = UnitQuaternion::from_axis_angle(&Vector3::y_axis(), 0.014);
+
+    while window.render() {
+        group.prepend_to_local_rotation(&rot);
+    }
+}
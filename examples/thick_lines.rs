@@ -0,0 +1,50 @@
+extern crate kiss3d;
+extern crate nalgebra as na;
+
+use kiss3d::light::Light;
+use kiss3d::renderer::{LineCap, LineJoin};
+use kiss3d::window::Window;
+use na::{Point2, Point3};
+
+fn main() {
+    let mut window = Window::new("Kiss3d: thick lines");
+    let mut c = window.add_cube(0.2, 0.2, 0.2);
+
+    c.set_color(0.2, 0.2, 0.2);
+    c.set_lines_width(4.0);
+    c.set_lines_color(Some(Point3::new(1.0, 1.0, 0.0)));
+
+    window.set_light(Light::StickToCamera);
+    window.line_renderer_mut().set_line_cap(LineCap::Round);
+    window.line_renderer_mut().set_line_join(LineJoin::Miter);
+    window
+        .planar_line_renderer_mut()
+        .set_dash_pattern(Some((20.0, 10.0)));
+
+    let zigzag = [
+        Point3::new(-0.4, -0.2, 0.3),
+        Point3::new(-0.2, 0.2, 0.3),
+        Point3::new(0.0, -0.2, 0.3),
+        Point3::new(0.2, 0.2, 0.3),
+        Point3::new(0.4, -0.2, 0.3),
+    ];
+    let colors = [
+        Point3::new(1.0, 0.0, 0.0),
+        Point3::new(1.0, 0.5, 0.0),
+        Point3::new(0.0, 1.0, 0.0),
+        Point3::new(0.0, 0.5, 1.0),
+        Point3::new(0.0, 0.0, 1.0),
+    ];
+    let widths = [2.0, 8.0, 16.0, 8.0, 2.0];
+    let square = [
+        Point2::new(-200.0, -200.0),
+        Point2::new(200.0, -200.0),
+        Point2::new(200.0, 200.0),
+        Point2::new(-200.0, 200.0),
+    ];
+
+    while window.render() {
+        window.draw_polyline(&zigzag, &colors, &widths, false);
+        window.draw_planar_polyline(&square, &[Point3::new(1.0, 1.0, 1.0)], &[3.0], true);
+    }
+}
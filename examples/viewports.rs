@@ -0,0 +1,43 @@
+extern crate kiss3d;
+extern crate nalgebra as na;
+
+use kiss3d::camera::ArcBall;
+use kiss3d::light::Light;
+use kiss3d::post_processing::Grayscales;
+use kiss3d::window::{Viewport, Window};
+use na::{Point3, UnitQuaternion, Vector3};
+
+fn main() {
+    let mut window = Window::new("Kiss3d: viewports");
+
+    let mut c = window.add_cube(1.0, 1.0, 1.0);
+    c.set_color(1.0, 0.0, 0.0);
+
+    window.set_light(Light::StickToCamera);
+
+    // Top, front, side and perspective views.
+    let top = ArcBall::new(Point3::new(0.0, 5.0, 0.01), Point3::origin());
+    let front = ArcBall::new(Point3::new(0.0, 0.0, 5.0), Point3::origin());
+    let side = ArcBall::new(Point3::new(5.0, 0.0, 0.0), Point3::origin());
+    let perspective = ArcBall::new(Point3::new(3.0, 3.0, 3.0), Point3::origin());
+
+    let _ = window.add_viewport(Viewport::new(0.0, 0.0, 0.5, 0.5, top));
+    let _ = window.add_viewport(Viewport::new(0.5, 0.0, 0.5, 0.5, front));
+    let _ = window.add_viewport(Viewport::new(0.0, 0.5, 0.5, 0.5, side));
+
+    let mut viewport = Viewport::new(0.5, 0.5, 0.5, 0.5, perspective);
+    viewport.set_background_color(0.1, 0.1, 0.2);
+    viewport.set_post_processing_effect(Some(Box::new(Grayscales::new())));
+    let _ = window.add_viewport(viewport);
+
+    let rot = UnitQuaternion::from_axis_angle(&Vector3::y_axis(), 0.014);
+
+    while window.render() {
+        c.prepend_to_local_rotation(&rot);
+        window.draw_line(
+            &Point3::origin(),
+            &Point3::new(1.0, 1.0, 1.0),
+            &Point3::new(0.0, 1.0, 0.0),
+        );
+    }
+}
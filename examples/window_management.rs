@@ -0,0 +1,76 @@
+extern crate kiss3d;
+extern crate nalgebra as na;
+
+use kiss3d::event::{Action, Key, WindowEvent};
+use kiss3d::light::Light;
+use kiss3d::window::{Fullscreen, Window};
+use na::{UnitQuaternion, Vector3};
+
+fn main() {
+    let mut window = Window::new("Kiss3d: window management");
+    let mut c = window.add_cube(1.0, 1.0, 1.0);
+    c.set_color(1.0, 0.0, 0.0);
+
+    window.set_light(Light::StickToCamera);
+    window.set_min_size(Some((320, 240)));
+
+    for (i, monitor) in window.monitors().iter().enumerate() {
+        println!("Monitor {}: {:?}", i, monitor);
+    }
+
+    println!("F: toggle fullscreen, B: toggle decorations, M: maximize, N: minimize");
+    println!("R: toggle resizable, T: toggle always on top, arrows: move the window");
+
+    let rot = UnitQuaternion::from_axis_angle(&Vector3::y_axis(), 0.014);
+    let mut maximized = false;
+    let mut always_on_top = false;
+
+    while window.render() {
+        c.prepend_to_local_rotation(&rot);
+
+        for event in window.events().iter() {
+            if let WindowEvent::Key(key, Action::Press, _) = event.value {
+                match key {
+                    Key::F => {
+                        let fullscreen = match window.fullscreen() {
+                            Some(_) => None,
+                            None => Some(Fullscreen::Borderless(None)),
+                        };
+                        if !window.set_fullscreen(fullscreen) {
+                            println!("Failed to set the fullscreen mode.");
+                        }
+                    }
+                    Key::B => {
+                        let decorations = !window.has_decorations();
+                        window.set_decorations(decorations)
+                    }
+                    Key::M => {
+                        maximized = !maximized;
+                        window.set_maximized(maximized)
+                    }
+                    Key::N => window.set_minimized(true),
+                    Key::R => {
+                        let resizable = !window.is_resizable();
+                        window.set_resizable(resizable)
+                    }
+                    Key::T => {
+                        always_on_top = !always_on_top;
+                        window.set_always_on_top(always_on_top)
+                    }
+                    Key::Left | Key::Right | Key::Up | Key::Down => {
+                        if let Some((x, y)) = window.position() {
+                            let (dx, dy) = match key {
+                                Key::Left => (-20, 0),
+                                Key::Right => (20, 0),
+                                Key::Up => (0, -20),
+                                _ => (0, 20),
+                            };
+                            window.set_position(x + dx, y + dy)
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+}
@@ -2,15 +2,19 @@
 attribute vec3 position;
 attribute vec2 tex_coord;
 attribute vec3 normal;
+attribute vec3 vertex_color;
 
 uniform mat3 ntransform, scale;
 uniform mat4 proj, view, transform;
 uniform vec3 light_position;
+uniform float use_vertex_color;
+uniform float point_size;
 
 varying vec3 local_light_position;
 varying vec2 tex_coord_v;
 varying vec3 normalInterp;
 varying vec3 vertPos;
+varying vec3 tint;
 
 void main(){
     gl_Position = proj * view * transform * vec4(scale * position, 1.0);
@@ -19,4 +23,6 @@ void main(){
     normalInterp = mat3(view) * ntransform * normal;
     tex_coord_v = tex_coord;
     local_light_position = (view * vec4(light_position, 1.0)).xyz;
+    tint = mix(vec3(1.0), vertex_color, use_vertex_color);
+    gl_PointSize = point_size;
 }
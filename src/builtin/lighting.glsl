@@ -0,0 +1,22 @@
+// Phong-like lighting, identical to the one of `default.frag`, which must be kept in sync.
+//
+// `position` and `light_position` are expressed in view space, and `normal` is normalized.
+const vec3 kiss3d_spec_color = vec3(0.4, 0.4, 0.4);
+
+vec3 kiss3d_lighting(vec3 base_color, vec3 position, vec3 normal, vec3 light_position) {
+  vec3 lightDir = normalize(light_position - position);
+
+  float lambertian = max(dot(lightDir, normal), 0.0);
+  float specular = 0.0;
+
+  if(lambertian > 0.0) {
+    vec3 viewDir = normalize(-position);
+    vec3 halfDir = normalize(lightDir + viewDir);
+    float specAngle = max(dot(halfDir, normal), 0.0);
+    specular = pow(specAngle, 30.0);
+  }
+
+  return base_color / 3.0 +
+         lambertian * base_color / 3.0 +
+         specular * kiss3d_spec_color / 3.0;
+}
@@ -1,6 +1,8 @@
 use crate::camera::Camera;
 use crate::context::Context;
 use crate::light::Light;
+use crate::renderer::line_effect::{LineEffect, LineStyle};
+use crate::renderer::{LineCap, LineJoin};
 use crate::resource::Material;
 use crate::resource::{Effect, Mesh, ShaderAttribute, ShaderUniform};
 use crate::scene::ObjectData;
@@ -15,6 +17,9 @@ pub struct ObjectMaterial {
     pos: ShaderAttribute<Point3<f32>>,
     normal: ShaderAttribute<Vector3<f32>>,
     tex_coord: ShaderAttribute<Point2<f32>>,
+    vertex_color: ShaderAttribute<Point3<f32>>,
+    use_vertex_color: ShaderUniform<f32>,
+    point_size: ShaderUniform<f32>,
     light: ShaderUniform<Point3<f32>>,
     color: ShaderUniform<Point3<f32>>,
     transform: ShaderUniform<Matrix4<f32>>,
@@ -22,6 +27,7 @@ pub struct ObjectMaterial {
     ntransform: ShaderUniform<Matrix3<f32>>,
     proj: ShaderUniform<Matrix4<f32>>,
     view: ShaderUniform<Matrix4<f32>>,
+    lines: LineEffect<Matrix4<f32>>,
 }
 
 impl ObjectMaterial {
@@ -37,6 +43,9 @@ impl ObjectMaterial {
             pos: effect.get_attrib("position").unwrap(),
             normal: effect.get_attrib("normal").unwrap(),
             tex_coord: effect.get_attrib("tex_coord").unwrap(),
+            vertex_color: effect.get_attrib("vertex_color").unwrap(),
+            use_vertex_color: effect.get_uniform("use_vertex_color").unwrap(),
+            point_size: effect.get_uniform("point_size").unwrap(),
             light: effect.get_uniform("light_position").unwrap(),
             color: effect.get_uniform("color").unwrap(),
             transform: effect.get_uniform("transform").unwrap(),
@@ -45,6 +54,7 @@ impl ObjectMaterial {
             view: effect.get_uniform("view").unwrap(),
             proj: effect.get_uniform("proj").unwrap(),
             effect: effect,
+            lines: LineEffect::new(),
         }
     }
 
@@ -59,6 +69,7 @@ impl ObjectMaterial {
         self.pos.disable();
         self.normal.disable();
         self.tex_coord.disable();
+        self.vertex_color.disable();
     }
 }
 
@@ -106,6 +117,15 @@ impl Material for ObjectMaterial {
 
             mesh.bind(&mut self.pos, &mut self.normal, &mut self.tex_coord);
 
+            if mesh.bind_colors(&mut self.vertex_color) {
+                self.vertex_color.enable();
+                self.use_vertex_color.upload(&1.0);
+            } else {
+                self.use_vertex_color.upload(&0.0);
+            }
+
+            self.point_size.upload(&data.points_size());
+
             verify!(ctxt.active_texture(Context::TEXTURE0));
             verify!(ctxt.bind_texture(Context::TEXTURE_2D, Some(&*data.texture())));
 
@@ -127,14 +147,12 @@ impl Material for ObjectMaterial {
                 ));
             }
 
-            if data.lines_width() != 0.0 {
-                self.color
-                    .upload(data.lines_color().unwrap_or(data.color()));
+            if data.points_size() != 0.0 {
+                self.color.upload(data.color());
 
                 verify!(ctxt.disable(Context::CULL_FACE));
-                ignore!(ctxt.line_width(data.lines_width()));
-
-                if verify!(ctxt.polygon_mode(Context::FRONT_AND_BACK, Context::LINE)) {
+                ctxt.point_size(data.points_size());
+                if verify!(ctxt.polygon_mode(Context::FRONT_AND_BACK, Context::POINT)) {
                     verify!(ctxt.draw_elements(
                         Context::TRIANGLES,
                         mesh.num_pts() as i32,
@@ -142,43 +160,72 @@ impl Material for ObjectMaterial {
                         0
                     ));
                 } else {
-                    mesh.bind_edges();
                     verify!(ctxt.draw_elements(
-                        Context::LINES,
-                        mesh.num_pts() as i32 * 2,
+                        Context::POINTS,
+                        mesh.num_pts() as i32,
                         Context::UNSIGNED_SHORT,
                         0
                     ));
                 }
-                ctxt.line_width(1.0);
+                ctxt.point_size(1.0);
             }
 
-            if data.points_size() != 0.0 {
-                self.color.upload(data.color());
+            // The wireframe quads cannot be built once the mesh data were unloaded from RAM, so
+            // the GL lines are used instead.
+            if data.lines_width() != 0.0 && mesh.wireframe().is_none() {
+                self.color
+                    .upload(data.lines_color().unwrap_or(data.color()));
 
                 verify!(ctxt.disable(Context::CULL_FACE));
-                ctxt.point_size(data.points_size());
-                if verify!(ctxt.polygon_mode(Context::FRONT_AND_BACK, Context::POINT)) {
+                ignore!(ctxt.line_width(data.lines_width()));
+
+                if verify!(ctxt.polygon_mode(Context::FRONT_AND_BACK, Context::LINE)) {
                     verify!(ctxt.draw_elements(
                         Context::TRIANGLES,
                         mesh.num_pts() as i32,
                         Context::UNSIGNED_SHORT,
                         0
                     ));
-                } else {
+                } else if mesh.bind_edges() {
                     verify!(ctxt.draw_elements(
-                        Context::POINTS,
-                        mesh.num_pts() as i32,
+                        Context::LINES,
+                        mesh.num_pts() as i32 * 2,
                         Context::UNSIGNED_SHORT,
                         0
                     ));
                 }
-                ctxt.point_size(1.0);
+
+                ctxt.line_width(1.0);
             }
         }
 
         mesh.unbind();
         self.deactivate();
+
+        if data.lines_width() == 0.0 {
+            return;
+        }
+
+        if let Some(wireframe) = mesh.wireframe() {
+            let model = transform.to_homogeneous() * Matrix4::new_nonuniform_scaling(scale);
+            let style = LineStyle {
+                cap: LineCap::Round,
+                join: LineJoin::Round,
+                color: *data.lines_color().unwrap_or(data.color()),
+                width_scale: data.lines_width(),
+                ..LineStyle::default()
+            };
+
+            self.lines.activate(&model, &style);
+
+            {
+                let (proj, view) = self.lines.proj_and_view();
+                camera.upload(pass, proj, view);
+            }
+
+            self.lines.draw(wireframe);
+            self.lines.deactivate();
+        }
     }
 }
 
@@ -1,5 +1,7 @@
 use crate::context::Context;
 use crate::planar_camera::PlanarCamera;
+use crate::renderer::line_effect::{LineEffect, LineStyle};
+use crate::renderer::{LineCap, LineJoin};
 use crate::resource::PlanarMaterial;
 use crate::resource::{Effect, PlanarMesh, ShaderAttribute, ShaderUniform};
 use crate::scene::PlanarObjectData;
@@ -18,6 +20,7 @@ pub struct PlanarObjectMaterial {
     model: ShaderUniform<Matrix3<f32>>,
     view: ShaderUniform<Matrix3<f32>>,
     proj: ShaderUniform<Matrix3<f32>>,
+    lines: LineEffect<Matrix3<f32>>,
 }
 
 impl PlanarObjectMaterial {
@@ -38,6 +41,7 @@ impl PlanarObjectMaterial {
             view: effect.get_uniform("view").unwrap(),
             proj: effect.get_uniform("proj").unwrap(),
             effect: effect,
+            lines: LineEffect::new_planar(),
         }
     }
 
@@ -102,14 +106,12 @@ impl PlanarMaterial for PlanarObjectMaterial {
                 ));
             }
 
-            if data.lines_width() != 0.0 {
-                self.color
-                    .upload(data.lines_color().unwrap_or(data.color()));
+            if data.points_size() != 0.0 {
+                self.color.upload(data.color());
 
                 verify!(ctxt.disable(Context::CULL_FACE));
-                ignore!(ctxt.line_width(data.lines_width()));
-
-                if verify!(ctxt.polygon_mode(Context::FRONT_AND_BACK, Context::LINE)) {
+                ctxt.point_size(data.points_size());
+                if verify!(ctxt.polygon_mode(Context::FRONT_AND_BACK, Context::POINT)) {
                     verify!(ctxt.draw_elements(
                         Context::TRIANGLES,
                         mesh.num_pts() as i32,
@@ -117,44 +119,72 @@ impl PlanarMaterial for PlanarObjectMaterial {
                         0
                     ));
                 } else {
-                    mesh.bind_edges();
                     verify!(ctxt.draw_elements(
-                        Context::LINES,
-                        mesh.num_pts() as i32 * 2,
+                        Context::POINTS,
+                        mesh.num_pts() as i32,
                         Context::UNSIGNED_SHORT,
                         0
                     ));
                 }
-
-                ctxt.line_width(1.0);
+                ctxt.point_size(1.0);
             }
 
-            if data.points_size() != 0.0 {
-                self.color.upload(data.color());
+            // The wireframe quads cannot be built once the mesh data were unloaded from RAM, so
+            // the GL lines are used instead.
+            if data.lines_width() != 0.0 && mesh.wireframe().is_none() {
+                self.color
+                    .upload(data.lines_color().unwrap_or(data.color()));
 
                 verify!(ctxt.disable(Context::CULL_FACE));
-                ctxt.point_size(data.points_size());
-                if verify!(ctxt.polygon_mode(Context::FRONT_AND_BACK, Context::POINT)) {
+                ignore!(ctxt.line_width(data.lines_width()));
+
+                if verify!(ctxt.polygon_mode(Context::FRONT_AND_BACK, Context::LINE)) {
                     verify!(ctxt.draw_elements(
                         Context::TRIANGLES,
                         mesh.num_pts() as i32,
                         Context::UNSIGNED_SHORT,
                         0
                     ));
-                } else {
+                } else if mesh.bind_edges() {
                     verify!(ctxt.draw_elements(
-                        Context::POINTS,
-                        mesh.num_pts() as i32,
+                        Context::LINES,
+                        mesh.num_pts() as i32 * 2,
                         Context::UNSIGNED_SHORT,
                         0
                     ));
                 }
-                ctxt.point_size(1.0);
+
+                ctxt.line_width(1.0);
             }
         }
 
         mesh.unbind();
         self.deactivate();
+
+        if data.lines_width() == 0.0 {
+            return;
+        }
+
+        if let Some(wireframe) = mesh.wireframe() {
+            let model = model.to_homogeneous() * Matrix3::new_nonuniform_scaling(scale);
+            let style = LineStyle {
+                cap: LineCap::Round,
+                join: LineJoin::Round,
+                color: *data.lines_color().unwrap_or(data.color()),
+                width_scale: data.lines_width(),
+                ..LineStyle::default()
+            };
+
+            self.lines.activate(&model, &style);
+
+            {
+                let (proj, view) = self.lines.proj_and_view();
+                camera.upload(proj, view);
+            }
+
+            self.lines.draw(wireframe);
+            self.lines.deactivate();
+        }
     }
 }
 
@@ -1,5 +1,7 @@
 use crate::camera::Camera;
-use crate::event::{Action, Key, Modifiers, MouseButton, WindowEvent};
+use crate::event::{
+    camera_actions, Action, Gesture, GestureRecognizer, Key, Modifiers, MouseButton, WindowEvent,
+};
 use crate::resource::ShaderUniform;
 use crate::window::Canvas;
 use na::{self, Isometry3, Matrix4, Perspective3, Point3, Unit, UnitQuaternion, Vector2, Vector3};
@@ -15,6 +17,13 @@ use std::f32;
 /// direction
 /// * Scroll in/out - zoom in/out
 /// * Enter key - set the focus point to the origin
+/// * One finger drag - rotates the camera around the focus point
+/// * Two fingers drag - translates the focus point
+/// * Pinch - zoom in/out
+/// * Two fingers rotation - rotates the camera around the up axis
+///
+/// The mouse buttons, modifiers and keys are replaced by the actions and axes of
+/// `camera_actions` bound in the action map of the window, if any.
 #[derive(Clone, Debug)]
 pub struct ArcBall {
     /// The focus point.
@@ -52,6 +61,7 @@ pub struct ArcBall {
     proj_view: Matrix4<f32>,
     inverse_proj_view: Matrix4<f32>,
     last_cursor_pos: Vector2<f32>,
+    gestures: GestureRecognizer,
     coord_system: CoordSystemRh,
 }
 
@@ -92,6 +102,7 @@ impl ArcBall {
             proj_view: na::zero(),
             inverse_proj_view: na::zero(),
             last_cursor_pos: na::zero(),
+            gestures: GestureRecognizer::new(),
             coord_system: CoordSystemRh::from_up_axis(Vector3::y_axis()),
         };
 
@@ -323,6 +334,56 @@ impl ArcBall {
         self.update_projviews();
     }
 
+    fn is_rotating(&self, canvas: &Canvas, modifiers: Modifiers) -> bool {
+        let actions = canvas.action_map();
+
+        if actions.is_bound(camera_actions::ROTATE) {
+            actions.is_active(camera_actions::ROTATE)
+        } else if let Some(rotate_button) = self.rotate_button {
+            canvas.get_mouse_button(rotate_button) == Action::Press
+                && self
+                    .rotate_modifiers
+                    .map(|m| m == modifiers)
+                    .unwrap_or(true)
+        } else {
+            false
+        }
+    }
+
+    fn is_dragging(&self, canvas: &Canvas, modifiers: Modifiers) -> bool {
+        let actions = canvas.action_map();
+
+        if actions.is_bound(camera_actions::PAN) {
+            actions.is_active(camera_actions::PAN)
+        } else if let Some(drag_button) = self.drag_button {
+            canvas.get_mouse_button(drag_button) == Action::Press
+                && self.drag_modifiers.map(|m| m == modifiers).unwrap_or(true)
+        } else {
+            false
+        }
+    }
+
+    fn handle_gesture(&mut self, gesture: Gesture) {
+        match gesture {
+            Gesture::Drag(dx, dy) => {
+                self.handle_left_button_displacement(&Vector2::new(dx as f32, dy as f32))
+            }
+            Gesture::Pan(dx, dy) => {
+                self.handle_right_button_displacement(&Vector2::new(dx as f32, dy as f32))
+            }
+            Gesture::Pinch(scale) => {
+                self.dist = self.dist / scale as f32;
+                self.update_restrictions();
+                self.update_projviews();
+            }
+            Gesture::Rotate(angle) => {
+                self.yaw = self.yaw + angle as f32;
+                self.update_projviews();
+            }
+            Gesture::Tap(..) => {}
+        }
+    }
+
     fn update_projviews(&mut self) {
         self.proj = *self.projection.as_matrix();
         self.view = self.view_transform().to_homogeneous();
@@ -374,34 +435,35 @@ impl Camera for ArcBall {
             WindowEvent::CursorPos(x, y, modifiers) => {
                 let curr_pos = Vector2::new(x as f32, y as f32);
 
-                if let Some(rotate_button) = self.rotate_button {
-                    if canvas.get_mouse_button(rotate_button) == Action::Press
-                        && self
-                            .rotate_modifiers
-                            .map(|m| m == modifiers)
-                            .unwrap_or(true)
-                    {
-                        let dpos = curr_pos - self.last_cursor_pos;
-                        self.handle_left_button_displacement(&dpos)
-                    }
+                if self.is_rotating(canvas, modifiers) {
+                    let dpos = curr_pos - self.last_cursor_pos;
+                    self.handle_left_button_displacement(&dpos)
                 }
 
-                if let Some(drag_button) = self.drag_button {
-                    if canvas.get_mouse_button(drag_button) == Action::Press
-                        && self.drag_modifiers.map(|m| m == modifiers).unwrap_or(true)
-                    {
-                        let dpos = curr_pos - self.last_cursor_pos;
-                        self.handle_right_button_displacement(&dpos)
-                    }
+                if self.is_dragging(canvas, modifiers) {
+                    let dpos = curr_pos - self.last_cursor_pos;
+                    self.handle_right_button_displacement(&dpos)
                 }
 
                 self.last_cursor_pos = curr_pos;
             }
-            WindowEvent::Key(key, Action::Press, _) if Some(key) == self.reset_key => {
+            WindowEvent::Key(key, Action::Press, _)
+                if Some(key) == self.reset_key
+                    && !canvas.action_map().is_bound(camera_actions::RESET) =>
+            {
                 self.at = Point3::origin();
                 self.update_projviews();
             }
-            WindowEvent::Scroll(_, off, _) => self.handle_scroll(off as f32),
+            WindowEvent::Scroll(_, off, _)
+                if !canvas.action_map().is_axis_bound(camera_actions::ZOOM) =>
+            {
+                self.handle_scroll(off as f32)
+            }
+            WindowEvent::Touch(..) => {
+                for gesture in self.gestures.handle_event(event) {
+                    self.handle_gesture(gesture)
+                }
+            }
             WindowEvent::FramebufferSize(w, h) => {
                 self.projection.set_aspect(w as f32 / h as f32);
                 self.update_projviews();
@@ -429,7 +491,20 @@ impl Camera for ArcBall {
         self.inverse_proj_view
     }
 
-    fn update(&mut self, _: &Canvas) {}
+    fn update(&mut self, canvas: &Canvas) {
+        let actions = canvas.action_map();
+
+        if actions.just_activated(camera_actions::RESET) {
+            self.at = Point3::origin();
+            self.update_projviews();
+        }
+
+        let zoom = actions.axis(camera_actions::ZOOM);
+
+        if zoom != 0.0 {
+            self.handle_scroll(zoom as f32);
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -1,5 +1,7 @@
 use crate::camera::Camera;
-use crate::event::{Action, Key, MouseButton, WindowEvent};
+use crate::event::{
+    camera_actions, Action, GamepadAxis, Gesture, GestureRecognizer, Key, MouseButton, WindowEvent,
+};
 use crate::resource::ShaderUniform;
 use crate::window::Canvas;
 use na::{
@@ -15,6 +17,16 @@ use std::f32;
 ///   * Right button press + drag - translates the camera position on the plane orthogonal to the
 ///   view direction
 ///   * Scroll in/out - zoom in/out
+///   * One finger drag - look around
+///   * Two fingers drag - translates the camera position on the plane orthogonal to the view
+///   direction
+///   * Pinch - move forward/backward
+///   * Two fingers rotation - look left/right
+///   * Left stick of the bound gamepad, if any - move around
+///   * Right stick of the bound gamepad, if any - look around
+///
+/// The mouse buttons and keys are replaced by the actions and axes of `camera_actions` bound in
+/// the action map of the window, if any.
 #[derive(Debug, Clone)]
 pub struct FirstPerson {
     eye: Point3<f32>,
@@ -30,6 +42,10 @@ pub struct FirstPerson {
     down_key: Option<Key>,
     left_key: Option<Key>,
     right_key: Option<Key>,
+    gamepad: Option<usize>,
+    // The last values of the left and right sticks of the bound gamepad.
+    gamepad_move: Vector2<f32>,
+    gamepad_look: Vector2<f32>,
 
     projection: Perspective3<f32>,
     proj: Matrix4<f32>,
@@ -37,9 +53,13 @@ pub struct FirstPerson {
     proj_view: Matrix4<f32>,
     inverse_proj_view: Matrix4<f32>,
     last_cursor_pos: Vector2<f32>,
+    gestures: GestureRecognizer,
     coord_system: CoordSystemRh,
 }
 
+/// The rotation of a fully tilted gamepad stick per frame, in cursor pixels.
+const GAMEPAD_LOOK_SPEED: f32 = 10.0;
+
 impl FirstPerson {
     /// Creates a first person camera with default sensitivity values.
     pub fn new(eye: Point3<f32>, at: Point3<f32>) -> FirstPerson {
@@ -67,12 +87,16 @@ impl FirstPerson {
             down_key: Some(Key::Down),
             left_key: Some(Key::Left),
             right_key: Some(Key::Right),
+            gamepad: None,
+            gamepad_move: na::zero(),
+            gamepad_look: na::zero(),
             projection: Perspective3::new(800.0 / 600.0, fov, znear, zfar),
             proj: na::zero(),
             view: na::zero(),
             proj_view: na::zero(),
             inverse_proj_view: na::zero(),
             last_cursor_pos: na::zero(),
+            gestures: GestureRecognizer::new(),
             coord_system: CoordSystemRh::from_up_axis(Vector3::y_axis()),
         };
 
@@ -232,6 +256,21 @@ impl FirstPerson {
         self.right_key = None;
     }
 
+    /// The gamepad used to move and rotate the FirstPerson camera.
+    pub fn gamepad(&self) -> Option<usize> {
+        self.gamepad
+    }
+
+    /// Set the gamepad used to move and rotate the FirstPerson camera.
+    /// Use None to disable the gamepad, which is the default.
+    ///
+    /// Gamepad events are only received if the `gamepad` feature is enabled.
+    pub fn rebind_gamepad(&mut self, new_gamepad: Option<usize>) {
+        self.gamepad = new_gamepad;
+        self.gamepad_move = na::zero();
+        self.gamepad_look = na::zero();
+    }
+
     #[doc(hidden)]
     pub fn handle_left_button_displacement(&mut self, dpos: &Vector2<f32>) {
         self.yaw = self.yaw + dpos.x * self.yaw_step;
@@ -263,6 +302,40 @@ impl FirstPerson {
         self.update_projviews();
     }
 
+    /// Moves the camera forward and to the right by the given fractions of the move step.
+    fn move_analog(&mut self, forward: f32, right: f32) {
+        if forward == 0.0 && right == 0.0 {
+            return;
+        }
+
+        let t = self.observer_frame();
+        let mut dir = t * Vector3::z() * forward - t * Vector3::x() * right;
+
+        if dir.norm() > 1.0 {
+            dir = dir.normalize();
+        }
+
+        self.translate_mut(&Translation3::from(dir * self.move_step));
+    }
+
+    fn handle_gesture(&mut self, gesture: Gesture) {
+        match gesture {
+            Gesture::Drag(dx, dy) => {
+                self.handle_left_button_displacement(&Vector2::new(dx as f32, dy as f32))
+            }
+            Gesture::Pan(dx, dy) => {
+                self.handle_right_button_displacement(&Vector2::new(dx as f32, dy as f32))
+            }
+            // Spreading the fingers apart by a factor of 2 moves like 10 scroll steps.
+            Gesture::Pinch(scale) => self.handle_scroll((scale.ln() / 2.0f64.ln() * 10.0) as f32),
+            Gesture::Rotate(angle) => {
+                self.yaw = self.yaw + angle as f32;
+                self.update_projviews();
+            }
+            Gesture::Tap(..) => {}
+        }
+    }
+
     fn update_projviews(&mut self) {
         self.view = self.view_transform().to_homogeneous();
         self.proj = *self.projection.as_matrix();
@@ -375,23 +448,43 @@ impl Camera for FirstPerson {
             WindowEvent::CursorPos(x, y, _) => {
                 let curr_pos = Vector2::new(x as f32, y as f32);
 
-                if let Some(rotate_button) = self.rotate_button {
-                    if canvas.get_mouse_button(rotate_button) == Action::Press {
-                        let dpos = curr_pos - self.last_cursor_pos;
-                        self.handle_left_button_displacement(&dpos)
-                    }
+                if check_button_action(canvas, camera_actions::ROTATE, self.rotate_button) {
+                    let dpos = curr_pos - self.last_cursor_pos;
+                    self.handle_left_button_displacement(&dpos)
                 }
 
-                if let Some(drag_button) = self.drag_button {
-                    if canvas.get_mouse_button(drag_button) == Action::Press {
-                        let dpos = curr_pos - self.last_cursor_pos;
-                        self.handle_right_button_displacement(&dpos)
-                    }
+                if check_button_action(canvas, camera_actions::PAN, self.drag_button) {
+                    let dpos = curr_pos - self.last_cursor_pos;
+                    self.handle_right_button_displacement(&dpos)
                 }
 
                 self.last_cursor_pos = curr_pos;
             }
-            WindowEvent::Scroll(_, off, _) => self.handle_scroll(off as f32),
+            WindowEvent::Scroll(_, off, _)
+                if !canvas.action_map().is_axis_bound(camera_actions::ZOOM) =>
+            {
+                self.handle_scroll(off as f32)
+            }
+            WindowEvent::Touch(..) => {
+                for gesture in self.gestures.handle_event(event) {
+                    self.handle_gesture(gesture)
+                }
+            }
+            WindowEvent::GamepadAxis(id, axis, value) if Some(id) == self.gamepad => {
+                let value = value as f32;
+
+                match axis {
+                    GamepadAxis::LeftStickX => self.gamepad_move.x = value,
+                    GamepadAxis::LeftStickY => self.gamepad_move.y = value,
+                    GamepadAxis::RightStickX => self.gamepad_look.x = value,
+                    GamepadAxis::RightStickY => self.gamepad_look.y = value,
+                    _ => {}
+                }
+            }
+            WindowEvent::GamepadDisconnected(id) if Some(id) == self.gamepad => {
+                self.gamepad_move = na::zero();
+                self.gamepad_look = na::zero();
+            }
             WindowEvent::FramebufferSize(w, h) => {
                 self.projection.set_aspect(w as f32 / h as f32);
                 self.update_projviews();
@@ -424,14 +517,52 @@ impl Camera for FirstPerson {
     }
 
     fn update(&mut self, canvas: &Canvas) {
-        let up = check_optional_key_state(canvas, self.up_key, Action::Press);
-        let down = check_optional_key_state(canvas, self.down_key, Action::Press);
-        let right = check_optional_key_state(canvas, self.right_key, Action::Press);
-        let left = check_optional_key_state(canvas, self.left_key, Action::Press);
-        let dir = self.move_dir(up, down, right, left);
-
-        let move_amount = dir * self.move_step;
-        self.translate_mut(&Translation3::from(move_amount));
+        let actions = canvas.action_map();
+
+        if actions.is_axis_bound(camera_actions::MOVE_FORWARD)
+            || actions.is_axis_bound(camera_actions::MOVE_RIGHT)
+        {
+            let forward = actions.axis(camera_actions::MOVE_FORWARD) as f32;
+            let right = actions.axis(camera_actions::MOVE_RIGHT) as f32;
+            self.move_analog(forward, right);
+        } else {
+            let up = check_optional_key_state(canvas, self.up_key, Action::Press);
+            let down = check_optional_key_state(canvas, self.down_key, Action::Press);
+            let right = check_optional_key_state(canvas, self.right_key, Action::Press);
+            let left = check_optional_key_state(canvas, self.left_key, Action::Press);
+            let dir = self.move_dir(up, down, right, left);
+
+            let move_amount = dir * self.move_step;
+            self.translate_mut(&Translation3::from(move_amount));
+        }
+
+        let zoom = actions.axis(camera_actions::ZOOM);
+
+        if zoom != 0.0 {
+            self.handle_scroll(zoom as f32);
+        }
+
+        if !self.gamepad_move.is_zero() {
+            self.move_analog(self.gamepad_move.y, self.gamepad_move.x);
+        }
+
+        if !self.gamepad_look.is_zero() {
+            // The stick is tilted up to look up, while the mouse is moved down.
+            let dpos = Vector2::new(self.gamepad_look.x, -self.gamepad_look.y) * GAMEPAD_LOOK_SPEED;
+            self.handle_left_button_displacement(&dpos);
+        }
+    }
+}
+
+fn check_button_action(canvas: &Canvas, action: &str, button: Option<MouseButton>) -> bool {
+    let actions = canvas.action_map();
+
+    if actions.is_bound(action) {
+        actions.is_active(action)
+    } else if let Some(actual_button) = button {
+        canvas.get_mouse_button(actual_button) == Action::Press
+    } else {
+        false
     }
 }
 
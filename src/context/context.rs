@@ -1,5 +1,7 @@
 #![allow(missing_docs)]
 
+use std::cell::Cell;
+use std::rc::Rc;
 use std::sync::Once;
 
 use crate::context::GLContext as ContextImpl;
@@ -38,6 +40,7 @@ static CONTEXT_INIT: Once = Once::new();
 #[derive(Clone)]
 pub struct Context {
     pub ctxt: ContextImpl,
+    viewport: Rc<Cell<(i32, i32, i32, i32)>>,
 }
 
 impl Context {
@@ -53,6 +56,7 @@ impl Context {
     pub const VERTEX_SHADER: u32 = ContextImpl::VERTEX_SHADER;
     pub const FRAGMENT_SHADER: u32 = ContextImpl::FRAGMENT_SHADER;
     pub const COMPILE_STATUS: u32 = ContextImpl::COMPILE_STATUS;
+    pub const LINK_STATUS: u32 = ContextImpl::LINK_STATUS;
     pub const FRAMEBUFFER: u32 = ContextImpl::FRAMEBUFFER;
     pub const RENDERBUFFER: u32 = ContextImpl::RENDERBUFFER;
     pub const DEPTH_ATTACHMENT: u32 = ContextImpl::DEPTH_ATTACHMENT;
@@ -75,6 +79,15 @@ impl Context {
     pub const REPEAT: u32 = ContextImpl::REPEAT;
     pub const MIRRORED_REPEAT: u32 = ContextImpl::MIRRORED_REPEAT;
     pub const LINEAR_MIPMAP_LINEAR: u32 = ContextImpl::LINEAR_MIPMAP_LINEAR;
+    pub const NEAREST_MIPMAP_NEAREST: u32 = ContextImpl::NEAREST_MIPMAP_NEAREST;
+    pub const LINEAR_MIPMAP_NEAREST: u32 = ContextImpl::LINEAR_MIPMAP_NEAREST;
+    pub const NEAREST_MIPMAP_LINEAR: u32 = ContextImpl::NEAREST_MIPMAP_LINEAR;
+    pub const CLAMP_TO_BORDER: u32 = ContextImpl::CLAMP_TO_BORDER;
+    pub const TEXTURE_BORDER_COLOR: u32 = ContextImpl::TEXTURE_BORDER_COLOR;
+    pub const TEXTURE_MAX_ANISOTROPY: u32 = ContextImpl::TEXTURE_MAX_ANISOTROPY;
+    pub const MAX_TEXTURE_MAX_ANISOTROPY: u32 = ContextImpl::MAX_TEXTURE_MAX_ANISOTROPY;
+    pub const R8: u32 = ContextImpl::R8;
+    pub const R32F: u32 = ContextImpl::R32F;
     pub const TRIANGLES: u32 = ContextImpl::TRIANGLES;
     pub const CULL_FACE: u32 = ContextImpl::CULL_FACE;
     pub const FRONT_AND_BACK: u32 = ContextImpl::FRONT_AND_BACK;
@@ -86,6 +99,7 @@ impl Context {
     pub const TRIANGLE_STRIP: u32 = ContextImpl::TRIANGLE_STRIP;
     pub const COLOR_BUFFER_BIT: u32 = ContextImpl::COLOR_BUFFER_BIT;
     pub const DEPTH_BUFFER_BIT: u32 = ContextImpl::DEPTH_BUFFER_BIT;
+    pub const CW: u32 = ContextImpl::CW;
     pub const CCW: u32 = ContextImpl::CCW;
     pub const DEPTH_TEST: u32 = ContextImpl::DEPTH_TEST;
     pub const SCISSOR_TEST: u32 = ContextImpl::SCISSOR_TEST;
@@ -107,6 +121,7 @@ impl Context {
                 let ctxt = get_ctxt();
                 CONTEXT_SINGLETON = Some(Context {
                     ctxt: ContextImpl::new(ctxt),
+                    viewport: Rc::new(Cell::new((0, 0, 0, 0))),
                 });
             });
         }
@@ -265,6 +280,22 @@ impl Context {
         self.ctxt.attach_shader(&program.0, &shader.0)
     }
 
+    pub fn detach_shader(&self, program: &Program, shader: &Shader) {
+        self.ctxt.detach_shader(&program.0, &shader.0)
+    }
+
+    pub fn bind_attrib_location(&self, program: &Program, index: u32, name: &str) {
+        self.ctxt.bind_attrib_location(&program.0, index, name)
+    }
+
+    pub fn get_program_link_status(&self, program: &Program) -> bool {
+        self.ctxt.get_program_link_status(&program.0)
+    }
+
+    pub fn get_program_info_log(&self, program: &Program) -> Option<String> {
+        self.ctxt.get_program_info_log(&program.0)
+    }
+
     pub fn get_shader_parameter_int(&self, shader: &Shader, pname: GLenum) -> Option<i32> {
         self.ctxt.get_shader_parameter_int(&shader.0, pname)
     }
@@ -305,9 +336,15 @@ impl Context {
     }
 
     pub fn viewport(&self, x: i32, y: i32, width: i32, height: i32) {
+        self.viewport.set((x, y, width, height));
         self.ctxt.viewport(x, y, width, height)
     }
 
+    /// The last viewport set with `Context::viewport`, as `(x, y, width, height)`.
+    pub fn current_viewport(&self) -> (i32, i32, i32, i32) {
+        self.viewport.get()
+    }
+
     pub fn scissor(&self, x: i32, y: i32, width: i32, height: i32) {
         self.ctxt.scissor(x, y, width, height)
     }
@@ -421,6 +458,29 @@ impl Context {
         )
     }
 
+    pub fn tex_image2df(
+        &self,
+        target: GLenum,
+        level: i32,
+        internalformat: i32,
+        width: i32,
+        height: i32,
+        border: i32,
+        format: GLenum,
+        pixels: Option<&[f32]>,
+    ) {
+        self.ctxt.tex_image2df(
+            target,
+            level,
+            internalformat,
+            width,
+            height,
+            border,
+            format,
+            pixels,
+        )
+    }
+
     pub fn tex_sub_image2d(
         &self,
         target: GLenum,
@@ -437,10 +497,46 @@ impl Context {
         )
     }
 
+    pub fn tex_sub_image2df(
+        &self,
+        target: GLenum,
+        level: i32,
+        xoffset: i32,
+        yoffset: i32,
+        width: i32,
+        height: i32,
+        format: GLenum,
+        pixels: Option<&[f32]>,
+    ) {
+        self.ctxt.tex_sub_image2df(
+            target, level, xoffset, yoffset, width, height, format, pixels,
+        )
+    }
+
     pub fn tex_parameteri(&self, target: GLenum, pname: GLenum, param: i32) {
         self.ctxt.tex_parameteri(target, pname, param)
     }
 
+    pub fn tex_parameterf(&self, target: GLenum, pname: GLenum, param: f32) {
+        self.ctxt.tex_parameterf(target, pname, param)
+    }
+
+    pub fn tex_parameterfv(&self, target: GLenum, pname: GLenum, params: &[f32]) {
+        self.ctxt.tex_parameterfv(target, pname, params)
+    }
+
+    pub fn get_tex_parameteri(&self, target: GLenum, pname: GLenum) -> i32 {
+        self.ctxt.get_tex_parameteri(target, pname)
+    }
+
+    pub fn generate_mipmap(&self, target: GLenum) {
+        self.ctxt.generate_mipmap(target)
+    }
+
+    pub fn get_parameteri(&self, pname: GLenum) -> i32 {
+        self.ctxt.get_parameteri(pname)
+    }
+
     pub fn is_texture(&self, texture: Option<&Texture>) -> bool {
         self.ctxt.is_texture(texture.map(|e| &e.0))
     }
@@ -532,11 +628,7 @@ impl Context {
             .blend_func_separate(src_rgb, dst_rgb, src_alpha, dst_alpha)
     }
 
-    pub fn blend_func(
-        &self,
-        sfactor: GLenum,
-        dfactor: GLenum,
-    ) {
+    pub fn blend_func(&self, sfactor: GLenum, dfactor: GLenum) {
         self.ctxt.blend_func(sfactor, dfactor)
     }
 }
@@ -554,6 +646,7 @@ pub(crate) trait AbstractContextConst {
     const VERTEX_SHADER: u32;
     const FRAGMENT_SHADER: u32;
     const COMPILE_STATUS: u32;
+    const LINK_STATUS: u32;
     const FRAMEBUFFER: u32;
     const RENDERBUFFER: u32;
     const DEPTH_ATTACHMENT: u32;
@@ -576,6 +669,15 @@ pub(crate) trait AbstractContextConst {
     const REPEAT: u32;
     const MIRRORED_REPEAT: u32;
     const LINEAR_MIPMAP_LINEAR: u32;
+    const NEAREST_MIPMAP_NEAREST: u32;
+    const LINEAR_MIPMAP_NEAREST: u32;
+    const NEAREST_MIPMAP_LINEAR: u32;
+    const CLAMP_TO_BORDER: u32;
+    const TEXTURE_BORDER_COLOR: u32;
+    const TEXTURE_MAX_ANISOTROPY: u32;
+    const MAX_TEXTURE_MAX_ANISOTROPY: u32;
+    const R8: u32;
+    const R32F: u32;
     const TRIANGLES: u32;
     const CULL_FACE: u32;
     const FRONT_AND_BACK: u32;
@@ -587,6 +689,7 @@ pub(crate) trait AbstractContextConst {
     const TRIANGLE_STRIP: u32;
     const COLOR_BUFFER_BIT: u32;
     const DEPTH_BUFFER_BIT: u32;
+    const CW: u32;
     const CCW: u32;
     const DEPTH_TEST: u32;
     const SCISSOR_TEST: u32;
@@ -662,6 +765,10 @@ pub(crate) trait AbstractContext {
     fn link_program(&self, program: &Self::Program);
     fn use_program(&self, program: Option<&Self::Program>);
     fn attach_shader(&self, program: &Self::Program, shader: &Self::Shader);
+    fn detach_shader(&self, program: &Self::Program, shader: &Self::Shader);
+    fn bind_attrib_location(&self, program: &Self::Program, index: u32, name: &str);
+    fn get_program_link_status(&self, program: &Self::Program) -> bool;
+    fn get_program_info_log(&self, program: &Self::Program) -> Option<String>;
     fn get_shader_parameter_int(&self, shader: &Self::Shader, pname: GLenum) -> Option<i32>;
     fn get_shader_info_log(&self, shader: &Self::Shader) -> Option<String>;
     fn vertex_attrib_pointer(
@@ -743,7 +850,34 @@ pub(crate) trait AbstractContext {
         format: GLenum,
         pixels: Option<&[u8]>,
     );
+    fn tex_image2df(
+        &self,
+        target: GLenum,
+        level: i32,
+        internalformat: i32,
+        width: i32,
+        height: i32,
+        border: i32,
+        format: GLenum,
+        pixels: Option<&[f32]>,
+    );
+    fn tex_sub_image2df(
+        &self,
+        target: GLenum,
+        level: i32,
+        xoffset: i32,
+        yoffset: i32,
+        width: i32,
+        height: i32,
+        format: GLenum,
+        pixels: Option<&[f32]>,
+    );
     fn tex_parameteri(&self, target: GLenum, pname: GLenum, param: i32);
+    fn tex_parameterf(&self, target: GLenum, pname: GLenum, param: f32);
+    fn tex_parameterfv(&self, target: GLenum, pname: GLenum, params: &[f32]);
+    fn get_tex_parameteri(&self, target: GLenum, pname: GLenum) -> i32;
+    fn generate_mipmap(&self, target: GLenum);
+    fn get_parameteri(&self, pname: GLenum) -> i32;
     fn is_texture(&self, texture: Option<&Self::Texture>) -> bool;
     fn create_texture(&self) -> Option<Self::Texture>;
     fn delete_texture(&self, texture: Option<&Self::Texture>);
@@ -786,9 +920,5 @@ pub(crate) trait AbstractContext {
         dst_alpha: GLenum,
     );
 
-    fn blend_func(
-        &self,
-        sfactor: GLenum,
-        dfactor: GLenum,
-    );
+    fn blend_func(&self, sfactor: GLenum, dfactor: GLenum);
 }
@@ -39,6 +39,7 @@ impl AbstractContextConst for GLContext {
     const VERTEX_SHADER: u32 = glow::VERTEX_SHADER;
     const FRAGMENT_SHADER: u32 = glow::FRAGMENT_SHADER;
     const COMPILE_STATUS: u32 = glow::COMPILE_STATUS;
+    const LINK_STATUS: u32 = glow::LINK_STATUS;
     const FRAMEBUFFER: u32 = glow::FRAMEBUFFER;
     const RENDERBUFFER: u32 = glow::RENDERBUFFER;
     const DEPTH_ATTACHMENT: u32 = glow::DEPTH_ATTACHMENT;
@@ -61,6 +62,21 @@ impl AbstractContextConst for GLContext {
     const REPEAT: u32 = glow::REPEAT;
     const MIRRORED_REPEAT: u32 = glow::MIRRORED_REPEAT;
     const LINEAR_MIPMAP_LINEAR: u32 = glow::LINEAR_MIPMAP_LINEAR;
+    const NEAREST_MIPMAP_NEAREST: u32 = glow::NEAREST_MIPMAP_NEAREST;
+    const LINEAR_MIPMAP_NEAREST: u32 = glow::LINEAR_MIPMAP_NEAREST;
+    const NEAREST_MIPMAP_LINEAR: u32 = glow::NEAREST_MIPMAP_LINEAR;
+    const CLAMP_TO_BORDER: u32 = glow::CLAMP_TO_BORDER;
+    const TEXTURE_BORDER_COLOR: u32 = glow::TEXTURE_BORDER_COLOR;
+    const TEXTURE_MAX_ANISOTROPY: u32 = glow::TEXTURE_MAX_ANISOTROPY;
+    const MAX_TEXTURE_MAX_ANISOTROPY: u32 = glow::MAX_TEXTURE_MAX_ANISOTROPY;
+    #[cfg(not(target_arch = "wasm32"))]
+    const R8: u32 = glow::R8;
+    #[cfg(target_arch = "wasm32")]
+    const R8: u32 = glow::LUMINANCE; // WebGL 1
+    #[cfg(not(target_arch = "wasm32"))]
+    const R32F: u32 = glow::R32F;
+    #[cfg(target_arch = "wasm32")]
+    const R32F: u32 = glow::LUMINANCE; // WebGL 1, with OES_texture_float
     const TRIANGLES: u32 = glow::TRIANGLES;
     const CULL_FACE: u32 = glow::CULL_FACE;
     const FRONT_AND_BACK: u32 = glow::FRONT_AND_BACK;
@@ -69,6 +85,7 @@ impl AbstractContextConst for GLContext {
     const TRIANGLE_STRIP: u32 = glow::TRIANGLE_STRIP;
     const COLOR_BUFFER_BIT: u32 = glow::COLOR_BUFFER_BIT;
     const DEPTH_BUFFER_BIT: u32 = glow::DEPTH_BUFFER_BIT;
+    const CW: u32 = glow::CW;
     const CCW: u32 = glow::CCW;
     const DEPTH_TEST: u32 = glow::DEPTH_TEST;
     const SCISSOR_TEST: u32 = glow::SCISSOR_TEST;
@@ -293,6 +310,22 @@ impl AbstractContext for GLContext {
         unsafe { self.context.attach_shader(*program, *shader) }
     }
 
+    fn detach_shader(&self, program: &Self::Program, shader: &Self::Shader) {
+        unsafe { self.context.detach_shader(*program, *shader) }
+    }
+
+    fn bind_attrib_location(&self, program: &Self::Program, index: u32, name: &str) {
+        unsafe { self.context.bind_attrib_location(*program, index, name) }
+    }
+
+    fn get_program_link_status(&self, program: &Self::Program) -> bool {
+        unsafe { self.context.get_program_link_status(*program) }
+    }
+
+    fn get_program_info_log(&self, program: &Self::Program) -> Option<String> {
+        unsafe { Some(self.context.get_program_info_log(*program)) }
+    }
+
     fn get_shader_parameter_int(&self, shader: &Self::Shader, _pname: GLenum) -> Option<i32> {
         unsafe {
             if self.context.get_shader_compile_status(*shader) {
@@ -500,6 +533,36 @@ impl AbstractContext for GLContext {
         }
     }
 
+    fn tex_image2df(
+        &self,
+        target: GLenum,
+        level: i32,
+        internalformat: i32,
+        width: i32,
+        height: i32,
+        border: i32,
+        format: GLenum,
+        pixels: Option<&[f32]>,
+    ) {
+        unsafe {
+            self.context.tex_image_2d(
+                target,
+                level,
+                internalformat,
+                width,
+                height,
+                border,
+                format,
+                Self::FLOAT,
+                pixels.map(|px| {
+                    let len = px.len() * 4;
+                    let ptr = px.as_ptr() as *const u8;
+                    std::slice::from_raw_parts(ptr, len)
+                }),
+            )
+        }
+    }
+
     fn tex_sub_image2d(
         &self,
         target: GLenum,
@@ -528,10 +591,60 @@ impl AbstractContext for GLContext {
         }
     }
 
+    fn tex_sub_image2df(
+        &self,
+        target: GLenum,
+        level: i32,
+        xoffset: i32,
+        yoffset: i32,
+        width: i32,
+        height: i32,
+        format: GLenum,
+        pixels: Option<&[f32]>,
+    ) {
+        if let Some(pixels) = pixels {
+            unsafe {
+                let bytes =
+                    std::slice::from_raw_parts(pixels.as_ptr() as *const u8, pixels.len() * 4);
+                self.context.tex_sub_image_2d(
+                    target,
+                    level,
+                    xoffset,
+                    yoffset,
+                    width,
+                    height,
+                    format,
+                    Self::FLOAT,
+                    glow::PixelUnpackData::Slice(bytes),
+                )
+            }
+        }
+    }
+
     fn tex_parameteri(&self, target: GLenum, pname: GLenum, param: i32) {
         unsafe { self.context.tex_parameter_i32(target, pname, param) }
     }
 
+    fn tex_parameterf(&self, target: GLenum, pname: GLenum, param: f32) {
+        unsafe { self.context.tex_parameter_f32(target, pname, param) }
+    }
+
+    fn tex_parameterfv(&self, target: GLenum, pname: GLenum, params: &[f32]) {
+        unsafe { self.context.tex_parameter_f32_slice(target, pname, params) }
+    }
+
+    fn get_tex_parameteri(&self, target: GLenum, pname: GLenum) -> i32 {
+        unsafe { self.context.get_tex_parameter_i32(target, pname) }
+    }
+
+    fn generate_mipmap(&self, target: GLenum) {
+        unsafe { self.context.generate_mipmap(target) }
+    }
+
+    fn get_parameteri(&self, pname: GLenum) -> i32 {
+        unsafe { self.context.get_parameter_i32(pname) }
+    }
+
     fn is_texture(&self, texture: Option<&Self::Texture>) -> bool {
         if let Some(t) = texture {
             unsafe { self.context.is_texture(t.clone()) }
@@ -651,11 +764,7 @@ impl AbstractContext for GLContext {
         }
     }
 
-    fn blend_func(
-        &self,
-        sfactor: GLenum,
-        dfactor: GLenum,
-    ) {
+    fn blend_func(&self, sfactor: GLenum, dfactor: GLenum) {
         // TODO(gijs) : Is this really needed
         // unsafe { gl::BlendFunc(sfactor, dfactor) }
     }
@@ -6,7 +6,13 @@ macro_rules! verify(
         {
             let res = $e;
             #[cfg(not(target_arch = "wasm32"))]
-            { assert_eq!(crate::context::Context::get().get_error(), 0); }
+            {
+                let error = crate::context::Context::get().get_error();
+
+                if error != 0 {
+                    eprintln!("OpenGL error {:#x} at {}:{}.", error, file!(), line!());
+                }
+            }
             res
         }
     }
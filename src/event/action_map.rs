@@ -0,0 +1,318 @@
+//! Named actions and axes bound to inputs.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::event::{Action, GamepadAxis, GamepadButton, Key, Modifiers, MouseButton, WindowEvent};
+
+/// The names of the actions and axes used by the builtin cameras.
+///
+/// When the action map of a window binds one of them, the cameras use it instead of their own
+/// key and button bindings.
+pub mod camera_actions {
+    /// Action held to rotate the camera with the cursor.
+    pub const ROTATE: &str = "camera_rotate";
+    /// Action held to translate the camera with the cursor.
+    pub const PAN: &str = "camera_pan";
+    /// Action resetting the focus point of the camera.
+    pub const RESET: &str = "camera_reset";
+    /// Axis zooming the camera, with the same scale as the scroll offset.
+    pub const ZOOM: &str = "camera_zoom";
+    /// Axis moving the camera forward, or backward if negative.
+    pub const MOVE_FORWARD: &str = "camera_move_forward";
+    /// Axis moving the camera to the right, or to the left if negative.
+    pub const MOVE_RIGHT: &str = "camera_move_right";
+}
+
+/// A button bound to an action.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub enum Binding {
+    /// A key, with the exact modifiers which must be held with it, or `None` for any modifiers.
+    Key(Key, Option<Modifiers>),
+    /// A mouse button, with the exact modifiers which must be held with it, or `None` for any
+    /// modifiers.
+    MouseButton(MouseButton, Option<Modifiers>),
+    /// A button of any gamepad.
+    GamepadButton(GamepadButton),
+}
+
+/// An input bound to an axis.
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub enum AxisBinding {
+    /// -1.0 while the first button is active and 1.0 while the second one is.
+    Buttons(Binding, Binding),
+    /// The horizontal scroll offset received during the frame.
+    ScrollX,
+    /// The vertical scroll offset received during the frame.
+    ScrollY,
+    /// The horizontal displacement of the cursor during the frame, in pixels.
+    CursorX,
+    /// The vertical displacement of the cursor during the frame, in pixels.
+    CursorY,
+    /// An axis of any gamepad.
+    GamepadAxis(GamepadAxis),
+}
+
+/// The state of the inputs, updated by the window events.
+#[derive(Clone, Debug)]
+struct InputState {
+    keys: HashSet<Key>,
+    mouse_buttons: HashSet<MouseButton>,
+    gamepad_buttons: HashSet<GamepadButton>,
+    gamepad_axes: HashMap<GamepadAxis, f64>,
+    modifiers: Modifiers,
+    cursor_pos: Option<(f64, f64)>,
+    cursor_delta: (f64, f64),
+    scroll: (f64, f64),
+    // The actions active at the beginning of the frame.
+    was_active: HashSet<String>,
+}
+
+impl Default for InputState {
+    fn default() -> Self {
+        InputState {
+            keys: HashSet::new(),
+            mouse_buttons: HashSet::new(),
+            gamepad_buttons: HashSet::new(),
+            gamepad_axes: HashMap::new(),
+            modifiers: Modifiers::empty(),
+            cursor_pos: None,
+            cursor_delta: (0.0, 0.0),
+            scroll: (0.0, 0.0),
+            was_active: HashSet::new(),
+        }
+    }
+}
+
+/// A set of named actions and axes, bound to keys, mouse buttons, scrolling, cursor motion and
+/// gamepad inputs.
+///
+/// Every window has an action map, updated by the events it receives at each frame and given to
+/// the cameras through `Canvas::action_map`. Since it implements `Serialize` and `Deserialize`,
+/// its bindings can be loaded from a configuration file with any serde format, and changed for
+/// the whole application with `Window::set_action_map`. The names of the actions and axes used by
+/// the builtin cameras are listed in `camera_actions`.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ActionMap {
+    actions: HashMap<String, Vec<Binding>>,
+    axes: HashMap<String, Vec<AxisBinding>>,
+    #[serde(skip)]
+    state: InputState,
+}
+
+impl ActionMap {
+    /// Creates an action map without any binding.
+    pub fn new() -> ActionMap {
+        ActionMap::default()
+    }
+
+    /// Creates an action map binding the actions of the builtin cameras to their default inputs.
+    pub fn camera_defaults() -> ActionMap {
+        let mut res = ActionMap::new();
+
+        res.bind(
+            camera_actions::ROTATE,
+            Binding::MouseButton(MouseButton::Button1, None),
+        );
+        res.bind(
+            camera_actions::PAN,
+            Binding::MouseButton(MouseButton::Button2, None),
+        );
+        res.bind(camera_actions::RESET, Binding::Key(Key::Return, None));
+        res.bind_axis(camera_actions::ZOOM, AxisBinding::ScrollY);
+        res.bind_axis(
+            camera_actions::MOVE_FORWARD,
+            AxisBinding::Buttons(Binding::Key(Key::Down, None), Binding::Key(Key::Up, None)),
+        );
+        res.bind_axis(
+            camera_actions::MOVE_RIGHT,
+            AxisBinding::Buttons(
+                Binding::Key(Key::Left, None),
+                Binding::Key(Key::Right, None),
+            ),
+        );
+
+        res
+    }
+
+    /// Binds an input to an action, in addition to its other bindings.
+    pub fn bind(&mut self, action: &str, binding: Binding) {
+        self.actions
+            .entry(action.to_string())
+            .or_insert_with(Vec::new)
+            .push(binding)
+    }
+
+    /// Binds an input to an axis, in addition to its other bindings.
+    pub fn bind_axis(&mut self, axis: &str, binding: AxisBinding) {
+        self.axes
+            .entry(axis.to_string())
+            .or_insert_with(Vec::new)
+            .push(binding)
+    }
+
+    /// Removes all the bindings of an action.
+    pub fn unbind(&mut self, action: &str) {
+        let _ = self.actions.remove(action);
+    }
+
+    /// Removes all the bindings of an axis.
+    pub fn unbind_axis(&mut self, axis: &str) {
+        let _ = self.axes.remove(axis);
+    }
+
+    /// The inputs bound to an action.
+    pub fn bindings(&self, action: &str) -> &[Binding] {
+        self.actions.get(action).map(|b| &b[..]).unwrap_or(&[])
+    }
+
+    /// The inputs bound to an axis.
+    pub fn axis_bindings(&self, axis: &str) -> &[AxisBinding] {
+        self.axes.get(axis).map(|b| &b[..]).unwrap_or(&[])
+    }
+
+    /// Whether at least one input is bound to an action.
+    pub fn is_bound(&self, action: &str) -> bool {
+        !self.bindings(action).is_empty()
+    }
+
+    /// Whether at least one input is bound to an axis.
+    pub fn is_axis_bound(&self, axis: &str) -> bool {
+        !self.axis_bindings(axis).is_empty()
+    }
+
+    /// Whether one of the inputs bound to an action is currently active.
+    pub fn is_active(&self, action: &str) -> bool {
+        self.bindings(action)
+            .iter()
+            .any(|b| self.is_binding_active(b))
+    }
+
+    /// Whether an action became active during the current frame.
+    pub fn just_activated(&self, action: &str) -> bool {
+        self.is_active(action) && !self.state.was_active.contains(action)
+    }
+
+    /// Whether an action stopped being active during the current frame.
+    pub fn just_deactivated(&self, action: &str) -> bool {
+        !self.is_active(action) && self.state.was_active.contains(action)
+    }
+
+    /// The value of an axis, which is the sum of the values of its bindings.
+    pub fn axis(&self, axis: &str) -> f64 {
+        self.axis_bindings(axis)
+            .iter()
+            .map(|b| self.axis_binding_value(b))
+            .sum()
+    }
+
+    /// Starts a new frame, resetting the scroll offsets and cursor displacement.
+    ///
+    /// This is called by the window before it handles the events of a frame.
+    pub fn begin_frame(&mut self) {
+        let was_active = self
+            .actions
+            .keys()
+            .filter(|a| self.is_active(a))
+            .cloned()
+            .collect();
+
+        self.state.was_active = was_active;
+        self.state.cursor_delta = (0.0, 0.0);
+        self.state.scroll = (0.0, 0.0);
+    }
+
+    /// Updates the state of the inputs with an event.
+    ///
+    /// This is called by the window for each event it handles.
+    pub fn handle_event(&mut self, event: &WindowEvent) {
+        let state = &mut self.state;
+
+        match *event {
+            WindowEvent::Key(key, action, modifiers) => {
+                state.modifiers = modifiers;
+                let _ = match action {
+                    Action::Press => state.keys.insert(key),
+                    Action::Release => state.keys.remove(&key),
+                };
+            }
+            WindowEvent::MouseButton(button, action, modifiers) => {
+                state.modifiers = modifiers;
+                let _ = match action {
+                    Action::Press => state.mouse_buttons.insert(button),
+                    Action::Release => state.mouse_buttons.remove(&button),
+                };
+            }
+            WindowEvent::CursorPos(x, y, modifiers) => {
+                state.modifiers = modifiers;
+
+                if let Some((last_x, last_y)) = state.cursor_pos {
+                    state.cursor_delta.0 += x - last_x;
+                    state.cursor_delta.1 += y - last_y;
+                }
+
+                state.cursor_pos = Some((x, y));
+            }
+            WindowEvent::Scroll(x, y, modifiers) => {
+                state.modifiers = modifiers;
+                state.scroll.0 += x;
+                state.scroll.1 += y;
+            }
+            WindowEvent::GamepadButton(_, button, action) => {
+                let _ = match action {
+                    Action::Press => state.gamepad_buttons.insert(button),
+                    Action::Release => state.gamepad_buttons.remove(&button),
+                };
+            }
+            WindowEvent::GamepadAxis(_, axis, value) => {
+                let _ = state.gamepad_axes.insert(axis, value);
+            }
+            WindowEvent::GamepadDisconnected(_) => {
+                state.gamepad_buttons.clear();
+                state.gamepad_axes.clear();
+            }
+            WindowEvent::Focus(false) => {
+                // The releases happening while the window is not focused are not received.
+                state.keys.clear();
+                state.mouse_buttons.clear();
+            }
+            _ => {}
+        }
+    }
+
+    fn is_binding_active(&self, binding: &Binding) -> bool {
+        let modifiers_match = |m: Option<Modifiers>| m.map_or(true, |m| m == self.state.modifiers);
+
+        match *binding {
+            Binding::Key(key, m) => self.state.keys.contains(&key) && modifiers_match(m),
+            Binding::MouseButton(button, m) => {
+                self.state.mouse_buttons.contains(&button) && modifiers_match(m)
+            }
+            Binding::GamepadButton(button) => self.state.gamepad_buttons.contains(&button),
+        }
+    }
+
+    fn axis_binding_value(&self, binding: &AxisBinding) -> f64 {
+        match *binding {
+            AxisBinding::Buttons(ref negative, ref positive) => {
+                let mut res = 0.0;
+
+                if self.is_binding_active(negative) {
+                    res -= 1.0;
+                }
+
+                if self.is_binding_active(positive) {
+                    res += 1.0;
+                }
+
+                res
+            }
+            AxisBinding::ScrollX => self.state.scroll.0,
+            AxisBinding::ScrollY => self.state.scroll.1,
+            AxisBinding::CursorX => self.state.cursor_delta.0,
+            AxisBinding::CursorY => self.state.cursor_delta.1,
+            AxisBinding::GamepadAxis(axis) => {
+                self.state.gamepad_axes.get(&axis).cloned().unwrap_or(0.0)
+            }
+        }
+    }
+}
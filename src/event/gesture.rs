@@ -0,0 +1,177 @@
+//! Recognition of touch gestures.
+
+use std::f64;
+
+use instant::Instant;
+use na::{Point2, Vector2};
+
+use crate::event::{TouchAction, WindowEvent};
+
+/// The distance, in pixels, a finger may move and still be considered a tap.
+const TAP_MAX_DISTANCE: f64 = 10.0;
+/// The time, in seconds, a finger may stay on the screen and still be considered a tap.
+const TAP_MAX_DURATION: f64 = 0.3;
+
+/// A gesture recognized by a `GestureRecognizer`.
+///
+/// Displacements are in pixels, with the same orientation as the cursor position.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Gesture {
+    /// A finger touched the screen briefly, without moving, at the given position.
+    Tap(f64, f64),
+    /// A single finger moved by the given displacement.
+    Drag(f64, f64),
+    /// The center of two fingers moved by the given displacement.
+    Pan(f64, f64),
+    /// The distance between two fingers was multiplied by the given factor, greater than 1.0 when
+    /// the fingers move apart.
+    Pinch(f64),
+    /// The line between two fingers rotated by the given angle, in radians, clockwise on the
+    /// screen.
+    Rotate(f64),
+}
+
+/// A finger which may be tapping the screen.
+#[derive(Clone, Debug)]
+struct TapCandidate {
+    id: u64,
+    start: Point2<f64>,
+    time: Instant,
+}
+
+/// Turns the touch events of a window into gestures.
+///
+/// The builtin cameras use their own recognizer to map gestures to the same actions as the
+/// mouse. An application can feed it all the events of a window with `handle_event`, which
+/// ignores the events unrelated to touch.
+#[derive(Clone, Debug)]
+pub struct GestureRecognizer {
+    // The fingers on the screen, in the order they touched it.
+    touches: Vec<(u64, Point2<f64>)>,
+    tap: Option<TapCandidate>,
+}
+
+impl Default for GestureRecognizer {
+    fn default() -> Self {
+        GestureRecognizer::new()
+    }
+}
+
+impl GestureRecognizer {
+    /// Creates a recognizer with no finger on the screen.
+    pub fn new() -> GestureRecognizer {
+        GestureRecognizer {
+            touches: Vec::new(),
+            tap: None,
+        }
+    }
+
+    /// The number of fingers currently on the screen.
+    pub fn num_touches(&self) -> usize {
+        self.touches.len()
+    }
+
+    /// Updates the state of this recognizer with an event, and returns the gestures it completes.
+    pub fn handle_event(&mut self, event: &WindowEvent) -> Vec<Gesture> {
+        let mut res = Vec::new();
+
+        if let WindowEvent::Touch(id, x, y, action, _) = *event {
+            let pos = Point2::new(x, y);
+
+            match action {
+                TouchAction::Start => self.touch_started(id, pos),
+                TouchAction::Move => self.touch_moved(id, pos, &mut res),
+                TouchAction::End => self.touch_ended(id, pos, &mut res),
+                TouchAction::Cancel => {
+                    self.touches.retain(|t| t.0 != id);
+                    self.tap = None;
+                }
+            }
+        }
+
+        res
+    }
+
+    fn touch_started(&mut self, id: u64, pos: Point2<f64>) {
+        self.touches.retain(|t| t.0 != id);
+        self.touches.push((id, pos));
+
+        self.tap = if self.touches.len() == 1 {
+            Some(TapCandidate {
+                id,
+                start: pos,
+                time: Instant::now(),
+            })
+        } else {
+            None
+        };
+    }
+
+    fn touch_moved(&mut self, id: u64, pos: Point2<f64>, out: &mut Vec<Gesture>) {
+        let i = match self.touches.iter().position(|t| t.0 == id) {
+            Some(i) => i,
+            None => return,
+        };
+
+        if let Some(tap) = &self.tap {
+            if (pos - tap.start).norm() > TAP_MAX_DISTANCE {
+                self.tap = None;
+            }
+        }
+
+        let old_pos = self.touches[i].1;
+        self.touches[i].1 = pos;
+
+        if self.touches.len() == 1 {
+            let delta = pos - old_pos;
+
+            if delta != Vector2::zeros() {
+                out.push(Gesture::Drag(delta.x, delta.y));
+            }
+        } else if i < 2 {
+            // Only the first two fingers are part of two-finger gestures.
+            let other = self.touches[1 - i].1;
+            let old_dir = other - old_pos;
+            let new_dir = other - pos;
+            let pan = (pos - old_pos) / 2.0;
+
+            if pan != Vector2::zeros() {
+                out.push(Gesture::Pan(pan.x, pan.y));
+            }
+
+            let (old_dist, new_dist) = (old_dir.norm(), new_dir.norm());
+
+            if old_dist > 0.0 && new_dist > 0.0 {
+                if new_dist != old_dist {
+                    out.push(Gesture::Pinch(new_dist / old_dist));
+                }
+
+                let mut angle = new_dir.y.atan2(new_dir.x) - old_dir.y.atan2(old_dir.x);
+
+                if angle > f64::consts::PI {
+                    angle -= f64::consts::PI * 2.0;
+                } else if angle < -f64::consts::PI {
+                    angle += f64::consts::PI * 2.0;
+                }
+
+                // The y axis points down, so a positive angle is clockwise.
+                if angle != 0.0 {
+                    out.push(Gesture::Rotate(angle));
+                }
+            }
+        }
+    }
+
+    fn touch_ended(&mut self, id: u64, pos: Point2<f64>, out: &mut Vec<Gesture>) {
+        self.touches.retain(|t| t.0 != id);
+
+        if let Some(tap) = self.tap.take() {
+            if tap.id == id
+                && (pos - tap.start).norm() <= TAP_MAX_DISTANCE
+                && tap.time.elapsed().as_secs_f64() <= TAP_MAX_DURATION
+            {
+                out.push(Gesture::Tap(pos.x, pos.y));
+            }
+        }
+    }
+}
@@ -1,7 +1,17 @@
 //! Window event handling.
 
+pub use self::action_map::{camera_actions, ActionMap, AxisBinding, Binding};
 pub use self::event_manager::{Event, EventManager, Events};
-pub use self::window_event::{Action, Key, Modifiers, MouseButton, TouchAction, WindowEvent};
+pub use self::gesture::{Gesture, GestureRecognizer};
+pub(crate) use self::recording::{EventRecorder, EventReplayer};
+pub use self::recording::{EventRecording, RecordedEvent};
+pub use self::window_event::{
+    Action, DroppedFile, GamepadAxis, GamepadButton, Key, Modifiers, MouseButton, TouchAction,
+    WindowEvent,
+};
 
+mod action_map;
 mod event_manager;
+mod gesture;
+mod recording;
 mod window_event;
@@ -0,0 +1,156 @@
+//! Recording and replay of window events.
+
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+use instant::Instant;
+
+use crate::event::WindowEvent;
+
+const HEADER: &str = "# kiss3d event recording";
+
+/// A recorded event.
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct RecordedEvent {
+    /// The frame the event was received at, counted from the start of the recording.
+    pub frame: u64,
+    /// The time the event was received at, in seconds from the start of the recording.
+    pub time: f64,
+    /// The event.
+    pub event: WindowEvent,
+}
+
+/// A stream of timestamped window events, recorded by `Window::start_recording` and replayed by
+/// `Window::replay`.
+///
+/// A recording is saved as a text file with one JSON event per line, which can be attached to bug
+/// reports or used as the input of automated tests.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct EventRecording {
+    events: Vec<RecordedEvent>,
+}
+
+impl EventRecording {
+    /// Creates an empty recording.
+    pub fn new() -> EventRecording {
+        EventRecording { events: Vec::new() }
+    }
+
+    /// The recorded events, in the order they were received.
+    pub fn events(&self) -> &[RecordedEvent] {
+        &self.events
+    }
+
+    /// Appends an event to this recording.
+    ///
+    /// The frame and time of the event must not be smaller than those of the last event.
+    pub fn push(&mut self, event: RecordedEvent) {
+        self.events.push(event)
+    }
+
+    /// Loads a recording saved by `EventRecording::save`.
+    pub fn load(path: &Path) -> io::Result<EventRecording> {
+        let file = BufReader::new(File::open(path)?);
+        let mut res = EventRecording::new();
+
+        for (i, line) in file.lines().enumerate() {
+            let line = line?;
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let event = serde_json::from_str(line).map_err(|e| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("invalid event at line {}: {}", i + 1, e),
+                )
+            })?;
+            res.push(event);
+        }
+
+        Ok(res)
+    }
+
+    /// Saves this recording to a file.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let mut file = BufWriter::new(File::create(path)?);
+        writeln!(file, "{}", HEADER)?;
+
+        for event in &self.events {
+            serde_json::to_writer(&mut file, event)?;
+            writeln!(file)?;
+        }
+
+        file.flush()
+    }
+}
+
+/// Records the events received by a window.
+pub(crate) struct EventRecorder {
+    recording: EventRecording,
+    start_time: Instant,
+    start_frame: u64,
+}
+
+impl EventRecorder {
+    pub fn new(frame: u64) -> EventRecorder {
+        EventRecorder {
+            recording: EventRecording::new(),
+            start_time: Instant::now(),
+            start_frame: frame,
+        }
+    }
+
+    pub fn record(&mut self, frame: u64, event: WindowEvent) {
+        self.recording.push(RecordedEvent {
+            frame: frame - self.start_frame,
+            time: self.start_time.elapsed().as_secs_f64(),
+            event,
+        })
+    }
+
+    pub fn finish(self) -> EventRecording {
+        self.recording
+    }
+}
+
+/// Feeds the events of a recording back to a window, at the frames they were recorded at.
+pub(crate) struct EventReplayer {
+    recording: EventRecording,
+    next: usize,
+    start_frame: u64,
+}
+
+impl EventReplayer {
+    pub fn new(recording: EventRecording, frame: u64) -> EventReplayer {
+        EventReplayer {
+            recording,
+            next: 0,
+            start_frame: frame,
+        }
+    }
+
+    /// The events due at the given frame, which were not returned yet.
+    pub fn due_events(&mut self, frame: u64) -> Vec<WindowEvent> {
+        let frame = frame - self.start_frame;
+        let start = self.next;
+
+        while self.next < self.recording.events.len()
+            && self.recording.events[self.next].frame <= frame
+        {
+            self.next += 1;
+        }
+
+        self.recording.events[start..self.next]
+            .iter()
+            .map(|e| e.event)
+            .collect()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.next == self.recording.events.len()
+    }
+}
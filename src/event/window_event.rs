@@ -1,5 +1,7 @@
 #![allow(missing_docs)]
 
+use std::path::PathBuf;
+
 #[derive(Copy, Clone, PartialEq, PartialOrd, Debug, Serialize, Deserialize)]
 pub enum WindowEvent {
     Pos(i32, i32),
@@ -17,6 +19,28 @@ pub enum WindowEvent {
     Char(char),
     CharModifiers(char, Modifiers),
     Touch(u64, f64, f64, TouchAction, Modifiers),
+    GamepadConnected(usize),
+    GamepadDisconnected(usize),
+    GamepadAxis(usize, GamepadAxis, f64),
+    GamepadButton(usize, GamepadButton, Action),
+    /// Files were dropped onto the window, see `Window::take_dropped_files`.
+    DroppedFile,
+    /// Files are being dragged over the window, see `Window::hovered_files`.
+    ///
+    /// This is only sent on native platforms.
+    HoveredFile,
+    /// The files dragged over the window left it without being dropped.
+    HoveredFileCancelled,
+}
+
+/// A file dropped onto the window, returned by `Window::take_dropped_files`.
+#[derive(Clone, PartialEq, Debug)]
+pub enum DroppedFile {
+    /// The path of a file dropped on native platforms.
+    Path(PathBuf),
+    /// The name and the content of a file dropped on web platforms, where paths are not
+    /// available.
+    Data(String, Vec<u8>),
 }
 
 impl WindowEvent {
@@ -46,6 +70,27 @@ impl WindowEvent {
             _ => false,
         }
     }
+
+    /// Tests if this event is related to a gamepad.
+    pub fn is_gamepad_event(&self) -> bool {
+        match self {
+            WindowEvent::GamepadConnected(..)
+            | WindowEvent::GamepadDisconnected(..)
+            | WindowEvent::GamepadAxis(..)
+            | WindowEvent::GamepadButton(..) => true,
+            _ => false,
+        }
+    }
+
+    /// Tests if this event is related to a file dragged and dropped onto the window.
+    pub fn is_file_event(&self) -> bool {
+        match self {
+            WindowEvent::DroppedFile
+            | WindowEvent::HoveredFile
+            | WindowEvent::HoveredFileCancelled => true,
+            _ => false,
+        }
+    }
 }
 
 // NOTE: list of keys inspired from glutin.
@@ -254,3 +299,45 @@ bitflags! {
         const Super       = 0b1000;
     }
 }
+
+/// The axes of a gamepad.
+///
+/// Stick values range from -1.0 to 1.0, with positive values pointing right and up. Trigger values
+/// range from 0.0 to 1.0.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
+pub enum GamepadAxis {
+    LeftStickX,
+    LeftStickY,
+    RightStickX,
+    RightStickY,
+    LeftTrigger,
+    RightTrigger,
+    Unknown,
+}
+
+// NOTE: list of buttons inspired from gilrs.
+/// The buttons of a gamepad.
+///
+/// The action buttons are named after their position, e.g., `South` is `A` on Xbox controllers
+/// and `Cross` on PlayStation controllers.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
+pub enum GamepadButton {
+    South,
+    East,
+    North,
+    West,
+    LeftTrigger,
+    LeftTrigger2,
+    RightTrigger,
+    RightTrigger2,
+    Select,
+    Start,
+    Mode,
+    LeftThumb,
+    RightThumb,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+    Unknown,
+}
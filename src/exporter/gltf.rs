@@ -0,0 +1,322 @@
+//! glTF 2.0 binary (`.glb`) exporter.
+
+use std::collections::HashMap;
+use std::fmt::Write as FmtWrite;
+use std::fs::File;
+use std::io::{self, BufWriter, Result as IoResult, Write};
+use std::path::Path;
+
+use image::png::PngEncoder;
+use image::ColorType;
+
+use crate::exporter::{self, ExportedMesh};
+use crate::resource::Texture;
+use crate::scene::SceneNode;
+use na::Vector3;
+
+const ARRAY_BUFFER: u32 = 34962;
+const ELEMENT_ARRAY_BUFFER: u32 = 34963;
+const UNSIGNED_SHORT: u32 = 5123;
+const FLOAT: u32 = 5126;
+
+/// Writes the visible objects of the subtree rooted by `node` to a glTF binary file.
+///
+/// Each object becomes a glTF node carrying the world transformation and scale of its scene
+/// node, so the objects keep their own local frame once imported. The object colors, vertex
+/// colors, and textures are exported as materials, the textures being embedded as PNG images.
+pub fn export_file(node: &SceneNode, path: &Path) -> IoResult<()> {
+    let mut out = BufWriter::new(File::create(path)?);
+    export(node, &mut out)?;
+    out.flush()
+}
+
+/// Writes the visible objects of the subtree rooted by `node` in the glTF binary format.
+///
+/// See `export_file` for details.
+pub fn export<W: Write>(node: &SceneNode, out: &mut W) -> IoResult<()> {
+    let meshes = exporter::collect_meshes(node)?;
+    let mut doc = Document::default();
+
+    for mesh in meshes.iter() {
+        doc.add_mesh(mesh)?;
+    }
+
+    doc.write(out)
+}
+
+/// The glTF document being built: its JSON arrays, and its binary buffer.
+#[derive(Default)]
+struct Document {
+    bin: Vec<u8>,
+    buffer_views: Vec<String>,
+    accessors: Vec<String>,
+    images: Vec<String>,
+    textures: Vec<String>,
+    materials: Vec<String>,
+    meshes: Vec<String>,
+    nodes: Vec<String>,
+    /// glTF texture ids, indexed by the address of their kiss3d texture.
+    texture_ids: HashMap<*const Texture, Option<usize>>,
+}
+
+impl Document {
+    fn add_mesh(&mut self, mesh: &ExportedMesh) -> IoResult<()> {
+        // glTF does not allow empty accessors.
+        if mesh.coords.is_empty() || mesh.faces.is_empty() {
+            return Ok(());
+        }
+
+        let (min, max) = mesh.coords.iter().fold(
+            (Vector3::repeat(f32::MAX), Vector3::repeat(f32::MIN)),
+            |(min, max), pt| (min.inf(&pt.coords), max.sup(&pt.coords)),
+        );
+        let coords: Vec<f32> = mesh.coords.iter().flat_map(|p| p.iter().cloned()).collect();
+        let position = self.add_accessor(
+            &coords,
+            "VEC3",
+            mesh.coords.len(),
+            Some((min.as_slice(), max.as_slice())),
+        );
+
+        let normals: Vec<f32> = mesh
+            .normals
+            .iter()
+            .flat_map(|n| {
+                let n = n.try_normalize(0.0).unwrap_or_else(Vector3::z);
+                vec![n.x, n.y, n.z]
+            })
+            .collect();
+        let normal = self.add_accessor(&normals, "VEC3", mesh.normals.len(), None);
+
+        let mut attributes = format!(r#""POSITION":{},"NORMAL":{}"#, position, normal);
+
+        if let Some(uvs) = &mesh.uvs {
+            let data: Vec<f32> = uvs.iter().flat_map(|uv| uv.iter().cloned()).collect();
+            let uv = self.add_accessor(&data, "VEC2", uvs.len(), None);
+            write!(attributes, r#","TEXCOORD_0":{}"#, uv).unwrap();
+        }
+
+        if let Some(colors) = &mesh.colors {
+            let data: Vec<f32> = colors.iter().flat_map(|c| c.iter().cloned()).collect();
+            let color = self.add_accessor(&data, "VEC3", colors.len(), None);
+            write!(attributes, r#","COLOR_0":{}"#, color).unwrap();
+        }
+
+        let indices = self.add_indices(mesh);
+        let material = self.add_material(mesh)?;
+
+        self.meshes.push(format!(
+            r#"{{"primitives":[{{"attributes":{{{}}},"indices":{},"material":{}}}]}}"#,
+            attributes, indices, material
+        ));
+
+        let t = mesh.transform.translation.vector;
+        let r = mesh.transform.rotation.coords;
+        let s = mesh.scale;
+        self.nodes.push(format!(
+            r#"{{"mesh":{},"translation":[{:?},{:?},{:?}],"rotation":[{:?},{:?},{:?},{:?}],"scale":[{:?},{:?},{:?}]}}"#,
+            self.meshes.len() - 1,
+            t.x, t.y, t.z,
+            r.x, r.y, r.z, r.w,
+            s.x, s.y, s.z
+        ));
+
+        Ok(())
+    }
+
+    fn add_material(&mut self, mesh: &ExportedMesh) -> IoResult<usize> {
+        let c = mesh.color;
+        let mut pbr = format!(
+            r#""baseColorFactor":[{:?},{:?},{:?},1.0],"metallicFactor":0.0,"roughnessFactor":1.0"#,
+            c.x, c.y, c.z
+        );
+
+        if let Some(texture) = &mesh.texture {
+            let key = &**texture as *const _;
+
+            let id = match self.texture_ids.get(&key) {
+                Some(id) => *id,
+                None => {
+                    let id = match exporter::read_texture(texture) {
+                        Some((name, image)) => {
+                            let mut png = Vec::new();
+                            PngEncoder::new(&mut png)
+                                .encode(&image, image.width(), image.height(), ColorType::Rgba8)
+                                .map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string()))?;
+
+                            let view = self.add_buffer_view(&png, None);
+                            self.images.push(format!(
+                                r#"{{"name":{},"bufferView":{},"mimeType":"image/png"}}"#,
+                                json_string(&name),
+                                view
+                            ));
+                            self.textures
+                                .push(format!(r#"{{"source":{}}}"#, self.images.len() - 1));
+                            Some(self.textures.len() - 1)
+                        }
+                        None => None,
+                    };
+
+                    let _ = self.texture_ids.insert(key, id);
+                    id
+                }
+            };
+
+            if let Some(id) = id {
+                write!(pbr, r#","baseColorTexture":{{"index":{}}}"#, id).unwrap();
+            }
+        }
+
+        self.materials
+            .push(format!(r#"{{"pbrMetallicRoughness":{{{}}}}}"#, pbr));
+
+        Ok(self.materials.len() - 1)
+    }
+
+    fn add_indices(&mut self, mesh: &ExportedMesh) -> usize {
+        let bytes: Vec<u8> = mesh
+            .faces
+            .iter()
+            .flat_map(|f| {
+                f.iter()
+                    .flat_map(|i| i.to_le_bytes().to_vec())
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        let view = self.add_buffer_view(&bytes, Some(ELEMENT_ARRAY_BUFFER));
+
+        self.accessors.push(format!(
+            r#"{{"bufferView":{},"componentType":{},"count":{},"type":"SCALAR"}}"#,
+            view,
+            UNSIGNED_SHORT,
+            mesh.faces.len() * 3
+        ));
+        self.accessors.len() - 1
+    }
+
+    fn add_accessor(
+        &mut self,
+        data: &[f32],
+        ty: &str,
+        count: usize,
+        bounds: Option<(&[f32], &[f32])>,
+    ) -> usize {
+        let bytes: Vec<u8> = data.iter().flat_map(|x| x.to_le_bytes().to_vec()).collect();
+        let view = self.add_buffer_view(&bytes, Some(ARRAY_BUFFER));
+        let mut accessor = format!(
+            r#"{{"bufferView":{},"componentType":{},"count":{},"type":"{}""#,
+            view, FLOAT, count, ty
+        );
+
+        // The bounds are mandatory for positions.
+        if let Some((min, max)) = bounds {
+            write!(
+                accessor,
+                r#","min":{},"max":{}"#,
+                json_floats(min),
+                json_floats(max)
+            )
+            .unwrap();
+        }
+
+        accessor.push('}');
+        self.accessors.push(accessor);
+        self.accessors.len() - 1
+    }
+
+    fn add_buffer_view(&mut self, bytes: &[u8], target: Option<u32>) -> usize {
+        let offset = self.bin.len();
+        self.bin.extend_from_slice(bytes);
+        // Every view starts on a 4-bytes boundary.
+        self.bin.resize((self.bin.len() + 3) & !3, 0);
+
+        let target = target
+            .map(|t| format!(r#","target":{}"#, t))
+            .unwrap_or_default();
+        self.buffer_views.push(format!(
+            r#"{{"buffer":0,"byteOffset":{},"byteLength":{}{}}}"#,
+            offset,
+            bytes.len(),
+            target
+        ));
+        self.buffer_views.len() - 1
+    }
+
+    fn write<W: Write>(&self, out: &mut W) -> IoResult<()> {
+        let nodes: Vec<String> = (0..self.nodes.len()).map(|i| i.to_string()).collect();
+        let mut json = format!(
+            r#"{{"asset":{{"version":"2.0","generator":"kiss3d"}},"scene":0,"scenes":[{{"nodes":[{}]}}]"#,
+            nodes.join(",")
+        );
+
+        let arrays = [
+            ("nodes", &self.nodes),
+            ("meshes", &self.meshes),
+            ("materials", &self.materials),
+            ("textures", &self.textures),
+            ("images", &self.images),
+            ("accessors", &self.accessors),
+            ("bufferViews", &self.buffer_views),
+        ];
+
+        for (name, items) in arrays.iter() {
+            if !items.is_empty() {
+                write!(json, r#","{}":[{}]"#, name, items.join(",")).unwrap();
+            }
+        }
+
+        if !self.bin.is_empty() {
+            write!(json, r#","buffers":[{{"byteLength":{}}}]"#, self.bin.len()).unwrap();
+        }
+
+        json.push('}');
+
+        // The JSON chunk is padded with spaces, the binary chunk with zeros.
+        let mut json = json.into_bytes();
+        json.resize((json.len() + 3) & !3, b' ');
+
+        let mut length = 12 + 8 + json.len();
+
+        if !self.bin.is_empty() {
+            length += 8 + self.bin.len();
+        }
+
+        out.write_all(b"glTF")?;
+        out.write_all(&2u32.to_le_bytes())?;
+        out.write_all(&(length as u32).to_le_bytes())?;
+
+        out.write_all(&(json.len() as u32).to_le_bytes())?;
+        out.write_all(b"JSON")?;
+        out.write_all(&json)?;
+
+        if !self.bin.is_empty() {
+            out.write_all(&(self.bin.len() as u32).to_le_bytes())?;
+            out.write_all(b"BIN\0")?;
+            out.write_all(&self.bin)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn json_floats(values: &[f32]) -> String {
+    let values: Vec<String> = values.iter().map(|v| format!("{:?}", v)).collect();
+    format!("[{}]", values.join(","))
+}
+
+fn json_string(s: &str) -> String {
+    let mut res = String::with_capacity(s.len() + 2);
+    res.push('"');
+
+    for c in s.chars() {
+        match c {
+            '"' => res.push_str("\\\""),
+            '\\' => res.push_str("\\\\"),
+            c if (c as u32) < 0x20 => write!(res, "\\u{:04x}", c as u32).unwrap(),
+            c => res.push(c),
+        }
+    }
+
+    res.push('"');
+    res
+}
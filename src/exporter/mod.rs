@@ -0,0 +1,220 @@
+//! File exporting.
+
+use std::io::{self, Result as IoResult};
+use std::rc::Rc;
+
+use image::RgbaImage;
+
+use crate::context::Context;
+use crate::resource::{Texture, TextureManager};
+use crate::scene::{SceneNode, SceneNodeData};
+use na::{Isometry3, Point2, Point3, Vector3};
+
+#[path = "../error.rs"]
+mod error;
+
+pub mod gltf;
+pub mod obj;
+pub mod stl;
+
+/// The geometry and appearance of an object, with the world transformation of its node.
+pub(crate) struct ExportedMesh {
+    /// The world transformation of the object.
+    pub transform: Isometry3<f32>,
+    /// The world scale of the object, applied before `transform`.
+    pub scale: Vector3<f32>,
+    pub coords: Vec<Point3<f32>>,
+    pub normals: Vec<Vector3<f32>>,
+    /// The texture coordinates, only set if the object is textured.
+    pub uvs: Option<Vec<Point2<f32>>>,
+    pub colors: Option<Vec<Point3<f32>>>,
+    pub faces: Vec<Point3<u16>>,
+    pub color: Point3<f32>,
+    /// The texture of the object, unless it uses the default white texture.
+    pub texture: Option<Rc<Texture>>,
+}
+
+impl ExportedMesh {
+    /// Applies the transformation and scale of this mesh to its vertices and normals, and resets
+    /// them to the identity.
+    pub fn bake_transform(&mut self) {
+        let transform = self.transform;
+        let scale = self.scale;
+
+        for pt in self.coords.iter_mut() {
+            *pt = transform * Point3::from(pt.coords.component_mul(&scale));
+        }
+
+        for n in self.normals.iter_mut() {
+            let scaled = n.component_div(&scale);
+            *n = transform
+                .rotation
+                .transform_vector(&scaled.try_normalize(0.0).unwrap_or(scaled));
+        }
+
+        // A mirroring scale flips the orientation of the faces.
+        if scale.x * scale.y * scale.z < 0.0 {
+            for face in self.faces.iter_mut() {
+                face.coords.swap_rows(1, 2);
+            }
+        }
+
+        self.transform = Isometry3::identity();
+        self.scale = Vector3::repeat(1.0);
+    }
+}
+
+/// Collects the meshes of the visible objects of the subtree rooted by `node`.
+///
+/// The meshes are expressed in the local frame of their objects.
+pub(crate) fn collect_meshes(node: &SceneNode) -> IoResult<Vec<ExportedMesh>> {
+    let data = node.data();
+    let transform = data.world_transformation();
+    let scale = data.world_scale();
+    let default_texture = TextureManager::get_global_manager(|tm| tm.get_default());
+    let mut meshes = Vec::new();
+
+    collect(&*data, &transform, &scale, &default_texture, &mut meshes)?;
+
+    Ok(meshes)
+}
+
+/// Collects the meshes of the visible objects of the subtree rooted by `node`.
+///
+/// The world transformation and scale of each node are applied to its mesh.
+pub(crate) fn collect_world_meshes(node: &SceneNode) -> IoResult<Vec<ExportedMesh>> {
+    let mut meshes = collect_meshes(node)?;
+
+    for mesh in meshes.iter_mut() {
+        mesh.bake_transform();
+    }
+
+    Ok(meshes)
+}
+
+fn collect(
+    data: &SceneNodeData,
+    transform: &Isometry3<f32>,
+    scale: &Vector3<f32>,
+    default_texture: &Rc<Texture>,
+    out: &mut Vec<ExportedMesh>,
+) -> IoResult<()> {
+    if !data.is_visible() {
+        return Ok(());
+    }
+
+    if let Some(object) = data.object() {
+        let mesh = object.mesh().borrow();
+        let coords = mesh.coords().read().unwrap();
+        let normals = mesh.normals().read().unwrap();
+        let uvs = mesh.uvs().read().unwrap();
+        let faces = mesh.faces().read().unwrap();
+        let colors = mesh.colors().map(|c| c.read().unwrap());
+        let colors = match colors {
+            Some(ref colors) => Some(colors.data().as_ref().ok_or_else(not_in_ram)?.clone()),
+            None => None,
+        };
+        let texture = if Rc::ptr_eq(object.data().texture(), default_texture) {
+            None
+        } else {
+            Some(object.data().texture().clone())
+        };
+        let uvs = if texture.is_some() {
+            Some(uvs.data().as_ref().ok_or_else(not_in_ram)?.clone())
+        } else {
+            None
+        };
+
+        out.push(ExportedMesh {
+            transform: *transform,
+            scale: *scale,
+            coords: coords.data().as_ref().ok_or_else(not_in_ram)?.clone(),
+            normals: normals.data().as_ref().ok_or_else(not_in_ram)?.clone(),
+            uvs,
+            colors,
+            faces: faces.data().as_ref().ok_or_else(not_in_ram)?.clone(),
+            color: *object.data().color(),
+            texture,
+        });
+    }
+
+    for child in data.children() {
+        let child = child.data();
+        let child_transform = transform * child.local_transformation();
+        let child_scale = scale.component_mul(&child.local_scale());
+
+        collect(
+            &*child,
+            &child_transform,
+            &child_scale,
+            default_texture,
+            out,
+        )?;
+    }
+
+    Ok(())
+}
+
+fn not_in_ram() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Other,
+        "The mesh data is not available on RAM.",
+    )
+}
+
+/// Reads back the content of a texture registered to the texture manager.
+///
+/// Returns the name of the texture and its image, or `None` if the texture is not registered or
+/// is empty.
+pub(crate) fn read_texture(texture: &Rc<Texture>) -> Option<(String, RgbaImage)> {
+    let (name, (width, height)) = TextureManager::get_global_manager(|tm| {
+        tm.find(texture)
+            .map(|(name, size)| (name.to_string(), size))
+    })?;
+
+    if width == 0 || height == 0 {
+        return None;
+    }
+
+    // Textures cannot be read directly on OpenGL ES, so the texture is attached to a temporary
+    // framebuffer which is then read.
+    let ctxt = Context::get();
+    let fbo = ctxt.create_framebuffer()?;
+    let mut pixels = vec![0u8; width as usize * height as usize * 4];
+
+    verify!(ctxt.bind_framebuffer(Context::FRAMEBUFFER, Some(&fbo)));
+    verify!(ctxt.framebuffer_texture2d(
+        Context::FRAMEBUFFER,
+        Context::COLOR_ATTACHMENT0,
+        Context::TEXTURE_2D,
+        Some(&**texture),
+        0
+    ));
+    verify!(ctxt.pixel_storei(Context::PACK_ALIGNMENT, 1));
+    verify!(ctxt.read_pixels(
+        0,
+        0,
+        width as i32,
+        height as i32,
+        Context::RGBA,
+        Some(&mut pixels)
+    ));
+    verify!(ctxt.bind_framebuffer(Context::FRAMEBUFFER, None));
+    verify!(ctxt.delete_framebuffer(Some(&fbo)));
+
+    // The first row of a texture is the top of its image, so no flip is needed.
+    RgbaImage::from_raw(width, height, pixels).map(|image| (name, image))
+}
+
+/// Turns a texture name into something usable as a file name.
+pub(crate) fn sanitize_name(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,127 @@
+//! Wavefront OBJ exporter.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufWriter, Result as IoResult, Write};
+use std::path::Path;
+
+use crate::exporter;
+use crate::scene::SceneNode;
+
+/// Writes the visible objects of the subtree rooted by `node` to an OBJ file.
+///
+/// The world transformation of each object is applied to its vertices. The colors and textures
+/// of the objects are written to a MTL file with the same name as `path` but the `mtl`
+/// extension. The textures are saved as PNG images next to it. Per-vertex colors are written
+/// after the vertex positions, an extension supported by most tools.
+pub fn export_file(node: &SceneNode, path: &Path) -> IoResult<()> {
+    let meshes = exporter::collect_world_meshes(node)?;
+    let mtl_path = path.with_extension("mtl");
+    let dir = path.parent().unwrap_or_else(|| Path::new(""));
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "scene".to_string());
+
+    let mut obj = BufWriter::new(File::create(path)?);
+    let mut mtl = BufWriter::new(File::create(&mtl_path)?);
+    // Texture file names, indexed by the address of their texture.
+    let mut texture_files = HashMap::new();
+    let mut num_coords = 0;
+    let mut num_uvs = 0;
+
+    writeln!(obj, "# kiss3d OBJ export")?;
+    writeln!(
+        obj,
+        "mtllib {}",
+        mtl_path.file_name().unwrap().to_string_lossy()
+    )?;
+    writeln!(mtl, "# kiss3d MTL export")?;
+
+    for (i, mesh) in meshes.iter().enumerate() {
+        /*
+         * Material.
+         */
+        let c = mesh.color;
+        writeln!(mtl)?;
+        writeln!(mtl, "newmtl material_{}", i)?;
+        writeln!(mtl, "Ka 0 0 0")?;
+        writeln!(mtl, "Kd {} {} {}", c.x, c.y, c.z)?;
+        writeln!(mtl, "Ks 0 0 0")?;
+        writeln!(mtl, "illum 1")?;
+
+        if let Some(texture) = &mesh.texture {
+            let key = &**texture as *const _;
+
+            if !texture_files.contains_key(&key) {
+                let file = match exporter::read_texture(texture) {
+                    Some((name, image)) => {
+                        let file = format!("{}_{}.png", stem, exporter::sanitize_name(&name));
+                        image
+                            .save(dir.join(&file))
+                            .map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string()))?;
+                        Some(file)
+                    }
+                    None => None,
+                };
+
+                let _ = texture_files.insert(key, file);
+            }
+
+            if let Some(file) = &texture_files[&key] {
+                writeln!(mtl, "map_Kd {}", file)?;
+            }
+        }
+
+        /*
+         * Geometry.
+         */
+        writeln!(obj)?;
+        writeln!(obj, "o object_{}", i)?;
+
+        for (j, v) in mesh.coords.iter().enumerate() {
+            match &mesh.colors {
+                Some(colors) => {
+                    let c = colors[j];
+                    writeln!(obj, "v {} {} {} {} {} {}", v.x, v.y, v.z, c.x, c.y, c.z)?
+                }
+                None => writeln!(obj, "v {} {} {}", v.x, v.y, v.z)?,
+            }
+        }
+
+        if let Some(uvs) = &mesh.uvs {
+            for uv in uvs.iter() {
+                // The `v` axis of OBJ files points up, while it points down on our textures.
+                writeln!(obj, "vt {} {}", uv.x, 1.0 - uv.y)?;
+            }
+        }
+
+        for n in mesh.normals.iter() {
+            writeln!(obj, "vn {} {} {}", n.x, n.y, n.z)?;
+        }
+
+        writeln!(obj, "usemtl material_{}", i)?;
+
+        for face in mesh.faces.iter() {
+            write!(obj, "f")?;
+
+            for id in face.iter() {
+                let v = num_coords + *id as usize + 1;
+
+                if mesh.uvs.is_some() {
+                    write!(obj, " {}/{}/{}", v, num_uvs + *id as usize + 1, v)?;
+                } else {
+                    write!(obj, " {}//{}", v, v)?;
+                }
+            }
+
+            writeln!(obj)?;
+        }
+
+        num_coords += mesh.coords.len();
+        num_uvs += mesh.uvs.as_ref().map(|uvs| uvs.len()).unwrap_or(0);
+    }
+
+    obj.flush()?;
+    mtl.flush()
+}
@@ -0,0 +1,82 @@
+//! STL mesh exporter.
+
+use std::fs::File;
+use std::io::{BufWriter, Result as IoResult, Write};
+use std::path::Path;
+
+use crate::exporter;
+use crate::scene::SceneNode;
+use na::{Point3, Vector3};
+
+/// Writes the visible objects of the subtree rooted by `node` to a STL file.
+///
+/// The world transformation of each object is applied to its vertices. Set `binary` to `false`
+/// to write an ASCII file.
+pub fn export_file(node: &SceneNode, path: &Path, binary: bool) -> IoResult<()> {
+    let mut out = BufWriter::new(File::create(path)?);
+    export(node, &mut out, binary)?;
+    out.flush()
+}
+
+/// Writes the visible objects of the subtree rooted by `node` in the STL format.
+///
+/// See `export_file` for details.
+pub fn export<W: Write>(node: &SceneNode, out: &mut W, binary: bool) -> IoResult<()> {
+    let meshes = exporter::collect_world_meshes(node)?;
+    let triangles = meshes.iter().flat_map(|mesh| {
+        mesh.faces.iter().map(move |f| {
+            [
+                mesh.coords[f.x as usize],
+                mesh.coords[f.y as usize],
+                mesh.coords[f.z as usize],
+            ]
+        })
+    });
+
+    if binary {
+        let num_triangles: usize = meshes.iter().map(|m| m.faces.len()).sum();
+        let mut header = [0u8; 80];
+        let title = b"kiss3d STL export";
+        header[..title.len()].copy_from_slice(title);
+
+        out.write_all(&header)?;
+        out.write_all(&(num_triangles as u32).to_le_bytes())?;
+
+        for triangle in triangles {
+            let normal = facet_normal(&triangle);
+
+            for val in normal.iter().chain(triangle.iter().flat_map(|v| v.iter())) {
+                out.write_all(&val.to_le_bytes())?;
+            }
+
+            // Attribute byte count.
+            out.write_all(&[0, 0])?;
+        }
+    } else {
+        writeln!(out, "solid kiss3d")?;
+
+        for triangle in triangles {
+            let n = facet_normal(&triangle);
+            writeln!(out, "  facet normal {:e} {:e} {:e}", n.x, n.y, n.z)?;
+            writeln!(out, "    outer loop")?;
+
+            for v in triangle.iter() {
+                writeln!(out, "      vertex {:e} {:e} {:e}", v.x, v.y, v.z)?;
+            }
+
+            writeln!(out, "    endloop")?;
+            writeln!(out, "  endfacet")?;
+        }
+
+        writeln!(out, "endsolid kiss3d")?;
+    }
+
+    Ok(())
+}
+
+fn facet_normal(triangle: &[Point3<f32>; 3]) -> Vector3<f32> {
+    (triangle[1] - triangle[0])
+        .cross(&(triangle[2] - triangle[0]))
+        .try_normalize(0.0)
+        .unwrap_or_else(Vector3::zeros)
+}
@@ -138,9 +138,14 @@ extern crate rusttype;
 #[macro_use]
 extern crate serde_derive;
 extern crate serde;
+extern crate serde_json;
 
 #[cfg(feature = "conrod")]
 pub extern crate conrod_core as conrod;
+#[cfg(all(feature = "clipboard", not(target_arch = "wasm32")))]
+extern crate copypasta;
+#[cfg(all(feature = "gamepad", not(target_arch = "wasm32")))]
+extern crate gilrs;
 #[cfg(not(target_arch = "wasm32"))]
 extern crate glutin;
 extern crate instant;
@@ -160,6 +165,7 @@ pub mod camera;
 pub mod context;
 mod error;
 pub mod event;
+pub mod exporter;
 pub mod light;
 pub mod loader;
 pub mod planar_camera;
@@ -1,4 +1,10 @@
 //! File loading.
 
+pub use self::point_set::PointSet;
+
 pub mod mtl;
 pub mod obj;
+pub mod pcd;
+pub mod ply;
+mod point_set;
+pub mod stl;
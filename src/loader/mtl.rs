@@ -1,36 +1,34 @@
 //! Simplistic mtl loader.
 
 use crate::loader::obj;
-use crate::loader::obj::Words;
+use crate::loader::obj::{Line, ParseError, ParseWarning, Words};
 use na::Vector3;
 use std::fs::File;
 use std::io::Read;
 use std::io::Result as IoResult;
 use std::mem;
 use std::path::Path;
-use std::str::FromStr;
-
-fn error(line: usize, err: &str) -> ! {
-    panic!("At line {}: {}", line, err)
-}
 
 /// Parses a mtl file.
-pub fn parse_file(path: &Path) -> IoResult<Vec<MtlMaterial>> {
-    match File::open(path) {
-        Ok(mut file) => {
-            let mut sfile = String::new();
-            file.read_to_string(&mut sfile).map(|_| parse(&sfile[..]))
-        }
-        Err(e) => Err(e),
-    }
+///
+/// Returns the materials, and the warnings emitted while parsing.
+pub fn parse_file(path: &Path) -> IoResult<(Vec<MtlMaterial>, Vec<ParseWarning>)> {
+    let mut sfile = String::new();
+    let _ = File::open(path)?.read_to_string(&mut sfile)?;
+
+    Ok(parse(&sfile[..])?)
 }
 
 /// Parses a string representing a mtl file.
-pub fn parse(string: &str) -> Vec<MtlMaterial> {
+///
+/// Returns the materials, and the warnings emitted while parsing.
+pub fn parse(string: &str) -> Result<(Vec<MtlMaterial>, Vec<ParseWarning>), ParseError> {
     let mut res = Vec::new();
+    let mut warnings = Vec::new();
     let mut curr_material = MtlMaterial::new_default("".to_string());
 
     for (l, line) in string.lines().enumerate() {
+        let l = Line::new(l, line);
         let mut words = obj::split_words(line);
         let tag = words.next();
 
@@ -58,15 +56,15 @@ pub fn parse(string: &str) -> Vec<MtlMaterial> {
                             }
                         }
                         // ambiant color
-                        "Ka" => curr_material.ambiant = parse_color(l, words),
+                        "Ka" => curr_material.ambiant = parse_color(l, words)?,
                         // diffuse color
-                        "Kd" => curr_material.diffuse = parse_color(l, words),
+                        "Kd" => curr_material.diffuse = parse_color(l, words)?,
                         // specular color
-                        "Ks" => curr_material.specular = parse_color(l, words),
+                        "Ks" => curr_material.specular = parse_color(l, words)?,
                         // shininess
-                        "Ns" => curr_material.shininess = parse_scalar(l, words),
+                        "Ns" => curr_material.shininess = parse_scalar(l, words)?,
                         // alpha
-                        "d" => curr_material.alpha = parse_scalar(l, words),
+                        "d" => curr_material.alpha = parse_scalar(l, words)?,
                         // ambiant map
                         "map_Ka" => curr_material.ambiant_texture = Some(parse_name(l, words)),
                         // diffuse texture map
@@ -78,7 +76,7 @@ pub fn parse(string: &str) -> Vec<MtlMaterial> {
                             curr_material.opacity_map = Some(parse_name(l, words))
                         }
                         _ => {
-                            println!("Warning: unknown line {} ignored: `{}'", l, line);
+                            warnings.push(l.warning(format!("unknown line ignored: `{}'", line)));
                         }
                     }
                 }
@@ -90,50 +88,30 @@ pub fn parse(string: &str) -> Vec<MtlMaterial> {
         res.push(curr_material);
     }
 
-    res
+    Ok((res, warnings))
 }
 
-fn parse_name<'a>(_: usize, ws: Words<'a>) -> String {
+fn parse_name<'a>(_: Line, ws: Words<'a>) -> String {
     let res: Vec<&'a str> = ws.collect();
     res.join(" ")
 }
 
-fn parse_color(l: usize, mut ws: Words) -> Vector3<f32> {
-    let sx = ws
-        .next()
-        .unwrap_or_else(|| error(l, "3 components were expected, found 0."));
-    let sy = ws
-        .next()
-        .unwrap_or_else(|| error(l, "3 components were expected, found 1."));
-    let sz = ws
-        .next()
-        .unwrap_or_else(|| error(l, "3 components were expected, found 2."));
-
-    let x: Result<f32, _> = FromStr::from_str(sx);
-    let y: Result<f32, _> = FromStr::from_str(sy);
-    let z: Result<f32, _> = FromStr::from_str(sz);
-
-    let x =
-        x.unwrap_or_else(|e| error(l, &format!("failed to parse `{}' as a f32: {}", sx, e)[..]));
-    let y =
-        y.unwrap_or_else(|e| error(l, &format!("failed to parse `{}' as a f32: {}", sy, e)[..]));
-    let z =
-        z.unwrap_or_else(|e| error(l, &format!("failed to parse `{}' as a f32: {}", sz, e)[..]));
-
-    Vector3::new(x, y, z)
-}
-
-fn parse_scalar(l: usize, mut ws: Words) -> f32 {
-    let sx = ws
-        .next()
-        .unwrap_or_else(|| error(l, "1 component was expected, found 0."));
+fn parse_color<'a>(l: Line<'a>, mut ws: Words<'a>) -> Result<Vector3<f32>, ParseError> {
+    let sx = l.next_component(&mut ws, 3, 0)?;
+    let sy = l.next_component(&mut ws, 3, 1)?;
+    let sz = l.next_component(&mut ws, 3, 2)?;
 
-    let x: Result<f32, _> = FromStr::from_str(sx);
+    Ok(Vector3::new(
+        l.parse_number(sx)?,
+        l.parse_number(sy)?,
+        l.parse_number(sz)?,
+    ))
+}
 
-    let x =
-        x.unwrap_or_else(|e| error(l, &format!("failed to parse `{}' as a f32: {}", sx, e)[..]));
+fn parse_scalar<'a>(l: Line<'a>, mut ws: Words<'a>) -> Result<f32, ParseError> {
+    let sx = l.next_component(&mut ws, 1, 0)?;
 
-    x
+    l.parse_number(sx)
 }
 
 /// Material informations read from a `.mtl` file.
@@ -1,4 +1,4 @@
-//! Simplistic obj loader.
+//! Wavefront obj loader.
 
 use crate::loader::mtl;
 use crate::loader::mtl::MtlMaterial;
@@ -8,9 +8,12 @@ use na::{Point2, Point3, Vector3};
 use num::Bounded;
 use std::collections::hash_map::Entry;
 use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
 use std::fs::File;
 use std::io::Read;
 use std::io::Result as IoResult;
+use std::io::{self, ErrorKind};
 use std::iter::repeat;
 use std::iter::Filter;
 use std::path::{Path, PathBuf};
@@ -44,52 +47,280 @@ pub fn split_words(s: &str) -> Words {
     s.split(is_whitespace).filter(is_not_empty)
 }
 
-fn error(line: usize, err: &str) -> ! {
-    panic!("At line {}: {}", line, err)
+/// The kind of error encountered while parsing an obj or mtl file.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ParseErrorKind {
+    /// Some components of a statement are missing.
+    MissingComponents {
+        /// The number of components expected.
+        expected: usize,
+        /// The number of components found.
+        found: usize,
+    },
+    /// A word could not be parsed as a number.
+    InvalidNumber(String),
+    /// A face refers to a vertex, texture coordinate, or normal that does not exist.
+    IndexOutOfBounds(i32),
+    /// The file has more vertices than a mesh can index.
+    TooManyVertices,
 }
 
-fn warn(line: usize, err: &str) {
-    println!("At line {}: {}", line, err)
+impl fmt::Display for ParseErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ParseErrorKind::MissingComponents { expected, found } => {
+                let plural = if expected == 1 { " was" } else { "s were" };
+                write!(
+                    f,
+                    "{} component{} expected, found {}.",
+                    expected, plural, found
+                )
+            }
+            ParseErrorKind::InvalidNumber(ref word) => {
+                write!(f, "failed to parse `{}' as a number.", word)
+            }
+            ParseErrorKind::IndexOutOfBounds(id) => write!(f, "the index {} is out of bounds.", id),
+            ParseErrorKind::TooManyVertices => write!(
+                f,
+                "more than {} distinct vertices.",
+                u16::max_value() as usize + 1
+            ),
+        }
+    }
+}
+
+/// An error encountered while parsing an obj or mtl file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseError {
+    /// The line of the error, starting at 1, or 0 if it is not related to a specific line.
+    pub line: usize,
+    /// The column of the error, starting at 1, or 0 if it is not related to a specific line.
+    pub column: usize,
+    /// The kind of error.
+    pub kind: ParseErrorKind,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.line == 0 {
+            write!(f, "{}", self.kind)
+        } else {
+            write!(
+                f,
+                "At line {}, column {}: {}",
+                self.line, self.column, self.kind
+            )
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+impl From<ParseError> for io::Error {
+    fn from(err: ParseError) -> io::Error {
+        io::Error::new(ErrorKind::InvalidData, err)
+    }
+}
+
+/// A non-fatal problem encountered while parsing an obj or mtl file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseWarning {
+    /// The line of the warning, starting at 1, or 0 if it is not related to a specific line.
+    pub line: usize,
+    /// A description of the problem.
+    pub message: String,
+}
+
+impl fmt::Display for ParseWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.line == 0 {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "At line {}: {}", self.line, self.message)
+        }
+    }
+}
+
+/// A line of an obj or mtl file, used to locate errors and warnings.
+#[derive(Clone, Copy)]
+pub(crate) struct Line<'a> {
+    number: usize,
+    text: &'a str,
+}
+
+impl<'a> Line<'a> {
+    /// Creates a line from its 0-based index and its text.
+    pub(crate) fn new(index: usize, text: &'a str) -> Line<'a> {
+        Line {
+            number: index + 1,
+            text,
+        }
+    }
+
+    /// An error located at `word`, which must be a slice of this line.
+    pub(crate) fn error_at(&self, word: &str, kind: ParseErrorKind) -> ParseError {
+        let offset = word.as_ptr() as usize - self.text.as_ptr() as usize;
+
+        ParseError {
+            line: self.number,
+            column: self.text[..offset].chars().count() + 1,
+            kind,
+        }
+    }
+
+    /// An error located at the end of this line.
+    pub(crate) fn error_at_end(&self, kind: ParseErrorKind) -> ParseError {
+        ParseError {
+            line: self.number,
+            column: self.text.chars().count() + 1,
+            kind,
+        }
+    }
+
+    pub(crate) fn warning(&self, message: String) -> ParseWarning {
+        ParseWarning {
+            line: self.number,
+            message,
+        }
+    }
+
+    /// Reads the next component of a statement expecting `expected` components.
+    pub(crate) fn next_component(
+        &self,
+        ws: &mut Words<'a>,
+        expected: usize,
+        found: usize,
+    ) -> Result<&'a str, ParseError> {
+        let kind = ParseErrorKind::MissingComponents { expected, found };
+        ws.next().ok_or_else(|| self.error_at_end(kind))
+    }
+
+    /// Parses a word of this line as a number.
+    pub(crate) fn parse_number<T: FromStr>(&self, word: &str) -> Result<T, ParseError> {
+        FromStr::from_str(word)
+            .map_err(|_| self.error_at(word, ParseErrorKind::InvalidNumber(word.to_string())))
+    }
 }
 
 /// Parses an obj file.
+///
+/// See `parse` for details.
 pub fn parse_file(
     path: &Path,
     mtl_base_dir: &Path,
     basename: &str,
-) -> IoResult<Vec<(String, Mesh, Option<MtlMaterial>)>> {
-    match File::open(path) {
-        Ok(mut file) => {
-            let mut sfile = String::new();
-            file.read_to_string(&mut sfile)
-                .map(|_| parse(&sfile[..], mtl_base_dir, basename))
+) -> IoResult<(
+    Vec<(String, Mesh, Option<MtlMaterial>, ElementKind)>,
+    Vec<ParseWarning>,
+)> {
+    let mut sfile = String::new();
+    let _ = File::open(path)?.read_to_string(&mut sfile)?;
+
+    Ok(parse(&sfile[..], mtl_base_dir, basename)?)
+}
+
+/// The kind of elements drawn by a mesh loaded from an obj file.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ElementKind {
+    /// Triangles, from `f` statements.
+    Faces,
+    /// Segments, from `l` statements. Each segment is stored as a degenerate triangle.
+    Lines,
+    /// Points, from `p` statements. Each point is stored as a degenerate triangle.
+    Points,
+}
+
+/// Sentinel for a missing texture coordinate or normal id.
+const NO_ID: u32 = u32::MAX;
+
+/// The smoothing group of the faces that are not smoothed.
+const NO_SMOOTHING: u32 = 0;
+
+/// The ids of the position, texture coordinates, and normal of a vertex, with its smoothing key.
+///
+/// Vertices are shared only by faces with the same smoothing key so that the computed normals
+/// are smoothed across a smoothing group only.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+struct VertexId {
+    coord: u32,
+    uv: u32,
+    normal: u32,
+    smoothing: u64,
+}
+
+/// The elements of a group.
+#[derive(Default)]
+struct GroupElements {
+    faces: Vec<[VertexId; 3]>,
+    lines: Vec<[VertexId; 2]>,
+    points: Vec<VertexId>,
+}
+
+/// The current smoothing state, set by `s` statements.
+struct Smoothing {
+    /// The current smoothing group, or `NO_SMOOTHING`.
+    group: u32,
+    /// The number of faces read so far, used to give each non-smoothed face its own key.
+    num_faces: u64,
+}
+
+impl Smoothing {
+    fn next_face_key(&mut self) -> u64 {
+        self.num_faces += 1;
+
+        if self.group == NO_SMOOTHING {
+            (1 << 32) + self.num_faces
+        } else {
+            self.group as u64
         }
-        Err(e) => Err(e),
     }
 }
 
 /// Parses a string representing an obj file.
+///
+/// Returns the named meshes with their materials and kind of elements, and the warnings emitted
+/// while parsing.
+///
+/// Polygons are triangulated by ear clipping so concave faces are supported. When the file has no
+/// normals, they are computed and smoothed across the faces of the same smoothing group (set by
+/// `s` statements). Faces appearing before any `s` statement are all smoothed together, and the
+/// faces after `s off` are flat. The `v x y z r g b` extension is supported for vertex colors.
 pub fn parse(
     string: &str,
     mtl_base_dir: &Path,
     basename: &str,
-) -> Vec<(String, Mesh, Option<MtlMaterial>)> {
+) -> Result<
+    (
+        Vec<(String, Mesh, Option<MtlMaterial>, ElementKind)>,
+        Vec<ParseWarning>,
+    ),
+    ParseError,
+> {
     let mut coords: Vec<Coord> = Vec::new();
+    let mut colors: Vec<Point3<f32>> = Vec::new();
+    let mut has_colors = false;
     let mut normals: Vec<Normal> = Vec::new();
     let mut uvs: Vec<UV> = Vec::new();
     let mut groups: HashMap<String, usize> = HashMap::new();
-    let mut groups_ids: Vec<Vec<Point3<u16>>> = Vec::new();
+    let mut groups_ids: Vec<GroupElements> = Vec::new();
     let mut curr_group: usize = 0;
+    let mut smoothing = Smoothing {
+        // Faces are smoothed together until the first `s` statement.
+        group: NO_SMOOTHING + 1,
+        num_faces: 0,
+    };
     let mut ignore_normals = false;
     let mut ignore_uvs = false;
     let mut mtllib = HashMap::new();
     let mut group2mtl = HashMap::new();
     let mut curr_mtl = None::<MtlMaterial>;
+    let mut warnings = Vec::new();
 
-    groups_ids.push(Vec::new());
+    groups_ids.push(GroupElements::default());
     let _ = groups.insert(basename.to_string(), 0);
 
     for (l, line) in string.lines().enumerate() {
+        let l = Line::new(l, line);
         let mut words = split_words(line);
         let tag = words.next();
         match tag {
@@ -97,35 +328,61 @@ pub fn parse(
             Some(w) => {
                 if w.len() != 0 && w.as_bytes()[0] != ('#' as u8) {
                     match w {
-                        "v" => coords.push(Point3::from(parse_v_or_vn(l, words))),
-                        "vn" => {
-                            if !ignore_normals {
-                                normals.push(parse_v_or_vn(l, words))
+                        "v" => {
+                            let (coord, color) = parse_v(l, words)?;
+                            coords.push(coord);
+                            colors.push(color.unwrap_or_else(|| Point3::new(1.0, 1.0, 1.0)));
+                            has_colors = has_colors || color.is_some();
+                        }
+                        "vn" => normals.push(parse_vn(l, words)?),
+                        "vt" => uvs.push(parse_vt(l, words)?),
+                        "f" => {
+                            let polygon = parse_vertex_ids(
+                                l,
+                                words,
+                                smoothing.next_face_key(),
+                                &coords[..],
+                                &uvs[..],
+                                &normals[..],
+                            )?;
+
+                            if polygon.is_empty() {
+                                return Err(l.error_at_end(ParseErrorKind::MissingComponents {
+                                    expected: 1,
+                                    found: 0,
+                                }));
+                            }
+
+                            for id in polygon.iter() {
+                                ignore_uvs = ignore_uvs || id.uv == NO_ID;
+                                ignore_normals = ignore_normals || id.normal == NO_ID;
                             }
+
+                            triangulate(&polygon, &coords, &mut groups_ids[curr_group].faces);
                         }
-                        "f" => parse_f(
-                            l,
-                            words,
-                            &coords[..],
-                            &uvs[..],
-                            &normals[..],
-                            &mut ignore_uvs,
-                            &mut ignore_normals,
-                            &mut groups_ids,
-                            curr_group,
-                        ),
-                        "vt" => {
-                            if !ignore_uvs {
-                                uvs.push(parse_vt(l, words))
+                        "l" => {
+                            let polyline =
+                                parse_vertex_ids(l, words, 0, &coords[..], &uvs[..], &normals[..])?;
+
+                            for segment in polyline.windows(2) {
+                                groups_ids[curr_group].lines.push([segment[0], segment[1]]);
                             }
                         }
-                        "g" => {
-                            curr_group = parse_g(l, words, basename, &mut groups, &mut groups_ids);
+                        "p" => {
+                            let points =
+                                parse_vertex_ids(l, words, 0, &coords[..], &uvs[..], &normals[..])?;
+                            groups_ids[curr_group].points.extend(points);
+                        }
+                        "g" | "o" => {
+                            curr_group = parse_g(words, basename, &mut groups, &mut groups_ids);
                             let _ = curr_mtl
                                 .as_ref()
                                 .map(|mtl| group2mtl.insert(curr_group, mtl.clone()));
                         }
-                        "mtllib" => parse_mtllib(l, words, mtl_base_dir, &mut mtllib),
+                        "s" => smoothing.group = parse_s(l, words)?,
+                        "mtllib" => {
+                            parse_mtllib(l, words, mtl_base_dir, &mut mtllib, &mut warnings)
+                        }
                         "usemtl" => {
                             curr_group = parse_usemtl(
                                 l,
@@ -136,10 +393,11 @@ pub fn parse(
                                 &mut groups,
                                 &mut groups_ids,
                                 &mut curr_mtl,
+                                &mut warnings,
                             )
                         }
                         _ => {
-                            println!("Warning: unknown line {} ignored: `{}'", l, line);
+                            warnings.push(l.warning(format!("unknown line ignored: `{}'", line)));
                         }
                     }
                 }
@@ -147,33 +405,52 @@ pub fn parse(
         }
     }
 
-    if uvs.is_empty() && ignore_uvs {
-        println!("Warning: some texture coordinates are missing. Dropping texture coordinates infos for every vertex.");
+    if !uvs.is_empty() && ignore_uvs {
+        warnings.push(ParseWarning {
+            line: 0,
+            message: "some texture coordinates are missing. Dropping texture coordinates infos for every vertex.".to_string(),
+        });
     }
 
-    if normals.is_empty() && ignore_normals {
-        println!("Warning: some normals are missing. Dropping normals infos for every vertex.");
+    if !normals.is_empty() && ignore_normals {
+        warnings.push(ParseWarning {
+            line: 0,
+            message: "some normals are missing. Dropping normals infos for every vertex."
+                .to_string(),
+        });
     }
 
-    reformat(
+    let meshes = reformat(
         coords,
-        if ignore_normals { None } else { Some(normals) },
-        if ignore_uvs { None } else { Some(uvs) },
+        if has_colors { Some(colors) } else { None },
+        if ignore_normals || normals.is_empty() {
+            None
+        } else {
+            Some(normals)
+        },
+        if ignore_uvs || uvs.is_empty() {
+            None
+        } else {
+            Some(uvs)
+        },
         groups_ids,
         groups,
         group2mtl,
-    )
+    )?;
+
+    Ok((meshes, warnings))
 }
 
 fn parse_usemtl<'a>(
-    l: usize,
+    l: Line,
     ws: Words<'a>,
     curr_group: usize,
     mtllib: &HashMap<String, MtlMaterial>,
     group2mtl: &mut HashMap<usize, MtlMaterial>,
     groups: &mut HashMap<String, usize>,
-    groups_ids: &mut Vec<Vec<Point3<u16>>>,
+    groups_ids: &mut Vec<GroupElements>,
     curr_mtl: &mut Option<MtlMaterial>,
+    warnings: &mut Vec<ParseWarning>,
 ) -> usize {
     let mname: Vec<&'a str> = ws.collect();
     let mname = mname.join(" ");
@@ -182,7 +459,7 @@ fn parse_usemtl<'a>(
         match mtllib.get(&mname) {
             None => {
                 *curr_mtl = None;
-                warn(l, &format!("could not find the material {}", mname)[..]);
+                warnings.push(l.warning(format!("could not find the material {}", mname)));
 
                 curr_group
             }
@@ -198,7 +475,6 @@ fn parse_usemtl<'a>(
                     g.push_str(&mname[..]);
 
                     let new_group = parse_g(
-                        l,
                         split_words(&g[..]),
                         "auto_generated_group_",
                         groups,
@@ -219,10 +495,11 @@ fn parse_usemtl<'a>(
 }
 
 fn parse_mtllib<'a>(
-    l: usize,
+    l: Line,
     ws: Words<'a>,
     mtl_base_dir: &Path,
     mtllib: &mut HashMap<String, MtlMaterial>,
+    warnings: &mut Vec<ParseWarning>,
 ) {
     let filename: Vec<&'a str> = ws.collect();
     let filename = filename.join(" ");
@@ -234,150 +511,228 @@ fn parse_mtllib<'a>(
     let ms = mtl::parse_file(&path);
 
     match ms {
-        Ok(ms) => {
+        Ok((ms, mtl_warnings)) => {
             for m in ms.into_iter() {
                 let _ = mtllib.insert(m.name.to_string(), m);
             }
+
+            for w in mtl_warnings {
+                warnings.push(l.warning(format!("{}: {}", path.display(), w)));
+            }
         }
-        Err(err) => warn(l, &format!("{}", err)[..]),
+        Err(err) => warnings.push(l.warning(format!("{}: {}", path.display(), err))),
     }
 }
 
-fn parse_v_or_vn(l: usize, mut ws: Words) -> Vector3<f32> {
-    let sx = ws
-        .next()
-        .unwrap_or_else(|| error(l, "3 components were expected, found 0."));
-    let sy = ws
-        .next()
-        .unwrap_or_else(|| error(l, "3 components were expected, found 1."));
-    let sz = ws
-        .next()
-        .unwrap_or_else(|| error(l, "3 components were expected, found 2."));
-
-    let x: Result<f32, _> = FromStr::from_str(sx);
-    let y: Result<f32, _> = FromStr::from_str(sy);
-    let z: Result<f32, _> = FromStr::from_str(sz);
-
-    let x =
-        x.unwrap_or_else(|e| error(l, &format!("failed to parse `{}' as a f32: {}", sx, e)[..]));
-    let y =
-        y.unwrap_or_else(|e| error(l, &format!("failed to parse `{}' as a f32: {}", sy, e)[..]));
-    let z =
-        z.unwrap_or_else(|e| error(l, &format!("failed to parse `{}' as a f32: {}", sz, e)[..]));
-
-    Vector3::new(x, y, z)
+fn parse_v<'a>(l: Line<'a>, mut ws: Words<'a>) -> Result<(Coord, Option<Point3<f32>>), ParseError> {
+    let mut values = [0.0f32; 6];
+    let mut found = 0;
+
+    for (i, word) in ws.by_ref().take(6).enumerate() {
+        values[i] = l.parse_number(word)?;
+        found += 1;
+    }
+
+    if found < 3 {
+        return Err(l.error_at_end(ParseErrorKind::MissingComponents { expected: 3, found }));
+    }
+
+    let coord = Point3::new(values[0], values[1], values[2]);
+    // Four components are a position with a weight, which is ignored.
+    let color = if found == 6 {
+        Some(Point3::new(values[3], values[4], values[5]))
+    } else {
+        None
+    };
+
+    Ok((coord, color))
 }
 
-fn parse_f<'a>(
-    l: usize,
+fn parse_vn<'a>(l: Line<'a>, mut ws: Words<'a>) -> Result<Vector3<f32>, ParseError> {
+    let sx = l.next_component(&mut ws, 3, 0)?;
+    let sy = l.next_component(&mut ws, 3, 1)?;
+    let sz = l.next_component(&mut ws, 3, 2)?;
+
+    Ok(Vector3::new(
+        l.parse_number(sx)?,
+        l.parse_number(sy)?,
+        l.parse_number(sz)?,
+    ))
+}
+
+/// Parses the vertices of a `f`, `l`, or `p` statement.
+fn parse_vertex_ids<'a>(
+    l: Line<'a>,
     ws: Words<'a>,
+    smoothing: u64,
     coords: &[Point3<f32>],
     uvs: &[Point2<f32>],
     normals: &[Vector3<f32>],
-    ignore_uvs: &mut bool,
-    ignore_normals: &mut bool,
-    groups_ids: &mut Vec<Vec<Point3<u16>>>,
-    curr_group: usize,
-) {
+) -> Result<Vec<VertexId>, ParseError> {
+    let mut res = Vec::new();
+
     // Four formats possible: v   v/t   v//n   v/t/n
-    let mut i = 0;
     for word in ws {
         let mut curr_ids: Vector3<i32> = Bounded::max_value();
 
-        for (i, w) in word.split('/').enumerate() {
+        for (i, w) in word.split('/').enumerate().take(3) {
             if i == 0 || w.len() != 0 {
-                let idx: Result<i32, _> = FromStr::from_str(w);
-                match idx {
-                    Ok(id) => curr_ids[i] = id - 1,
-                    Err(e) => error(l, &format!("failed to parse `{}' as a i32: {}", w, e)[..]),
-                }
+                let id = l.parse_number::<i32>(w)?;
+                curr_ids[i] = id
+                    .checked_sub(1)
+                    .ok_or_else(|| l.error_at(w, ParseErrorKind::IndexOutOfBounds(id)))?;
             }
         }
 
-        if i > 2 {
-            // on the fly triangulation as trangle fan
-            let g = &mut groups_ids[curr_group];
-            let p1 = (*g)[g.len() - i];
-            let p2 = (*g)[g.len() - 1];
-            g.push(p1);
-            g.push(p2);
-        }
+        let coord = resolve_index(l, word, curr_ids.x, coords.len())?;
+        let uv = if curr_ids.y == i32::max_value() {
+            NO_ID
+        } else {
+            resolve_index(l, word, curr_ids.y, uvs.len())?
+        };
+        let normal = if curr_ids.z == i32::max_value() {
+            NO_ID
+        } else {
+            resolve_index(l, word, curr_ids.z, normals.len())?
+        };
+
+        res.push(VertexId {
+            coord,
+            uv,
+            normal,
+            smoothing,
+        });
+    }
 
-        if curr_ids.y == i32::max_value() as i32 {
-            *ignore_uvs = true;
-        }
+    Ok(res)
+}
 
-        if curr_ids.z == i32::max_value() as i32 {
-            *ignore_normals = true;
-        }
+/// Turns a 0-based, possibly relative, index into an absolute index into an array of `len`
+/// elements.
+fn resolve_index(l: Line, word: &str, id: i32, len: usize) -> Result<u32, ParseError> {
+    // Relative indices are negative, -1 being the last element.
+    let abs = if id < 0 {
+        len as i64 + id as i64 + 1
+    } else {
+        id as i64
+    };
 
-        // Handle relatives indice
-        let x;
-        let y;
-        let z;
+    if abs < 0 || abs as usize >= len {
+        // Report the index as written in the file.
+        return Err(l.error_at(word, ParseErrorKind::IndexOutOfBounds(id + 1)));
+    }
 
-        if curr_ids.x < 0 {
-            x = coords.len() as i32 + curr_ids.x + 1;
-        } else {
-            x = curr_ids.x;
-        }
+    Ok(abs as u32)
+}
 
-        if curr_ids.y < 0 {
-            y = uvs.len() as i32 + curr_ids.y + 1;
-        } else {
-            y = curr_ids.y;
-        }
+/// Triangulates a polygon by ear clipping, and appends the triangles to `out`.
+///
+/// Polygons with less than three vertices are completed to form a degenerate triangle.
+fn triangulate(polygon: &[VertexId], coords: &[Coord], out: &mut Vec<[VertexId; 3]>) {
+    if polygon.len() < 3 {
+        let last = polygon[polygon.len() - 1];
+        let second = polygon.get(1).cloned().unwrap_or(last);
+        out.push([polygon[0], second, last]);
+        return;
+    }
 
-        if curr_ids.z < 0 {
-            z = normals.len() as i32 + curr_ids.z + 1;
-        } else {
-            z = curr_ids.z;
-        }
+    let pts: Vec<Coord> = polygon.iter().map(|id| coords[id.coord as usize]).collect();
 
-        assert!(x >= 0 && y >= 0 && z >= 0);
-        groups_ids[curr_group].push(Point3::new(x as u16, y as u16, z as u16));
+    // Compute the polygon normal with Newell's method, and project the polygon on the plane
+    // orthogonal to its dominant axis.
+    let mut normal = Vector3::zeros();
 
-        i = i + 1;
+    for (i, a) in pts.iter().enumerate() {
+        let b = pts[(i + 1) % pts.len()];
+        normal += Vector3::new(
+            (a.y - b.y) * (a.z + b.z),
+            (a.z - b.z) * (a.x + b.x),
+            (a.x - b.x) * (a.y + b.y),
+        );
     }
 
-    // there is not enough vertex to form a triangle. Complete it.
-    if i < 2 {
-        for _ in 0usize..3 - i {
-            let last = (*groups_ids)[curr_group].last().unwrap().clone();
-            groups_ids[curr_group].push(last);
-        }
+    let axis = normal.iamax();
+    let (u, v) = ((axis + 1) % 3, (axis + 2) % 3);
+    // Makes the projected polygon counterclockwise.
+    let sign = if normal[axis] < 0.0 { -1.0 } else { 1.0 };
+    let pts2: Vec<Point2<f32>> = pts.iter().map(|p| Point2::new(p[u], p[v] * sign)).collect();
+
+    let cross = |a: usize, b: usize, c: usize| {
+        let (a, b, c) = (pts2[a], pts2[b], pts2[c]);
+        (b - a).perp(&(c - a))
+    };
+
+    let mut remaining: Vec<usize> = (0..polygon.len()).collect();
+
+    while remaining.len() > 3 {
+        let n = remaining.len();
+        let is_ear = |i: usize| {
+            let (a, b, c) = (
+                remaining[(i + n - 1) % n],
+                remaining[i],
+                remaining[(i + 1) % n],
+            );
+
+            if cross(a, b, c) <= 0.0 {
+                return false;
+            }
+
+            // No other vertex may lie inside of the ear.
+            remaining.iter().all(|&p| {
+                p == a
+                    || p == b
+                    || p == c
+                    || pts2[p] == pts2[a]
+                    || pts2[p] == pts2[b]
+                    || pts2[p] == pts2[c]
+                    || cross(a, b, p) < 0.0
+                    || cross(b, c, p) < 0.0
+                    || cross(c, a, p) < 0.0
+            })
+        };
+
+        // Degenerate polygons may have no ear, a fan is used for them.
+        let ear = (0..n).find(|i| is_ear(*i)).unwrap_or(1);
+        let (a, b, c) = (
+            remaining[(ear + n - 1) % n],
+            remaining[ear],
+            remaining[(ear + 1) % n],
+        );
+
+        out.push([polygon[a], polygon[b], polygon[c]]);
+        let _ = remaining.remove(ear);
     }
+
+    out.push([
+        polygon[remaining[0]],
+        polygon[remaining[1]],
+        polygon[remaining[2]],
+    ]);
 }
 
-fn parse_vt(l: usize, mut ws: Words) -> UV {
-    let _0 = "0.0";
-    let sx = ws
-        .next()
-        .unwrap_or_else(|| error(l, "at least 2 components were expected, found 0."));
-    let sy = ws
-        .next()
-        .unwrap_or_else(|| error(l, "at least 2 components were expected, found 1."));
-    // let sz  = ws.next().unwrap_or(_0);
-
-    let x: Result<f32, _> = FromStr::from_str(sx);
-    let y: Result<f32, _> = FromStr::from_str(sy);
-    // let z: Option<f32> = FromStr::from_str(sz);
-
-    let x =
-        x.unwrap_or_else(|e| error(l, &format!("failed to parse `{}' as a f32: {}", sx, e)[..]));
-    let y =
-        y.unwrap_or_else(|e| error(l, &format!("failed to parse `{}' as a f32: {}", sy, e)[..]));
-    // let z = z.unwrap_or_else(|| error(l, "failed to parse `" + sz + "' as a f32."));
-
-    Point2::new(x, y)
+fn parse_vt<'a>(l: Line<'a>, mut ws: Words<'a>) -> Result<UV, ParseError> {
+    let sx = l.next_component(&mut ws, 2, 0)?;
+    let sy = l.next_component(&mut ws, 2, 1)?;
+    // The optional third component is ignored.
+
+    Ok(Point2::new(l.parse_number(sx)?, l.parse_number(sy)?))
+}
+
+fn parse_s<'a>(l: Line<'a>, mut ws: Words<'a>) -> Result<u32, ParseError> {
+    let word = l.next_component(&mut ws, 1, 0)?;
+
+    if word == "off" {
+        Ok(NO_SMOOTHING)
+    } else {
+        l.parse_number(word)
+    }
 }
 
 fn parse_g<'a>(
-    _: usize,
     ws: Words<'a>,
     prefix: &str,
     groups: &mut HashMap<String, usize>,
-    groups_ids: &mut Vec<Vec<Point3<u16>>>,
+    groups_ids: &mut Vec<GroupElements>,
 ) -> usize {
     let suffix: Vec<&'a str> = ws.collect();
     let suffix = suffix.join(" ");
@@ -390,7 +745,7 @@ fn parse_g<'a>(
     match groups.entry(name) {
         Entry::Occupied(entry) => *entry.into_mut(),
         Entry::Vacant(entry) => {
-            groups_ids.push(Vec::new());
+            groups_ids.push(GroupElements::default());
 
             let val = groups_ids.len() - 1;
             *entry.insert(val)
@@ -400,67 +755,113 @@ fn parse_g<'a>(
 
 fn reformat(
     coords: Vec<Coord>,
+    colors: Option<Vec<Point3<f32>>>,
     normals: Option<Vec<Normal>>,
     uvs: Option<Vec<UV>>,
-    groups_ids: Vec<Vec<Point3<u16>>>,
+    groups_ids: Vec<GroupElements>,
     groups: HashMap<String, usize>,
     group2mtl: HashMap<usize, MtlMaterial>,
-) -> Vec<(String, Mesh, Option<MtlMaterial>)> {
-    let mut vt2id: HashMap<Point3<u16>, u16> = HashMap::new();
-    let mut vertex_ids: Vec<u16> = Vec::new();
+) -> Result<Vec<(String, Mesh, Option<MtlMaterial>, ElementKind)>, ParseError> {
+    let mut vt2id: HashMap<VertexId, u16> = HashMap::new();
     let mut resc: Vec<Coord> = Vec::new();
+    let mut resk: Option<Vec<Point3<f32>>> = colors.as_ref().map(|_| Vec::new());
     let mut resn: Option<Vec<Normal>> = normals.as_ref().map(|_| Vec::new());
     let mut resu: Option<Vec<UV>> = uvs.as_ref().map(|_| Vec::new());
-    let mut resfs: Vec<Vec<Point3<u16>>> = Vec::new();
+    let mut resfs: Vec<(String, Vec<Point3<u16>>, Option<MtlMaterial>, ElementKind)> = Vec::new();
     let mut allfs: Vec<Point3<u16>> = Vec::new();
-    let mut names: Vec<String> = Vec::new();
-    let mut mtls: Vec<Option<MtlMaterial>> = Vec::new();
 
-    for (name, i) in groups.into_iter() {
-        names.push(name);
-        mtls.push(group2mtl.get(&i).map(|m| m.clone()));
-
-        for point in groups_ids[i].iter() {
-            let idx = match vt2id.get(point) {
-                Some(i) => {
-                    vertex_ids.push(*i);
-                    None
-                }
-                None => {
-                    let idx = resc.len() as u16;
+    let mut vertex_id = |mut id: VertexId| -> Result<u16, ParseError> {
+        // The smoothing groups only matter when the normals are computed.
+        if normals.is_some() {
+            id.smoothing = 0;
+        }
 
-                    resc.push(coords[point.x as usize]);
+        if let Some(idx) = vt2id.get(&id) {
+            return Ok(*idx);
+        }
 
-                    let _ = resu
-                        .as_mut()
-                        .map(|l| l.push((*uvs.as_ref().unwrap())[point.y as usize]));
-                    let _ = resn
-                        .as_mut()
-                        .map(|l| l.push((*normals.as_ref().unwrap())[point.z as usize]));
+        if resc.len() > u16::max_value() as usize {
+            return Err(ParseError {
+                line: 0,
+                column: 0,
+                kind: ParseErrorKind::TooManyVertices,
+            });
+        }
 
-                    vertex_ids.push(idx);
+        let idx = resc.len() as u16;
 
-                    Some(idx)
-                }
-            };
+        resc.push(coords[id.coord as usize]);
 
-            let _ = idx.map(|i| vt2id.insert(point.clone(), i));
+        if let (Some(resk), Some(colors)) = (resk.as_mut(), colors.as_ref()) {
+            resk.push(colors[id.coord as usize]);
         }
 
-        let mut resf = Vec::with_capacity(vertex_ids.len() / 3);
+        if let (Some(resu), Some(uvs)) = (resu.as_mut(), uvs.as_ref()) {
+            resu.push(
+                uvs.get(id.uv as usize)
+                    .cloned()
+                    .unwrap_or_else(Point2::origin),
+            );
+        }
 
-        assert!(vertex_ids.len() % 3 == 0);
+        if let (Some(resn), Some(normals)) = (resn.as_mut(), normals.as_ref()) {
+            resn.push(
+                normals
+                    .get(id.normal as usize)
+                    .cloned()
+                    .unwrap_or_else(Vector3::z),
+            );
+        }
 
-        for f in vertex_ids[..].chunks(3) {
-            resf.push(Point3::new(f[0], f[1], f[2]));
-            allfs.push(Point3::new(f[0], f[1], f[2]));
+        let _ = vt2id.insert(id, idx);
+        Ok(idx)
+    };
+
+    // Sort the groups by order of appearance.
+    let mut groups: Vec<(String, usize)> = groups.into_iter().collect();
+    groups.sort_by_key(|g| g.1);
+
+    for (name, i) in groups.into_iter() {
+        let mtl = group2mtl.get(&i).cloned();
+        let elements = &groups_ids[i];
+        let mut faces = Vec::with_capacity(elements.faces.len());
+        let mut lines = Vec::with_capacity(elements.lines.len());
+        let mut points = Vec::with_capacity(elements.points.len());
+
+        for f in elements.faces.iter() {
+            let face = Point3::new(vertex_id(f[0])?, vertex_id(f[1])?, vertex_id(f[2])?);
+            faces.push(face);
+            allfs.push(face);
         }
 
-        resfs.push(resf);
-        vertex_ids.clear();
+        for s in elements.lines.iter() {
+            let (a, b) = (vertex_id(s[0])?, vertex_id(s[1])?);
+            lines.push(Point3::new(a, b, a));
+        }
+
+        for p in elements.points.iter() {
+            let a = vertex_id(*p)?;
+            points.push(Point3::new(a, a, a));
+        }
+
+        resfs.push((name.clone(), faces, mtl.clone(), ElementKind::Faces));
+        resfs.push((name.clone(), lines, mtl.clone(), ElementKind::Lines));
+        resfs.push((name, points, mtl, ElementKind::Points));
     }
 
-    let resn = resn.unwrap_or_else(|| Mesh::compute_normals_array(&resc[..], &allfs[..]));
+    let resn = resn.unwrap_or_else(|| {
+        // Only the triangles are used, the vertices of the lines and points get an arbitrary
+        // normal.
+        let mut normals = Mesh::compute_normals_array(&resc[..], &allfs[..]);
+
+        for n in normals.iter_mut() {
+            if !n.iter().all(|x| x.is_finite()) {
+                *n = Vector3::z();
+            }
+        }
+
+        normals
+    });
     let resn = Arc::new(RwLock::new(GPUVec::new(
         resn,
         BufferType::Array,
@@ -479,21 +880,18 @@ fn reformat(
     )));
 
     let mut meshes = Vec::new();
-    for ((fs, name), mtl) in resfs
-        .into_iter()
-        .zip(names.into_iter())
-        .zip(mtls.into_iter())
-    {
+    for (name, fs, mtl, kind) in resfs.into_iter() {
         if fs.len() != 0 {
             let fs = Arc::new(RwLock::new(GPUVec::new(
                 fs,
                 BufferType::ElementArray,
                 AllocationType::StaticDraw,
             )));
-            let mesh = Mesh::new_with_gpu_vectors(resc.clone(), fs, resn.clone(), resu.clone());
-            meshes.push((name, mesh, mtl))
+            let mut mesh = Mesh::new_with_gpu_vectors(resc.clone(), fs, resn.clone(), resu.clone());
+            mesh.set_colors(resk.clone());
+            meshes.push((name, mesh, mtl, kind))
         }
     }
 
-    meshes
+    Ok(meshes)
 }
@@ -0,0 +1,452 @@
+//! PCD (Point Cloud Library) point cloud loader.
+//!
+//! Supports the `ascii`, `binary`, and `binary_compressed` data formats. The point positions,
+//! normals, and `rgb` or `rgba` colors are loaded. Points with NaN coordinates, which mark the
+//! invalid measurements of organized point clouds, are skipped.
+
+use std::fs::File;
+use std::io::{self, Read, Result as IoResult};
+use std::path::Path;
+use std::str;
+
+use crate::loader::point_set::PointSet;
+use na::{Point3, Vector3};
+
+#[derive(Clone, Copy, PartialEq)]
+enum DataFormat {
+    Ascii,
+    Binary,
+    BinaryCompressed,
+}
+
+struct Field {
+    name: String,
+    size: usize,
+    ty: u8,
+    count: usize,
+}
+
+impl Field {
+    fn byte_len(&self) -> usize {
+        self.size * self.count
+    }
+}
+
+struct Header {
+    fields: Vec<Field>,
+    num_points: usize,
+    // The size of a point, and of all the points, in bytes. They are checked not to overflow, so
+    // the offsets within the binary data do not overflow either.
+    point_size: usize,
+    data_size: usize,
+    data: DataFormat,
+}
+
+impl Header {
+    fn find(&self, name: &str) -> Option<usize> {
+        self.fields.iter().position(|f| f.name == name)
+    }
+}
+
+fn invalid(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("PCD: {}", msg))
+}
+
+/// Parses a PCD file.
+pub fn parse_file(path: &Path) -> IoResult<PointSet> {
+    let mut data = Vec::new();
+    let _ = File::open(path)?.read_to_end(&mut data)?;
+
+    parse(&data)
+}
+
+/// Parses the content of a PCD file.
+pub fn parse(data: &[u8]) -> IoResult<PointSet> {
+    let (header, body_start) = parse_header(data)?;
+    let body = &data[body_start..];
+
+    let position = (header.find("x"), header.find("y"), header.find("z"));
+    let (x, y, z) = match position {
+        (Some(x), Some(y), Some(z)) => (x, y, z),
+        _ => {
+            return Err(invalid(
+                "the points must have x, y, and z fields.".to_string(),
+            ))
+        }
+    };
+    let normal = match (
+        header.find("normal_x"),
+        header.find("normal_y"),
+        header.find("normal_z"),
+    ) {
+        (Some(nx), Some(ny), Some(nz)) => Some((nx, ny, nz)),
+        _ => None,
+    };
+    let color = header.find("rgb").or_else(|| header.find("rgba"));
+
+    // Each field value is read as `(value, raw bits)`: packed colors are stored in the bits of
+    // their value.
+    let mut values = vec![(0.0f64, 0u32); header.fields.len()];
+    let mut set = PointSet::new(Vec::new());
+    let mut normals = Vec::new();
+    let mut colors = Vec::new();
+
+    let mut push_point = |values: &[(f64, u32)]| {
+        let pt = Point3::new(values[x].0 as f32, values[y].0 as f32, values[z].0 as f32);
+
+        if pt.iter().any(|c| c.is_nan()) {
+            return;
+        }
+
+        set.coords.push(pt);
+
+        if let Some((nx, ny, nz)) = normal {
+            normals.push(Vector3::new(
+                values[nx].0 as f32,
+                values[ny].0 as f32,
+                values[nz].0 as f32,
+            ));
+        }
+
+        if let Some(c) = color {
+            let bits = values[c].1;
+            colors.push(Point3::new(
+                ((bits >> 16) & 0xff) as f32 / 255.0,
+                ((bits >> 8) & 0xff) as f32 / 255.0,
+                (bits & 0xff) as f32 / 255.0,
+            ));
+        }
+    };
+
+    match header.data {
+        DataFormat::Ascii => {
+            let text = str::from_utf8(body)
+                .map_err(|_| invalid("the ASCII data is not valid UTF-8.".to_string()))?;
+            let mut lines = text.lines().filter(|l| !l.trim().is_empty());
+
+            for _ in 0..header.num_points {
+                let line = lines
+                    .next()
+                    .ok_or_else(|| invalid("unexpected end of file.".to_string()))?;
+                let mut words = line.split_whitespace();
+
+                for (field, value) in header.fields.iter().zip(values.iter_mut()) {
+                    for i in 0..field.count {
+                        let word = words
+                            .next()
+                            .ok_or_else(|| invalid(format!("missing values in `{}'.", line)))?;
+
+                        if i == 0 {
+                            *value = parse_ascii_value(field, word)?;
+                        }
+                    }
+                }
+
+                push_point(&values);
+            }
+        }
+        DataFormat::Binary => {
+            if body.len() < header.data_size {
+                return Err(invalid("unexpected end of file.".to_string()));
+            }
+
+            for point in body.chunks(header.point_size).take(header.num_points) {
+                let mut offset = 0;
+
+                for (field, value) in header.fields.iter().zip(values.iter_mut()) {
+                    *value = decode_value(field, &point[offset..offset + field.size]);
+                    offset += field.byte_len();
+                }
+
+                push_point(&values);
+            }
+        }
+        DataFormat::BinaryCompressed => {
+            if body.len() < 8 {
+                return Err(invalid("unexpected end of file.".to_string()));
+            }
+
+            let compressed_size = read_u32(&body[0..]) as usize;
+            let decompressed_size = read_u32(&body[4..]) as usize;
+
+            if body.len() - 8 < compressed_size {
+                return Err(invalid("unexpected end of file.".to_string()));
+            }
+
+            if decompressed_size < header.data_size {
+                return Err(invalid("the compressed data is too short.".to_string()));
+            }
+
+            let decompressed = lzf_decompress(&body[8..8 + compressed_size], decompressed_size)?;
+
+            // The compressed data is stored field by field rather than point by point.
+            let mut field_starts = Vec::with_capacity(header.fields.len());
+            let mut start = 0;
+
+            for field in header.fields.iter() {
+                field_starts.push(start);
+                start += field.byte_len() * header.num_points;
+            }
+
+            for i in 0..header.num_points {
+                for ((field, start), value) in header
+                    .fields
+                    .iter()
+                    .zip(field_starts.iter())
+                    .zip(values.iter_mut())
+                {
+                    let offset = start + i * field.byte_len();
+                    *value = decode_value(field, &decompressed[offset..offset + field.size]);
+                }
+
+                push_point(&values);
+            }
+        }
+    }
+
+    if normal.is_some() {
+        set.normals = Some(normals);
+    }
+
+    if color.is_some() {
+        set.colors = Some(colors);
+    }
+
+    Ok(set)
+}
+
+fn parse_ascii_value(field: &Field, word: &str) -> IoResult<(f64, u32)> {
+    let error = || invalid(format!("invalid number `{}'.", word));
+
+    match field.ty {
+        b'F' => {
+            let val = word.parse::<f64>().map_err(|_| error())?;
+            Ok((val, (val as f32).to_bits()))
+        }
+        b'U' => {
+            let val = word.parse::<u64>().map_err(|_| error())?;
+            Ok((val as f64, val as u32))
+        }
+        _ => {
+            let val = word.parse::<i64>().map_err(|_| error())?;
+            Ok((val as f64, val as u32))
+        }
+    }
+}
+
+fn decode_value(field: &Field, bytes: &[u8]) -> (f64, u32) {
+    let mut buf = [0u8; 8];
+    buf[..field.size].copy_from_slice(bytes);
+    let bits = read_u32(&buf);
+
+    let val = match (field.ty, field.size) {
+        (b'F', 4) => f32::from_bits(bits) as f64,
+        (b'F', _) => f64::from_le_bytes(buf),
+        (b'U', 1) => buf[0] as f64,
+        (b'U', 2) => u16::from_le_bytes([buf[0], buf[1]]) as f64,
+        (b'U', 4) => bits as f64,
+        (b'U', _) => u64::from_le_bytes(buf) as f64,
+        (_, 1) => buf[0] as i8 as f64,
+        (_, 2) => i16::from_le_bytes([buf[0], buf[1]]) as f64,
+        (_, 4) => bits as i32 as f64,
+        (_, _) => i64::from_le_bytes(buf) as f64,
+    };
+
+    (val, bits)
+}
+
+fn read_u32(bytes: &[u8]) -> u32 {
+    u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+/// The largest ratio between the sizes of decompressed and compressed LZF data, reached by
+/// back references of 264 bytes encoded with 3 bytes.
+const LZF_MAX_RATIO: usize = 88;
+
+/// Decompresses data compressed with the LZF algorithm.
+fn lzf_decompress(input: &[u8], output_len: usize) -> IoResult<Vec<u8>> {
+    let corrupted = || invalid("corrupted compressed data.".to_string());
+
+    // The decompressed size is read from the file, so it is checked before allocating.
+    if input.len().saturating_mul(LZF_MAX_RATIO) < output_len {
+        return Err(corrupted());
+    }
+
+    let mut output = Vec::with_capacity(output_len);
+    let mut i = 0;
+
+    while i < input.len() {
+        let ctrl = input[i] as usize;
+        i += 1;
+
+        if ctrl < 32 {
+            // Literal run.
+            let len = ctrl + 1;
+
+            if i + len > input.len() || output.len() + len > output_len {
+                return Err(corrupted());
+            }
+
+            output.extend_from_slice(&input[i..i + len]);
+            i += len;
+        } else {
+            // Back reference.
+            let mut len = ctrl >> 5;
+
+            if len == 7 {
+                len += *input.get(i).ok_or_else(corrupted)? as usize;
+                i += 1;
+            }
+
+            let back = ((ctrl & 0x1f) << 8) + *input.get(i).ok_or_else(corrupted)? as usize + 1;
+            i += 1;
+
+            if back > output.len() || output.len() + len + 2 > output_len {
+                return Err(corrupted());
+            }
+
+            let start = output.len() - back;
+
+            for k in 0..len + 2 {
+                let byte = output[start + k];
+                output.push(byte);
+            }
+        }
+    }
+
+    if output.len() != output_len {
+        return Err(corrupted());
+    }
+
+    Ok(output)
+}
+
+fn parse_header(data: &[u8]) -> IoResult<(Header, usize)> {
+    let mut pos = 0;
+    let mut names = Vec::new();
+    let mut sizes = Vec::new();
+    let mut types = Vec::new();
+    let mut counts = Vec::new();
+    let mut width = None;
+    let mut height = None;
+    let mut num_points = None;
+
+    let format = loop {
+        let end = data[pos..]
+            .iter()
+            .position(|b| *b == b'\n')
+            .unwrap_or(data.len() - pos);
+        let line = str::from_utf8(&data[pos..pos + end])
+            .map_err(|_| invalid("the header is not valid UTF-8.".to_string()))?
+            .trim();
+        pos = (pos + end + 1).min(data.len());
+
+        if line.starts_with('#') || line.is_empty() {
+            if pos == data.len() {
+                return Err(invalid("missing DATA declaration.".to_string()));
+            }
+
+            continue;
+        }
+
+        let mut words = line.split_whitespace();
+        let key = words.next().unwrap_or("");
+        let args: Vec<&str> = words.collect();
+        let parse_usizes = |args: &[&str]| -> IoResult<Vec<usize>> {
+            args.iter()
+                .map(|a| {
+                    a.parse()
+                        .map_err(|_| invalid(format!("invalid line `{}'.", line)))
+                })
+                .collect()
+        };
+
+        match key {
+            "FIELDS" => names = args.iter().map(|a| a.to_string()).collect(),
+            "SIZE" => sizes = parse_usizes(&args)?,
+            "TYPE" => types = args.iter().map(|a| a.as_bytes()[0]).collect(),
+            "COUNT" => counts = parse_usizes(&args)?,
+            "WIDTH" => width = parse_usizes(&args)?.first().cloned(),
+            "HEIGHT" => height = parse_usizes(&args)?.first().cloned(),
+            "POINTS" => num_points = parse_usizes(&args)?.first().cloned(),
+            "DATA" => match args.first().cloned() {
+                Some("ascii") => break DataFormat::Ascii,
+                Some("binary") => break DataFormat::Binary,
+                Some("binary_compressed") => break DataFormat::BinaryCompressed,
+                _ => return Err(invalid(format!("unknown data format `{}'.", line))),
+            },
+            // VERSION, VIEWPOINT.
+            _ => {}
+        }
+
+        if pos == data.len() {
+            return Err(invalid("missing DATA declaration.".to_string()));
+        }
+    };
+
+    if counts.is_empty() {
+        counts = vec![1; names.len()];
+    }
+
+    if sizes.len() != names.len() || types.len() != names.len() || counts.len() != names.len() {
+        return Err(invalid(
+            "FIELDS, SIZE, TYPE, and COUNT have different lengths.".to_string(),
+        ));
+    }
+
+    let mut fields = Vec::with_capacity(names.len());
+
+    for (((name, size), ty), count) in names
+        .into_iter()
+        .zip(sizes.into_iter())
+        .zip(types.into_iter())
+        .zip(counts.into_iter())
+    {
+        if ![1, 2, 4, 8].contains(&size) || !b"FUI".contains(&ty) || (ty == b'F' && size < 4) {
+            return Err(invalid(format!(
+                "unsupported type {}{} for the field `{}'.",
+                ty as char, size, name
+            )));
+        }
+
+        if count == 0 {
+            return Err(invalid(format!("the field `{}' has no value.", name)));
+        }
+
+        fields.push(Field {
+            name,
+            size,
+            ty,
+            count,
+        });
+    }
+
+    let too_large = || invalid("the point cloud is too large.".to_string());
+    let num_points = match (num_points, width, height) {
+        (Some(n), _, _) => n,
+        (None, Some(w), Some(h)) => w.checked_mul(h).ok_or_else(too_large)?,
+        _ => return Err(invalid("missing POINTS declaration.".to_string())),
+    };
+
+    let mut point_size = 0usize;
+
+    for field in fields.iter() {
+        point_size = field
+            .size
+            .checked_mul(field.count)
+            .and_then(|len| point_size.checked_add(len))
+            .ok_or_else(too_large)?;
+    }
+
+    let data_size = point_size.checked_mul(num_points).ok_or_else(too_large)?;
+
+    let header = Header {
+        fields,
+        num_points,
+        point_size,
+        data_size,
+        data: format,
+    };
+
+    Ok((header, pos))
+}
@@ -0,0 +1,418 @@
+//! PLY point cloud and mesh loader.
+//!
+//! Supports the ASCII, binary little endian, and binary big endian formats. The vertex
+//! positions, normals, and colors are loaded, as well as the faces which are triangulated.
+
+use std::fs::File;
+use std::io::{self, Read, Result as IoResult};
+use std::path::Path;
+use std::str::{self, SplitWhitespace};
+
+use crate::loader::point_set::PointSet;
+use na::{Point3, Vector3};
+
+#[derive(Clone, Copy, PartialEq)]
+enum Format {
+    Ascii,
+    BinaryLittleEndian,
+    BinaryBigEndian,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum ScalarType {
+    I8,
+    U8,
+    I16,
+    U16,
+    I32,
+    U32,
+    F32,
+    F64,
+}
+
+impl ScalarType {
+    fn from_name(name: &str) -> Option<ScalarType> {
+        match name {
+            "char" | "int8" => Some(ScalarType::I8),
+            "uchar" | "uint8" => Some(ScalarType::U8),
+            "short" | "int16" => Some(ScalarType::I16),
+            "ushort" | "uint16" => Some(ScalarType::U16),
+            "int" | "int32" => Some(ScalarType::I32),
+            "uint" | "uint32" => Some(ScalarType::U32),
+            "float" | "float32" => Some(ScalarType::F32),
+            "double" | "float64" => Some(ScalarType::F64),
+            _ => None,
+        }
+    }
+
+    fn size(self) -> usize {
+        match self {
+            ScalarType::I8 | ScalarType::U8 => 1,
+            ScalarType::I16 | ScalarType::U16 => 2,
+            ScalarType::I32 | ScalarType::U32 | ScalarType::F32 => 4,
+            ScalarType::F64 => 8,
+        }
+    }
+
+    /// The value a color component of this type has at full intensity.
+    fn color_scale(self) -> f64 {
+        match self {
+            ScalarType::I8 | ScalarType::U8 => 255.0,
+            ScalarType::I16 | ScalarType::U16 => 65535.0,
+            _ => 1.0,
+        }
+    }
+}
+
+enum Property {
+    Scalar(String, ScalarType),
+    List(String, ScalarType, ScalarType),
+}
+
+impl Property {
+    fn name(&self) -> &str {
+        match *self {
+            Property::Scalar(ref name, _) | Property::List(ref name, _, _) => name,
+        }
+    }
+}
+
+struct Element {
+    name: String,
+    count: usize,
+    properties: Vec<Property>,
+}
+
+impl Element {
+    fn find(&self, names: &[&str]) -> Option<usize> {
+        self.properties
+            .iter()
+            .position(|p| names.contains(&p.name()))
+    }
+
+    fn scalar_type(&self, id: usize) -> ScalarType {
+        match self.properties[id] {
+            Property::Scalar(_, ty) | Property::List(_, _, ty) => ty,
+        }
+    }
+}
+
+enum Reader<'a> {
+    Ascii(SplitWhitespace<'a>),
+    Binary {
+        data: &'a [u8],
+        pos: usize,
+        big_endian: bool,
+    },
+}
+
+impl<'a> Reader<'a> {
+    fn read(&mut self, ty: ScalarType) -> IoResult<f64> {
+        match *self {
+            Reader::Ascii(ref mut words) => {
+                let word = words
+                    .next()
+                    .ok_or_else(|| invalid("unexpected end of file.".to_string()))?;
+                word.parse::<f64>()
+                    .map_err(|_| invalid(format!("invalid number `{}'.", word)))
+            }
+            Reader::Binary {
+                data,
+                ref mut pos,
+                big_endian,
+            } => {
+                let size = ty.size();
+
+                if *pos + size > data.len() {
+                    return Err(invalid("unexpected end of file.".to_string()));
+                }
+
+                let mut buf = [0u8; 8];
+                buf[..size].copy_from_slice(&data[*pos..*pos + size]);
+                *pos += size;
+
+                if big_endian {
+                    buf[..size].reverse();
+                }
+
+                let val = match ty {
+                    ScalarType::I8 => buf[0] as i8 as f64,
+                    ScalarType::U8 => buf[0] as f64,
+                    ScalarType::I16 => i16::from_le_bytes([buf[0], buf[1]]) as f64,
+                    ScalarType::U16 => u16::from_le_bytes([buf[0], buf[1]]) as f64,
+                    ScalarType::I32 => i32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]) as f64,
+                    ScalarType::U32 => u32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]) as f64,
+                    ScalarType::F32 => f32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]) as f64,
+                    ScalarType::F64 => f64::from_le_bytes(buf),
+                };
+
+                Ok(val)
+            }
+        }
+    }
+
+    /// Reads one element instance.
+    ///
+    /// The scalar properties are written to `scalars`. The items of the list property `list_id`
+    /// are written to `list`, and the other lists are skipped.
+    fn read_row(
+        &mut self,
+        element: &Element,
+        scalars: &mut [f64],
+        list_id: Option<usize>,
+        list: &mut Vec<u32>,
+    ) -> IoResult<()> {
+        for (i, property) in element.properties.iter().enumerate() {
+            match *property {
+                Property::Scalar(_, ty) => scalars[i] = self.read(ty)?,
+                Property::List(_, count_ty, item_ty) => {
+                    let count = self.read(count_ty)? as usize;
+
+                    if list_id == Some(i) {
+                        list.clear();
+
+                        for _ in 0..count {
+                            list.push(self.read(item_ty)? as u32);
+                        }
+                    } else {
+                        for _ in 0..count {
+                            let _ = self.read(item_ty)?;
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn invalid(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("PLY: {}", msg))
+}
+
+/// Parses a PLY file.
+pub fn parse_file(path: &Path) -> IoResult<PointSet> {
+    let mut data = Vec::new();
+    let _ = File::open(path)?.read_to_end(&mut data)?;
+
+    parse(&data)
+}
+
+/// Parses the content of a PLY file.
+pub fn parse(data: &[u8]) -> IoResult<PointSet> {
+    let (format, elements, body_start) = parse_header(data)?;
+    let body = &data[body_start..];
+    let mut reader = match format {
+        Format::Ascii => Reader::Ascii(
+            str::from_utf8(body)
+                .map_err(|_| invalid("the ASCII data is not valid UTF-8.".to_string()))?
+                .split_whitespace(),
+        ),
+        _ => Reader::Binary {
+            data: body,
+            pos: 0,
+            big_endian: format == Format::BinaryBigEndian,
+        },
+    };
+
+    let mut set = PointSet::new(Vec::new());
+    let mut list = Vec::new();
+
+    for element in elements.iter() {
+        let mut scalars = vec![0.0; element.properties.len()];
+
+        match &element.name[..] {
+            "vertex" => read_vertices(&mut reader, element, &mut set)?,
+            "face" => {
+                let list_id = element.find(&["vertex_indices", "vertex_index"]);
+
+                for _ in 0..element.count {
+                    reader.read_row(element, &mut scalars, list_id, &mut list)?;
+
+                    for i in 1..list.len().saturating_sub(1) {
+                        set.faces.push(Point3::new(list[0], list[i], list[i + 1]));
+                    }
+                }
+            }
+            _ => {
+                for _ in 0..element.count {
+                    reader.read_row(element, &mut scalars, None, &mut list)?;
+                }
+            }
+        }
+    }
+
+    let num_vertices = set.coords.len() as u32;
+
+    if set
+        .faces
+        .iter()
+        .any(|f| f.iter().any(|i| *i >= num_vertices))
+    {
+        return Err(invalid("a face index is out of bounds.".to_string()));
+    }
+
+    Ok(set)
+}
+
+fn read_vertices(reader: &mut Reader, element: &Element, set: &mut PointSet) -> IoResult<()> {
+    let position = [
+        element.find(&["x"]),
+        element.find(&["y"]),
+        element.find(&["z"]),
+    ];
+    let normal = [
+        element.find(&["nx", "normal_x"]),
+        element.find(&["ny", "normal_y"]),
+        element.find(&["nz", "normal_z"]),
+    ];
+    let color = [
+        element.find(&["red", "r", "diffuse_red"]),
+        element.find(&["green", "g", "diffuse_green"]),
+        element.find(&["blue", "b", "diffuse_blue"]),
+    ];
+
+    let (x, y, z) = match position {
+        [Some(x), Some(y), Some(z)] => (x, y, z),
+        _ => {
+            return Err(invalid(
+                "the vertices must have x, y, and z properties.".to_string(),
+            ))
+        }
+    };
+    let normal = match normal {
+        [Some(nx), Some(ny), Some(nz)] => Some((nx, ny, nz)),
+        _ => None,
+    };
+    let color = match color {
+        [Some(r), Some(g), Some(b)] => Some((r, g, b)),
+        _ => None,
+    };
+
+    let mut scalars = vec![0.0; element.properties.len()];
+    let mut list = Vec::new();
+    let mut normals = Vec::new();
+    let mut colors = Vec::new();
+
+    // The vertices are not reserved since their count, read from the header, may be bogus.
+    for _ in 0..element.count {
+        reader.read_row(element, &mut scalars, None, &mut list)?;
+
+        set.coords.push(Point3::new(
+            scalars[x] as f32,
+            scalars[y] as f32,
+            scalars[z] as f32,
+        ));
+
+        if let Some((nx, ny, nz)) = normal {
+            normals.push(Vector3::new(
+                scalars[nx] as f32,
+                scalars[ny] as f32,
+                scalars[nz] as f32,
+            ));
+        }
+
+        if let Some((r, g, b)) = color {
+            colors.push(Point3::new(
+                (scalars[r] / element.scalar_type(r).color_scale()) as f32,
+                (scalars[g] / element.scalar_type(g).color_scale()) as f32,
+                (scalars[b] / element.scalar_type(b).color_scale()) as f32,
+            ));
+        }
+    }
+
+    if normal.is_some() {
+        set.normals = Some(normals);
+    }
+
+    if color.is_some() {
+        set.colors = Some(colors);
+    }
+
+    Ok(())
+}
+
+fn parse_header(data: &[u8]) -> IoResult<(Format, Vec<Element>, usize)> {
+    let mut pos = 0;
+    let mut format = None;
+    let mut elements: Vec<Element> = Vec::new();
+    let mut first = true;
+
+    loop {
+        let end = data[pos..]
+            .iter()
+            .position(|b| *b == b'\n')
+            .ok_or_else(|| invalid("the header is not terminated by `end_header'.".to_string()))?;
+        let line = str::from_utf8(&data[pos..pos + end])
+            .map_err(|_| invalid("the header is not valid UTF-8.".to_string()))?
+            .trim();
+        pos += end + 1;
+
+        if first {
+            if line != "ply" {
+                return Err(invalid("missing `ply' magic number.".to_string()));
+            }
+
+            first = false;
+            continue;
+        }
+
+        let words: Vec<&str> = line.split_whitespace().collect();
+
+        match words.first().cloned() {
+            Some("format") => {
+                format = match words.get(1).cloned() {
+                    Some("ascii") => Some(Format::Ascii),
+                    Some("binary_little_endian") => Some(Format::BinaryLittleEndian),
+                    Some("binary_big_endian") => Some(Format::BinaryBigEndian),
+                    _ => return Err(invalid(format!("unknown format `{}'.", line))),
+                }
+            }
+            Some("element") => {
+                let count = words.get(2).and_then(|c| c.parse().ok());
+
+                match (words.get(1), count) {
+                    (Some(name), Some(count)) => elements.push(Element {
+                        name: name.to_string(),
+                        count,
+                        properties: Vec::new(),
+                    }),
+                    _ => return Err(invalid(format!("invalid element `{}'.", line))),
+                }
+            }
+            Some("property") => {
+                let element = elements.last_mut().ok_or_else(|| {
+                    invalid(format!("property `{}' declared before any element.", line))
+                })?;
+                let property = if words.get(1) == Some(&"list") {
+                    let count_ty = words.get(2).and_then(|t| ScalarType::from_name(t));
+                    let item_ty = words.get(3).and_then(|t| ScalarType::from_name(t));
+
+                    match (count_ty, item_ty, words.get(4)) {
+                        (Some(count_ty), Some(item_ty), Some(name)) => {
+                            Property::List(name.to_string(), count_ty, item_ty)
+                        }
+                        _ => return Err(invalid(format!("invalid property `{}'.", line))),
+                    }
+                } else {
+                    let ty = words.get(1).and_then(|t| ScalarType::from_name(t));
+
+                    match (ty, words.get(2)) {
+                        (Some(ty), Some(name)) => Property::Scalar(name.to_string(), ty),
+                        _ => return Err(invalid(format!("invalid property `{}'.", line))),
+                    }
+                };
+
+                element.properties.push(property);
+            }
+            Some("end_header") => break,
+            // Comments, obj_info, and empty lines.
+            _ => {}
+        }
+    }
+
+    let format = format.ok_or_else(|| invalid("missing format declaration.".to_string()))?;
+
+    Ok((format, elements, pos))
+}
@@ -0,0 +1,122 @@
+//! Points, with optional attributes and faces, produced by the point cloud loaders.
+
+use std::collections::HashMap;
+
+use crate::renderer::PersistentPointRenderer;
+use crate::resource::Mesh;
+use na::{Point3, Vector3};
+
+/// The maximum number of vertices a single `Mesh` can index.
+const MAX_MESH_VERTICES: usize = u16::MAX as usize + 1;
+
+/// A set of points, with optional colors and normals, possibly connected by triangular faces.
+pub struct PointSet {
+    /// The point coordinates.
+    pub coords: Vec<Point3<f32>>,
+    /// The color of each point, with components in `[0, 1]`.
+    pub colors: Option<Vec<Point3<f32>>>,
+    /// The normal of each point.
+    pub normals: Option<Vec<Vector3<f32>>>,
+    /// The triangles connecting the points, if any.
+    pub faces: Vec<Point3<u32>>,
+}
+
+impl PointSet {
+    /// Creates a point set without any attribute.
+    pub fn new(coords: Vec<Point3<f32>>) -> PointSet {
+        PointSet {
+            coords,
+            colors: None,
+            normals: None,
+            faces: Vec::new(),
+        }
+    }
+
+    /// Adds all the points of this set to the given renderer.
+    ///
+    /// Points without colors are white.
+    pub fn add_to_renderer(&self, renderer: &mut PersistentPointRenderer) {
+        let white = Point3::new(1.0, 1.0, 1.0);
+
+        for (i, pt) in self.coords.iter().enumerate() {
+            let color = self.colors.as_ref().map(|c| c[i]).unwrap_or(white);
+            renderer.draw_point(*pt, color);
+        }
+    }
+
+    /// Converts this point set to meshes with per-vertex colors.
+    ///
+    /// Meshes index their vertices with 16 bits integers so big point sets are split into
+    /// several meshes. If this set has no face, each point is given a degenerate triangle so that
+    /// the meshes can still be displayed as points.
+    pub fn to_meshes(&self) -> Vec<Mesh> {
+        if self.faces.is_empty() {
+            let faces: Vec<Point3<u32>> = (0..self.coords.len() as u32)
+                .map(|i| Point3::new(i, i, i))
+                .collect();
+            self.split_faces(&faces, true)
+        } else {
+            self.split_faces(&self.faces, false)
+        }
+    }
+
+    fn split_faces(&self, faces: &[Point3<u32>], points_only: bool) -> Vec<Mesh> {
+        let mut meshes = Vec::new();
+        let mut remap = HashMap::new();
+        let mut vertices = Vec::new();
+        let mut chunk_faces = Vec::new();
+
+        for face in faces {
+            if vertices.len() + 3 > MAX_MESH_VERTICES {
+                meshes.push(self.make_mesh(&vertices, &mut chunk_faces, points_only));
+                remap.clear();
+                vertices.clear();
+            }
+
+            let mut ids = [0u16; 3];
+
+            for (id, v) in ids.iter_mut().zip(face.iter()) {
+                *id = *remap.entry(*v).or_insert_with(|| {
+                    vertices.push(*v as usize);
+                    (vertices.len() - 1) as u16
+                });
+            }
+
+            chunk_faces.push(Point3::new(ids[0], ids[1], ids[2]));
+        }
+
+        if !chunk_faces.is_empty() {
+            meshes.push(self.make_mesh(&vertices, &mut chunk_faces, points_only));
+        }
+
+        meshes
+    }
+
+    fn make_mesh(
+        &self,
+        vertices: &[usize],
+        faces: &mut Vec<Point3<u16>>,
+        points_only: bool,
+    ) -> Mesh {
+        let coords = vertices.iter().map(|i| self.coords[*i]).collect();
+        let mut normals = self
+            .normals
+            .as_ref()
+            .map(|n| vertices.iter().map(|i| n[*i]).collect());
+
+        // Normals cannot be computed from degenerate triangles.
+        if normals.is_none() && points_only {
+            normals = Some(vec![Vector3::z(); vertices.len()]);
+        }
+
+        let colors = self
+            .colors
+            .as_ref()
+            .map(|c| vertices.iter().map(|i| c[*i]).collect());
+        let faces = std::mem::replace(faces, Vec::new());
+
+        let mut mesh = Mesh::new(coords, faces, normals, None, false);
+        mesh.set_colors(colors);
+        mesh
+    }
+}
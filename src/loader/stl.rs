@@ -0,0 +1,182 @@
+//! STL mesh loader.
+//!
+//! Supports both the ASCII and the binary formats.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, Read, Result as IoResult};
+use std::path::Path;
+use std::str;
+
+use crate::loader::point_set::PointSet;
+use na::{Point3, Vector3};
+
+fn invalid(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("STL: {}", msg))
+}
+
+/// Parses a STL file.
+///
+/// If `weld` is `true`, vertices sharing the same position are merged so that the normals are
+/// smoothed across faces. Otherwise, each triangle has its own vertices and the facet normals are
+/// used.
+pub fn parse_file(path: &Path, weld: bool) -> IoResult<PointSet> {
+    let mut data = Vec::new();
+    let _ = File::open(path)?.read_to_end(&mut data)?;
+
+    parse(&data, weld)
+}
+
+/// Parses the content of a STL file.
+///
+/// See `parse_file` for the meaning of `weld`.
+pub fn parse(data: &[u8], weld: bool) -> IoResult<PointSet> {
+    // Some exporters append data after the triangles, so the binary size is only a lower bound.
+    // Binary files may start with `solid` too, in which case their size must match exactly.
+    let num_triangles = if data.len() >= 84 {
+        u32::from_le_bytes([data[80], data[81], data[82], data[83]]) as usize
+    } else {
+        0
+    };
+    let binary_size = num_triangles
+        .checked_mul(50)
+        .and_then(|size| size.checked_add(84));
+    let is_binary = match binary_size {
+        Some(size) if data.len() >= 84 => {
+            size == data.len() || (size < data.len() && !data.starts_with(b"solid"))
+        }
+        _ => false,
+    };
+
+    let triangles = if is_binary {
+        parse_binary(data, num_triangles)
+    } else if data.starts_with(b"solid") {
+        parse_ascii(data)?
+    } else {
+        return Err(invalid(
+            "neither an ASCII nor a binary STL file.".to_string(),
+        ));
+    };
+
+    Ok(build_point_set(triangles, weld))
+}
+
+/// A triangle, as its facet normal followed by its three vertices.
+type Facet = (Vector3<f32>, [Point3<f32>; 3]);
+
+fn parse_binary(data: &[u8], num_triangles: usize) -> Vec<Facet> {
+    let read_f32 = |i: usize| f32::from_le_bytes([data[i], data[i + 1], data[i + 2], data[i + 3]]);
+    let read_vec = |i: usize| Vector3::new(read_f32(i), read_f32(i + 4), read_f32(i + 8));
+
+    (0..num_triangles)
+        .map(|i| 84 + 50 * i)
+        .map(|i| {
+            let normal = read_vec(i);
+            let a = Point3::from(read_vec(i + 12));
+            let b = Point3::from(read_vec(i + 24));
+            let c = Point3::from(read_vec(i + 36));
+            (normal, [a, b, c])
+        })
+        .collect()
+}
+
+fn parse_ascii(data: &[u8]) -> IoResult<Vec<Facet>> {
+    let text = str::from_utf8(data)
+        .map_err(|_| invalid("the ASCII data is not valid UTF-8.".to_string()))?;
+    let mut words = text.split_whitespace();
+    let mut facets = Vec::new();
+    let mut normal = Vector3::zeros();
+    let mut vertices = Vec::new();
+
+    while let Some(word) = words.next() {
+        match word {
+            "facet" => {
+                vertices.clear();
+
+                if words.next() == Some("normal") {
+                    normal = read_vector(&mut words)?;
+                } else {
+                    normal = Vector3::zeros();
+                }
+            }
+            "vertex" => vertices.push(Point3::from(read_vector(&mut words)?)),
+            "endfacet" => {
+                // Some exporters output polygons instead of triangles.
+                for i in 1..vertices.len().saturating_sub(1) {
+                    facets.push((normal, [vertices[0], vertices[i], vertices[i + 1]]));
+                }
+            }
+            // solid, outer, loop, endloop, endsolid, and the solid names.
+            _ => {}
+        }
+    }
+
+    Ok(facets)
+}
+
+fn read_vector(words: &mut str::SplitWhitespace) -> IoResult<Vector3<f32>> {
+    let mut v = Vector3::zeros();
+
+    for i in 0..3 {
+        let word = words
+            .next()
+            .ok_or_else(|| invalid("unexpected end of file.".to_string()))?;
+        v[i] = word
+            .parse()
+            .map_err(|_| invalid(format!("invalid number `{}'.", word)))?;
+    }
+
+    Ok(v)
+}
+
+fn build_point_set(facets: Vec<Facet>, weld: bool) -> PointSet {
+    let mut set = PointSet::new(Vec::new());
+
+    if weld {
+        let mut ids = HashMap::new();
+
+        for (_, vertices) in facets {
+            let mut face = [0u32; 3];
+
+            for (id, v) in face.iter_mut().zip(vertices.iter()) {
+                // Adding 0.0 turns -0.0 into 0.0 so both are welded together.
+                let key = [
+                    (v.x + 0.0).to_bits(),
+                    (v.y + 0.0).to_bits(),
+                    (v.z + 0.0).to_bits(),
+                ];
+                let coords = &mut set.coords;
+                *id = *ids.entry(key).or_insert_with(|| {
+                    coords.push(*v);
+                    (coords.len() - 1) as u32
+                });
+            }
+
+            if face[0] != face[1] && face[1] != face[2] && face[2] != face[0] {
+                set.faces.push(Point3::new(face[0], face[1], face[2]));
+            }
+        }
+    } else {
+        let mut normals = Vec::with_capacity(facets.len() * 3);
+
+        for (normal, vertices) in facets {
+            let mut normal = normal;
+
+            if normal.norm_squared() == 0.0 {
+                normal = (vertices[1] - vertices[0])
+                    .cross(&(vertices[2] - vertices[0]))
+                    .try_normalize(0.0)
+                    .unwrap_or_else(Vector3::zeros);
+            }
+
+            let id = set.coords.len() as u32;
+            set.coords.extend_from_slice(&vertices);
+            normals.extend_from_slice(&[normal, normal, normal]);
+            set.faces.push(Point3::new(id, id + 1, id + 2));
+        }
+
+        set.normals = Some(normals);
+    }
+
+    set
+}
@@ -1,4 +1,4 @@
-use crate::event::{Action, MouseButton, WindowEvent};
+use crate::event::{camera_actions, Action, Gesture, GestureRecognizer, MouseButton, WindowEvent};
 use crate::planar_camera::PlanarCamera;
 use crate::resource::ShaderUniform;
 use crate::window::Canvas;
@@ -7,6 +7,11 @@ use num::Pow;
 use std::f32;
 
 /// A 2D camera that can be zoomed and panned.
+///
+/// It is panned with the right mouse button or by dragging one or two fingers, and zoomed with the
+/// scroll wheel or by pinching. The mouse button and the scroll wheel are replaced by the
+/// `camera_actions::PAN` action and the `camera_actions::ZOOM` axis bound in the action map of
+/// the window, if any.
 #[derive(Clone, Debug)]
 pub struct Sidescroll {
     at: Point2<f32>,
@@ -22,6 +27,7 @@ pub struct Sidescroll {
     scaled_proj: Matrix3<f32>,
     inv_scaled_proj: Matrix3<f32>,
     last_cursor_pos: Vector2<f32>,
+    gestures: GestureRecognizer,
 }
 
 impl Sidescroll {
@@ -37,6 +43,7 @@ impl Sidescroll {
             scaled_proj: na::one(),
             inv_scaled_proj: na::one(),
             last_cursor_pos: na::zero(),
+            gestures: GestureRecognizer::new(),
         };
 
         res.update_projviews();
@@ -117,6 +124,32 @@ impl Sidescroll {
         self.update_projviews();
     }
 
+    fn is_dragging(&self, canvas: &Canvas) -> bool {
+        let actions = canvas.action_map();
+
+        if actions.is_bound(camera_actions::PAN) {
+            actions.is_active(camera_actions::PAN)
+        } else if let Some(drag_button) = self.drag_button {
+            canvas.get_mouse_button(drag_button) == Action::Press
+        } else {
+            false
+        }
+    }
+
+    fn handle_gesture(&mut self, gesture: Gesture) {
+        match gesture {
+            Gesture::Drag(dx, dy) | Gesture::Pan(dx, dy) => {
+                self.handle_right_button_displacement(&Vector2::new(dx as f32, dy as f32))
+            }
+            Gesture::Pinch(scale) => {
+                self.zoom = self.zoom * scale as f32;
+                self.update_restrictions();
+                self.update_projviews();
+            }
+            Gesture::Rotate(..) | Gesture::Tap(..) => {}
+        }
+    }
+
     fn update_projviews(&mut self) {
         self.view = Translation2::new(-self.at.x, -self.at.y).to_homogeneous();
         self.scaled_proj = self.proj;
@@ -136,16 +169,23 @@ impl PlanarCamera for Sidescroll {
             WindowEvent::CursorPos(x, y, _) => {
                 let curr_pos = Vector2::new(x as f32, y as f32);
 
-                if let Some(drag_button) = self.drag_button {
-                    if canvas.get_mouse_button(drag_button) == Action::Press {
-                        let dpos = curr_pos - self.last_cursor_pos;
-                        self.handle_right_button_displacement(&dpos)
-                    }
+                if self.is_dragging(canvas) {
+                    let dpos = curr_pos - self.last_cursor_pos;
+                    self.handle_right_button_displacement(&dpos)
                 }
 
                 self.last_cursor_pos = curr_pos;
             }
-            WindowEvent::Scroll(_, off, _) => self.handle_scroll(off as f32),
+            WindowEvent::Scroll(_, off, _)
+                if !canvas.action_map().is_axis_bound(camera_actions::ZOOM) =>
+            {
+                self.handle_scroll(off as f32)
+            }
+            WindowEvent::Touch(..) => {
+                for gesture in self.gestures.handle_event(event) {
+                    self.handle_gesture(gesture)
+                }
+            }
             WindowEvent::FramebufferSize(w, h) => {
                 self.proj = Matrix3::new(
                     2.0 * (scale as f32) / (w as f32),
@@ -174,7 +214,13 @@ impl PlanarCamera for Sidescroll {
         view.upload(&self.view);
     }
 
-    fn update(&mut self, _: &Canvas) {}
+    fn update(&mut self, canvas: &Canvas) {
+        let zoom = canvas.action_map().axis(camera_actions::ZOOM);
+
+        if zoom != 0.0 {
+            self.handle_scroll(zoom as f32);
+        }
+    }
 
     /// Calculate the global position of the given window coordinate
     fn unproject(&self, window_coord: &Point2<f32>, size: &Vector2<f32>) -> Point2<f32> {
@@ -1,48 +1,32 @@
 //! A batched line renderer.
 
-use crate::context::Context;
 use crate::planar_camera::PlanarCamera;
-use crate::renderer::PlanarRenderer;
-use crate::resource::{AllocationType, BufferType, Effect, GPUVec, ShaderAttribute, ShaderUniform};
+use crate::renderer::line_effect::{self, LineEffect, LineStyle};
+use crate::renderer::{LineCap, LineJoin, PlanarRenderer};
+use crate::resource::{AllocationType, BufferType, GPUVec};
 use na::{Matrix3, Point2, Point3};
 
-#[path = "error.rs"]
-mod error;
-
 /// Structure which manages the display of short-living lines.
+///
+/// Lines are rendered as screen-space quads so their width, expressed in pixels, is not
+/// limited by the OpenGL implementation.
 pub struct PlanarLineRenderer {
-    shader: Effect,
-    pos: ShaderAttribute<Point2<f32>>,
-    color: ShaderAttribute<Point3<f32>>,
-    view: ShaderUniform<Matrix3<f32>>,
-    proj: ShaderUniform<Matrix3<f32>>,
-    colors: GPUVec<Point3<f32>>,
-    lines: GPUVec<Point2<f32>>,
+    effect: LineEffect<Matrix3<f32>>,
+    lines: GPUVec<f32>,
+    points: Vec<Point3<f32>>,
+    line_width: f32,
+    style: LineStyle,
 }
 
 impl PlanarLineRenderer {
     /// Creates a new lines manager.
     pub fn new() -> PlanarLineRenderer {
-        let mut shader = Effect::new_from_str(LINES_VERTEX_SRC, LINES_FRAGMENT_SRC);
-
-        shader.use_program();
-
         PlanarLineRenderer {
+            effect: LineEffect::new_planar(),
             lines: GPUVec::new(Vec::new(), BufferType::Array, AllocationType::StreamDraw),
-            colors: GPUVec::new(Vec::new(), BufferType::Array, AllocationType::StreamDraw),
-            pos: shader
-                .get_attrib::<Point2<f32>>("position")
-                .expect("Failed to get shader attribute."),
-            color: shader
-                .get_attrib::<Point3<f32>>("color")
-                .expect("Failed to get shader attribute."),
-            view: shader
-                .get_uniform::<Matrix3<f32>>("view")
-                .expect("Failed to get shader uniform."),
-            proj: shader
-                .get_uniform::<Matrix3<f32>>("proj")
-                .expect("Failed to get shader uniform."),
-            shader: shader,
+            points: Vec::new(),
+            line_width: 1.0,
+            style: LineStyle::default(),
         }
     }
 
@@ -54,15 +38,62 @@ impl PlanarLineRenderer {
     /// Adds a line to be drawn during the next frame. Lines are not persistent between frames.
     /// This method must be called for each line to draw, and at each update loop iteration.
     pub fn draw_line(&mut self, a: Point2<f32>, b: Point2<f32>, color: Point3<f32>) {
+        self.draw_polyline(&[a, b], &[color], &[], false)
+    }
+
+    /// Adds a polyline to be drawn during the next frame.
+    ///
+    /// Consecutive segments are connected with the join set by `set_line_join`.
+    ///
+    /// # Arguments
+    /// * `points` - the vertices of the polyline.
+    /// * `colors` - either a single color for the whole polyline, or one color per vertex.
+    /// * `widths` - either a single width, or one width per vertex, in pixels. If empty, the
+    /// width set by `set_line_width` is used.
+    /// * `closed` - if `true`, the last vertex is connected to the first one.
+    pub fn draw_polyline(
+        &mut self,
+        points: &[Point2<f32>],
+        colors: &[Point3<f32>],
+        widths: &[f32],
+        closed: bool,
+    ) {
+        let widths = if widths.is_empty() {
+            &[self.line_width][..]
+        } else {
+            widths
+        };
+
+        self.points.clear();
+        self.points
+            .extend(points.iter().map(|p| Point3::new(p.x, p.y, 0.0)));
+
         for lines in self.lines.data_mut().iter_mut() {
-            lines.push(a);
-            lines.push(b);
-        }
-        for colors in self.colors.data_mut().iter_mut() {
-            colors.push(color);
-            colors.push(color);
+            line_effect::push_polyline(lines, &self.points, colors, widths, closed);
         }
     }
+
+    /// Sets the width, in pixels, of the lines added after this call.
+    pub fn set_line_width(&mut self, line_width: f32) {
+        self.line_width = line_width;
+    }
+
+    /// Sets the shape of the extremities of the rendered lines.
+    pub fn set_line_cap(&mut self, cap: LineCap) {
+        self.style.cap = cap;
+    }
+
+    /// Sets the shape of the joins between consecutive segments of the rendered polylines.
+    pub fn set_line_join(&mut self, join: LineJoin) {
+        self.style.join = join;
+    }
+
+    /// Sets the dash pattern of the rendered lines, as the `(dash, gap)` lengths.
+    ///
+    /// Lengths are expressed in the same unit as the line coordinates. `None` draws solid lines.
+    pub fn set_dash_pattern(&mut self, pattern: Option<(f32, f32)>) {
+        self.style.dash = pattern;
+    }
 }
 
 impl PlanarRenderer for PlanarLineRenderer {
@@ -72,59 +103,18 @@ impl PlanarRenderer for PlanarLineRenderer {
             return;
         }
 
-        self.shader.use_program();
-        self.pos.enable();
-        self.color.enable();
-
-        planar_camera.upload(&mut self.proj, &mut self.view);
+        self.effect.activate(&Matrix3::identity(), &self.style);
 
-        self.color.bind_sub_buffer(&mut self.colors, 0, 0);
-        self.pos.bind_sub_buffer(&mut self.lines, 0, 0);
-
-        let ctxt = Context::get();
-        verify!(ctxt.draw_arrays(Context::LINES, 0, self.lines.len() as i32));
+        {
+            let (proj, view) = self.effect.proj_and_view();
+            planar_camera.upload(proj, view);
+        }
 
-        self.pos.disable();
-        self.color.disable();
+        self.effect.draw(&mut self.lines);
+        self.effect.deactivate();
 
         for lines in self.lines.data_mut().iter_mut() {
             lines.clear()
         }
-
-        for colors in self.colors.data_mut().iter_mut() {
-            colors.clear()
-        }
     }
 }
-
-/// Vertex shader used by the material to display line.
-static LINES_VERTEX_SRC: &'static str = A_VERY_LONG_STRING;
-/// Fragment shader used by the material to display line.
-static LINES_FRAGMENT_SRC: &'static str = ANOTHER_VERY_LONG_STRING;
-
-const A_VERY_LONG_STRING: &'static str = "#version 100
-    attribute vec2 position;
-    attribute vec3 color;
-    varying   vec3 vColor;
-    uniform   mat3 proj;
-    uniform   mat3 view;
-
-    void main() {
-        vec3 projected_pos = proj * view * vec3(position, 1.0);
-        projected_pos.z = 0.0;
-
-        gl_Position = vec4(projected_pos, 1.0);
-        vColor = color;
-    }";
-
-const ANOTHER_VERY_LONG_STRING: &'static str = "#version 100
-#ifdef GL_FRAGMENT_PRECISION_HIGH
-   precision highp float;
-#else
-   precision mediump float;
-#endif
-
-    varying vec3 vColor;
-    void main() {
-        gl_FragColor = vec4(vColor, 1.0);
-    }";
@@ -0,0 +1,185 @@
+//! A post-processing effect to improve the depth perception of point clouds.
+
+use na::Vector2;
+
+use crate::context::Context;
+use crate::post_processing::post_processing_effect::PostProcessingEffect;
+use crate::resource::{
+    AllocationType, BufferType, Effect, GPUVec, RenderTarget, ShaderAttribute, ShaderUniform,
+};
+
+#[path = "../error.rs"]
+mod error;
+
+/// Post processing effect which shades each pixel depending on the depth of its neighbors.
+///
+/// This is known as Eye-Dome Lighting. It does not need any normal, which makes it well suited
+/// for large point clouds. Its cost only depends on the size of the window.
+pub struct EyeDomeLighting {
+    shiftx: f32,
+    shifty: f32,
+    zn: f32,
+    zf: f32,
+    strength: f32,
+    radius: f32,
+    shader: Effect,
+    gl_nx: ShaderUniform<f32>,
+    gl_ny: ShaderUniform<f32>,
+    gl_fbo_depth: ShaderUniform<i32>,
+    gl_fbo_texture: ShaderUniform<i32>,
+    gl_znear: ShaderUniform<f32>,
+    gl_zfar: ShaderUniform<f32>,
+    gl_strength: ShaderUniform<f32>,
+    gl_v_coord: ShaderAttribute<Vector2<f32>>,
+    gl_fbo_vertices: GPUVec<Vector2<f32>>,
+}
+
+impl EyeDomeLighting {
+    /// Creates a new EyeDomeLighting post processing effect.
+    ///
+    /// # Arguments:
+    /// * `strength` - the darkening factor applied to depth discontinuities.
+    /// * `radius` - the distance, in pixels, at which the neighbors depths are sampled.
+    pub fn new(strength: f32, radius: f32) -> EyeDomeLighting {
+        let fbo_vertices: Vec<Vector2<f32>> = vec![
+            Vector2::new(-1.0, -1.0),
+            Vector2::new(1.0, -1.0),
+            Vector2::new(-1.0, 1.0),
+            Vector2::new(1.0, 1.0),
+        ];
+
+        let mut fbo_vertices =
+            GPUVec::new(fbo_vertices, BufferType::Array, AllocationType::StaticDraw);
+        fbo_vertices.load_to_gpu();
+        fbo_vertices.unload_from_ram();
+
+        let mut shader = Effect::new_from_str(VERTEX_SHADER, FRAGMENT_SHADER);
+
+        shader.use_program();
+
+        EyeDomeLighting {
+            shiftx: 0.0,
+            shifty: 0.0,
+            zn: 0.0,
+            zf: 0.0,
+            strength,
+            radius,
+            gl_nx: shader.get_uniform("nx").unwrap(),
+            gl_ny: shader.get_uniform("ny").unwrap(),
+            gl_fbo_depth: shader.get_uniform("fbo_depth").unwrap(),
+            gl_fbo_texture: shader.get_uniform("fbo_texture").unwrap(),
+            gl_znear: shader.get_uniform("znear").unwrap(),
+            gl_zfar: shader.get_uniform("zfar").unwrap(),
+            gl_strength: shader.get_uniform("strength").unwrap(),
+            gl_v_coord: shader.get_attrib("v_coord").unwrap(),
+            gl_fbo_vertices: fbo_vertices,
+            shader,
+        }
+    }
+
+    /// Sets the darkening factor applied to depth discontinuities.
+    pub fn set_strength(&mut self, strength: f32) {
+        self.strength = strength;
+    }
+
+    /// Sets the distance, in pixels, at which the neighbors depths are sampled.
+    pub fn set_radius(&mut self, radius: f32) {
+        self.radius = radius;
+    }
+}
+
+impl PostProcessingEffect for EyeDomeLighting {
+    fn update(&mut self, _: f32, w: f32, h: f32, znear: f32, zfar: f32) {
+        self.shiftx = self.radius / w;
+        self.shifty = self.radius / h;
+        self.zn = znear;
+        self.zf = zfar;
+    }
+
+    fn draw(&mut self, target: &RenderTarget) {
+        let ctxt = Context::get();
+        self.gl_v_coord.enable();
+
+        /*
+         * Finalize draw
+         */
+        verify!(ctxt.clear(Context::COLOR_BUFFER_BIT | Context::DEPTH_BUFFER_BIT));
+
+        self.shader.use_program();
+
+        self.gl_strength.upload(&self.strength);
+        self.gl_nx.upload(&self.shiftx);
+        self.gl_ny.upload(&self.shifty);
+        self.gl_znear.upload(&self.zn);
+        self.gl_zfar.upload(&self.zf);
+
+        verify!(ctxt.active_texture(Context::TEXTURE0));
+        verify!(ctxt.bind_texture(Context::TEXTURE_2D, target.texture_id()));
+
+        self.gl_fbo_texture.upload(&0);
+
+        verify!(ctxt.active_texture(Context::TEXTURE1));
+        verify!(ctxt.bind_texture(
+            Context::TEXTURE_2D,
+            target.depth_id().and_then(|id| id.as_ref().left())
+        ));
+
+        self.gl_fbo_depth.upload(&1);
+
+        self.gl_v_coord.bind(&mut self.gl_fbo_vertices);
+
+        verify!(ctxt.draw_arrays(Context::TRIANGLE_STRIP, 0, 4));
+
+        self.gl_v_coord.disable();
+        verify!(ctxt.active_texture(Context::TEXTURE0));
+    }
+}
+
+static VERTEX_SHADER: &'static str = "#version 100
+    attribute vec2    v_coord;
+    varying vec2      f_texcoord;
+
+    void main(void) {
+        gl_Position = vec4(v_coord, 0.0, 1.0);
+        f_texcoord  = (v_coord + 1.0) / 2.0;
+    }";
+
+static FRAGMENT_SHADER: &'static str = "#version 100
+#ifdef GL_FRAGMENT_PRECISION_HIGH
+   precision highp float;
+#else
+   precision mediump float;
+#endif
+
+    uniform sampler2D fbo_depth;
+    uniform sampler2D fbo_texture;
+    uniform float     nx;
+    uniform float     ny;
+    uniform float     znear;
+    uniform float     zfar;
+    uniform float     strength;
+    varying vec2      f_texcoord;
+
+    float log_depth(vec2 uv) {
+        float nlin_depth = texture2D(fbo_depth, uv).x;
+        float lin_depth  = znear * zfar / (zfar - nlin_depth * (zfar - znear));
+
+        return log2(lin_depth);
+    }
+
+    void main(void) {
+        float depth    = log_depth(f_texcoord);
+        float response = 0.0;
+
+        for (int i = 0; i < 8; ++i) {
+            float angle    = float(i) * 0.78539816;
+            vec2  neighbor = f_texcoord + vec2(cos(angle) * nx, sin(angle) * ny);
+
+            response += max(0.0, depth - log_depth(neighbor));
+        }
+
+        float shade = exp(-response / 8.0 * 300.0 * strength);
+        vec4  color = texture2D(fbo_texture, f_texcoord);
+
+        gl_FragColor = vec4(shade * color.xyz, 1.0);
+    }";
@@ -1,5 +1,7 @@
 //! Post-processing effects.
 
+#[cfg(not(target_arch = "wasm32"))]
+pub use crate::post_processing::eye_dome_lighting::EyeDomeLighting;
 pub use crate::post_processing::grayscales::Grayscales;
 pub use crate::post_processing::oculus_stereo::OculusStereo;
 pub use crate::post_processing::post_processing_effect::PostProcessingEffect;
@@ -7,6 +9,8 @@ pub use crate::post_processing::post_processing_effect::PostProcessingEffect;
 pub use crate::post_processing::sobel_edge_highlight::SobelEdgeHighlight;
 pub use crate::post_processing::waves::Waves;
 
+#[cfg(not(target_arch = "wasm32"))]
+mod eye_dome_lighting;
 mod grayscales;
 mod oculus_stereo;
 pub mod post_processing_effect;
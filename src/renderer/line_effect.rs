@@ -0,0 +1,530 @@
+//! Screen-space extruded lines, shared by the line renderers and the wireframe rendering.
+//!
+//! Each line segment is expanded into a quad by the vertex shader, so the line width is
+//! independent of `glLineWidth` (which is clamped to 1 pixel by core profiles and WebGL).
+
+use crate::context::Context;
+use crate::resource::{Effect, GLPrimitive, GPUVec, ShaderAttribute, ShaderUniform};
+use na::{Matrix3, Matrix4, Point3, Point4, Vector2, Vector4};
+
+#[path = "../error.rs"]
+mod error;
+
+/// The number of `f32` stored for each vertex of a line quad.
+pub(crate) const LINE_VERTEX_SIZE: usize = 18;
+/// The number of vertices generated for each line segment.
+pub(crate) const LINE_SEGMENT_VERTICES: usize = 6;
+
+/// The shape drawn at the extremities of a line.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LineCap {
+    /// The line stops exactly at its extremities.
+    Butt,
+    /// The line is extended by half its width at its extremities.
+    Square,
+    /// The extremities are rounded.
+    Round,
+}
+
+/// The shape drawn where two consecutive segments of a polyline meet.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LineJoin {
+    /// The outer edges of both segments are extended until they meet.
+    ///
+    /// Joins sharper than the miter limit are clipped.
+    Miter,
+    /// The joins are rounded.
+    Round,
+}
+
+/// The parameters used to draw a batch of lines.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) struct LineStyle {
+    pub cap: LineCap,
+    pub join: LineJoin,
+    pub dash: Option<(f32, f32)>,
+    pub depth_test: bool,
+    pub color: Point3<f32>,
+    pub width_scale: f32,
+}
+
+impl Default for LineStyle {
+    fn default() -> Self {
+        LineStyle {
+            cap: LineCap::Butt,
+            join: LineJoin::Miter,
+            dash: None,
+            depth_test: true,
+            color: Point3::new(1.0, 1.0, 1.0),
+            width_scale: 1.0,
+        }
+    }
+}
+
+/// Appends to `out` the vertices necessary to draw a polyline.
+///
+/// `colors` and `widths` must either contain a single element used for every point, or one
+/// element per point. Widths are expressed in pixels.
+pub(crate) fn push_polyline(
+    out: &mut Vec<f32>,
+    points: &[Point3<f32>],
+    colors: &[Point3<f32>],
+    widths: &[f32],
+    closed: bool,
+) {
+    assert!(
+        colors.len() == 1 || colors.len() == points.len(),
+        "There must be either one color, or one color per point."
+    );
+    assert!(
+        widths.len() == 1 || widths.len() == points.len(),
+        "There must be either one width, or one width per point."
+    );
+
+    let n = points.len();
+
+    if n < 2 {
+        return;
+    }
+
+    let nsegments = if closed { n } else { n - 1 };
+    let color = |i: usize| colors[if colors.len() == 1 { 0 } else { i }];
+    let width = |i: usize| widths[if widths.len() == 1 { 0 } else { i }];
+    let mut distance = 0.0;
+
+    out.reserve(nsegments * LINE_SEGMENT_VERTICES * LINE_VERTEX_SIZE);
+
+    for s in 0..nsegments {
+        let ia = s;
+        let ib = (s + 1) % n;
+        let start_cap = !closed && s == 0;
+        let end_cap = !closed && s == nsegments - 1;
+
+        let prev = if start_cap {
+            points[ia]
+        } else {
+            points[(ia + n - 1) % n]
+        };
+        let next = if end_cap {
+            points[ib]
+        } else {
+            points[(ib + 1) % n]
+        };
+
+        let length = na::distance(&points[ia], &points[ib]);
+        let caps = [start_cap as u32 as f32, end_cap as u32 as f32];
+
+        let start = |side: f32, out: &mut Vec<f32>| {
+            push_vertex(
+                out,
+                &points[ia],
+                &prev,
+                &points[ib],
+                &color(ia),
+                [side, 0.0, width(ia), distance],
+                caps,
+            )
+        };
+        let end = |side: f32, out: &mut Vec<f32>| {
+            push_vertex(
+                out,
+                &points[ib],
+                &points[ia],
+                &next,
+                &color(ib),
+                [side, 1.0, width(ib), distance + length],
+                caps,
+            )
+        };
+
+        start(-1.0, out);
+        start(1.0, out);
+        end(-1.0, out);
+        end(-1.0, out);
+        start(1.0, out);
+        end(1.0, out);
+
+        distance += length;
+    }
+}
+
+fn push_vertex(
+    out: &mut Vec<f32>,
+    position: &Point3<f32>,
+    prev: &Point3<f32>,
+    next: &Point3<f32>,
+    color: &Point3<f32>,
+    params: [f32; 4],
+    caps: [f32; 2],
+) {
+    out.extend_from_slice(position.coords.as_slice());
+    out.extend_from_slice(prev.coords.as_slice());
+    out.extend_from_slice(next.coords.as_slice());
+    out.extend_from_slice(color.coords.as_slice());
+    out.extend_from_slice(&params);
+    out.extend_from_slice(&caps);
+}
+
+/// The shader program used to draw lines as screen-space quads.
+///
+/// The matrix type `M` is `Matrix4<f32>` for 3D lines and `Matrix3<f32>` for 2D lines.
+pub(crate) struct LineEffect<M> {
+    effect: Effect,
+    pos: ShaderAttribute<Point3<f32>>,
+    prev: ShaderAttribute<Point3<f32>>,
+    next: ShaderAttribute<Point3<f32>>,
+    color: ShaderAttribute<Point3<f32>>,
+    params: ShaderAttribute<Point4<f32>>,
+    caps: ShaderAttribute<Vector2<f32>>,
+    model: ShaderUniform<M>,
+    proj: ShaderUniform<M>,
+    view: ShaderUniform<M>,
+    viewport: ShaderUniform<Vector2<f32>>,
+    style: ShaderUniform<Vector4<f32>>,
+    tint: ShaderUniform<Vector4<f32>>,
+}
+
+impl LineEffect<Matrix4<f32>> {
+    /// Creates the effect used to draw 3D lines.
+    pub fn new() -> Self {
+        LineEffect::from_sources(
+            &[
+                LINES_VERTEX_HEADER_SRC,
+                LINES_TO_CLIP_3D_SRC,
+                LINES_VERTEX_MAIN_SRC,
+            ]
+            .concat(),
+        )
+    }
+}
+
+impl LineEffect<Matrix3<f32>> {
+    /// Creates the effect used to draw 2D lines.
+    pub fn new_planar() -> Self {
+        LineEffect::from_sources(
+            &[
+                LINES_VERTEX_HEADER_SRC,
+                LINES_TO_CLIP_2D_SRC,
+                LINES_VERTEX_MAIN_SRC,
+            ]
+            .concat(),
+        )
+    }
+}
+
+impl<M: GLPrimitive> LineEffect<M> {
+    fn from_sources(vertex_src: &str) -> Self {
+        let mut effect = Effect::new_from_str(vertex_src, LINES_FRAGMENT_SRC);
+
+        effect.use_program();
+
+        LineEffect {
+            pos: effect.get_attrib("position").unwrap(),
+            prev: effect.get_attrib("prev").unwrap(),
+            next: effect.get_attrib("next").unwrap(),
+            color: effect.get_attrib("color").unwrap(),
+            params: effect.get_attrib("params").unwrap(),
+            caps: effect.get_attrib("caps").unwrap(),
+            model: effect.get_uniform("model").unwrap(),
+            proj: effect.get_uniform("proj").unwrap(),
+            view: effect.get_uniform("view").unwrap(),
+            viewport: effect.get_uniform("viewport").unwrap(),
+            style: effect.get_uniform("style").unwrap(),
+            tint: effect.get_uniform("tint").unwrap(),
+            effect,
+        }
+    }
+
+    /// The projection and view uniforms, to be set by a camera after `activate` is called.
+    pub fn proj_and_view(&mut self) -> (&mut ShaderUniform<M>, &mut ShaderUniform<M>) {
+        (&mut self.proj, &mut self.view)
+    }
+
+    /// Makes this effect active, and sets the pipeline state required by `style`.
+    pub fn activate(&mut self, model: &M, style: &LineStyle) {
+        let ctxt = Context::get();
+        let (_, _, w, h) = ctxt.current_viewport();
+
+        self.effect.use_program();
+        self.pos.enable();
+        self.prev.enable();
+        self.next.enable();
+        self.color.enable();
+        self.params.enable();
+        self.caps.enable();
+
+        let cap = match style.cap {
+            LineCap::Butt => 0.0,
+            LineCap::Square => 1.0,
+            LineCap::Round => 2.0,
+        };
+        let join = match style.join {
+            LineJoin::Miter => 0.0,
+            LineJoin::Round => 1.0,
+        };
+        let (dash, gap) = style.dash.unwrap_or((0.0, 0.0));
+
+        self.model.upload(model);
+        self.viewport
+            .upload(&Vector2::new(w.max(1) as f32, h.max(1) as f32));
+        self.style.upload(&Vector4::new(cap, join, dash, gap));
+        self.tint.upload(&Vector4::new(
+            style.color.x,
+            style.color.y,
+            style.color.z,
+            style.width_scale,
+        ));
+
+        verify!(ctxt.disable(Context::CULL_FACE));
+        let _ = verify!(ctxt.polygon_mode(Context::FRONT_AND_BACK, Context::FILL));
+        verify!(ctxt.enable(Context::BLEND));
+        verify!(ctxt.blend_func_separate(
+            Context::SRC_ALPHA,
+            Context::ONE_MINUS_SRC_ALPHA,
+            Context::ONE,
+            Context::ONE_MINUS_SRC_ALPHA,
+        ));
+
+        if !style.depth_test {
+            verify!(ctxt.disable(Context::DEPTH_TEST));
+        }
+    }
+
+    /// Draws the line quads contained by `vertices`.
+    pub fn draw(&mut self, vertices: &mut GPUVec<f32>) {
+        let nvertices = vertices.len() / LINE_VERTEX_SIZE;
+
+        if nvertices == 0 {
+            return;
+        }
+
+        let stride = LINE_VERTEX_SIZE - 1;
+
+        unsafe {
+            self.pos.bind_sub_buffer_generic(vertices, stride, 0);
+            self.prev.bind_sub_buffer_generic(vertices, stride, 3);
+            self.next.bind_sub_buffer_generic(vertices, stride, 6);
+            self.color.bind_sub_buffer_generic(vertices, stride, 9);
+            self.params.bind_sub_buffer_generic(vertices, stride, 12);
+            self.caps.bind_sub_buffer_generic(vertices, stride, 16);
+        }
+
+        verify!(Context::get().draw_arrays(Context::TRIANGLES, 0, nvertices as i32));
+    }
+
+    /// Disables this effect, and restores the default pipeline state.
+    pub fn deactivate(&mut self) {
+        let ctxt = Context::get();
+
+        self.pos.disable();
+        self.prev.disable();
+        self.next.disable();
+        self.color.disable();
+        self.params.disable();
+        self.caps.disable();
+
+        verify!(ctxt.disable(Context::BLEND));
+        verify!(ctxt.enable(Context::DEPTH_TEST));
+        verify!(ctxt.enable(Context::CULL_FACE));
+    }
+}
+
+const LINES_VERTEX_HEADER_SRC: &'static str = "#version 100
+#ifdef GL_FRAGMENT_PRECISION_HIGH
+   precision highp float;
+#else
+   precision mediump float;
+#endif
+
+attribute vec3 position;
+attribute vec3 prev;
+attribute vec3 next;
+attribute vec3 color;
+// x: side (-1 or 1), y: 0 at the segment start, 1 at its end, z: width, w: distance along the line.
+attribute vec4 params;
+// 1 if the segment start (resp. end) is an extremity of the polyline.
+attribute vec2 caps;
+
+uniform vec2 viewport;
+// x: cap, y: join, z: dash length, w: gap length.
+uniform vec4 style;
+// rgb: color multiplier, a: width multiplier.
+uniform vec4 tint;
+
+varying vec3 vColor;
+varying vec2 vPos;
+varying vec4 vSegment;
+varying vec2 vCaps;
+varying float vHalfWidth;
+varying float vDistanceW;
+varying float vInvW;
+";
+
+const LINES_TO_CLIP_3D_SRC: &'static str = "
+uniform mat4 proj, view, model;
+
+vec4 to_clip(vec3 p) {
+    return proj * view * model * vec4(p, 1.0);
+}
+";
+
+const LINES_TO_CLIP_2D_SRC: &'static str = "
+uniform mat3 proj, view, model;
+
+vec4 to_clip(vec3 p) {
+    vec3 projected_pos = proj * view * model * vec3(p.xy, 1.0);
+    return vec4(projected_pos.xy, 0.0, 1.0);
+}
+";
+
+const LINES_VERTEX_MAIN_SRC: &'static str = "
+const float FEATHER = 1.0;
+const float MITER_LIMIT = 4.0;
+
+// Moves `a` along the segment [a, b] so it lies in front of the near clipping plane.
+vec4 clip_near(vec4 a, vec4 b) {
+    float da = a.z + a.w;
+    float db = b.z + b.w;
+
+    if (da < 0.0 && db > da) {
+        return mix(a, b, da / (da - db));
+    }
+
+    return a;
+}
+
+vec2 to_screen(vec4 c) {
+    return (c.xy / c.w * 0.5 + 0.5) * viewport;
+}
+
+void main() {
+    float side = params.x;
+    bool at_end = params.y > 0.5;
+    float hw = params.z * tint.a * 0.5;
+    float ext = hw + FEATHER;
+
+    vec4 c_curr = to_clip(position);
+    vec4 c_a = at_end ? to_clip(prev) : c_curr;
+    vec4 c_b = at_end ? c_curr : to_clip(next);
+
+    if (c_a.z + c_a.w < 0.0 && c_b.z + c_b.w < 0.0) {
+        // The whole segment is behind the camera.
+        gl_Position = vec4(0.0, 0.0, 2.0, 1.0);
+        return;
+    }
+
+    c_a = clip_near(c_a, c_b);
+    c_b = clip_near(c_b, c_a);
+
+    vec2 a = to_screen(c_a);
+    vec2 b = to_screen(c_b);
+    vec2 dir = b - a;
+    float len = length(dir);
+    dir = len > 0.0 ? dir / len : vec2(1.0, 0.0);
+    vec2 normal = vec2(-dir.y, dir.x);
+
+    vec4 c_here = at_end ? c_b : c_a;
+    vec2 here = at_end ? b : a;
+    float along = at_end ? 1.0 : -1.0;
+    bool is_cap = at_end ? caps.y > 0.5 : caps.x > 0.5;
+    vec2 offset = normal * side * ext;
+
+    if (is_cap) {
+        float cap_ext = style.x > 0.5 ? hw : 0.0;
+        offset += dir * along * (cap_ext + FEATHER);
+    } else if (style.y > 0.5) {
+        offset += dir * along * ext;
+    } else {
+        vec4 c_other = clip_near(to_clip(at_end ? next : prev), c_here);
+        vec2 other = to_screen(c_other);
+        vec2 other_dir = at_end ? other - here : here - other;
+        float other_len = length(other_dir);
+        vec2 sum = dir + (other_len > 0.0 ? other_dir / other_len : dir);
+
+        if (length(sum) > 1.0e-4) {
+            vec2 tangent = normalize(sum);
+            vec2 miter = vec2(-tangent.y, tangent.x);
+            float cos_half = max(dot(miter, normal), 1.0 / MITER_LIMIT);
+            offset = miter * side * ext / cos_half;
+        }
+    }
+
+    vPos = here + offset;
+    vSegment = vec4(a, b);
+    vCaps = caps;
+    vHalfWidth = hw;
+    vColor = color * tint.rgb;
+    vInvW = 1.0 / c_here.w;
+    vDistanceW = params.w * vInvW;
+
+    gl_Position = vec4(vPos / viewport * 2.0 - 1.0, c_here.z / c_here.w, 1.0);
+}
+";
+
+const LINES_FRAGMENT_SRC: &'static str = "#version 100
+#ifdef GL_FRAGMENT_PRECISION_HIGH
+   precision highp float;
+#else
+   precision mediump float;
+#endif
+
+uniform vec4 style;
+
+varying vec3 vColor;
+varying vec2 vPos;
+varying vec4 vSegment;
+varying vec2 vCaps;
+varying float vHalfWidth;
+varying float vDistanceW;
+varying float vInvW;
+
+// Signed distance to the shape drawn beyond one extremity of the segment.
+float end_distance(float line_dist, float outside, vec2 to_end, float is_cap) {
+    if (is_cap > 0.5) {
+        if (style.x > 1.5) {
+            return length(to_end) - vHalfWidth;
+        } else if (style.x > 0.5) {
+            return max(line_dist, outside - vHalfWidth);
+        } else {
+            return max(line_dist, outside);
+        }
+    } else if (style.y > 0.5) {
+        return length(to_end) - vHalfWidth;
+    } else {
+        return line_dist;
+    }
+}
+
+void main() {
+    vec2 a = vSegment.xy;
+    vec2 b = vSegment.zw;
+    vec2 ab = b - a;
+    float len = length(ab);
+    vec2 dir = len > 0.0 ? ab / len : vec2(1.0, 0.0);
+    vec2 ap = vPos - a;
+    float along = dot(ap, dir);
+    float dist = abs(dir.x * ap.y - dir.y * ap.x) - vHalfWidth;
+
+    if (along < 0.0) {
+        dist = end_distance(dist, -along, ap, vCaps.x);
+    } else if (along > len) {
+        dist = end_distance(dist, along - len, vPos - b, vCaps.y);
+    }
+
+    float alpha = clamp(0.5 - dist, 0.0, 1.0);
+
+    if (alpha <= 0.0) {
+        discard;
+    }
+
+    if (style.z > 0.0) {
+        float distance = vDistanceW / vInvW;
+
+        if (mod(distance, style.z + style.w) > style.z) {
+            discard;
+        }
+    }
+
+    gl_FragColor = vec4(vColor, alpha);
+}
+";
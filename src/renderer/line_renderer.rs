@@ -1,48 +1,30 @@
 //! A batched line renderer.
 
 use crate::camera::Camera;
-use crate::context::Context;
-use crate::renderer::Renderer;
-use crate::resource::{AllocationType, BufferType, Effect, GPUVec, ShaderAttribute, ShaderUniform};
+use crate::renderer::line_effect::{self, LineEffect, LineStyle};
+use crate::renderer::{LineCap, LineJoin, Renderer};
+use crate::resource::{AllocationType, BufferType, GPUVec};
 use na::{Matrix4, Point3};
 
-#[path = "../error.rs"]
-mod error;
-
 /// Structure which manages the display of short-living lines.
+///
+/// Lines are rendered as screen-space quads so their width, expressed in pixels, is not
+/// limited by the OpenGL implementation.
 pub struct LineRenderer {
-    shader: Effect,
-    pos: ShaderAttribute<Point3<f32>>,
-    color: ShaderAttribute<Point3<f32>>,
-    view: ShaderUniform<Matrix4<f32>>,
-    proj: ShaderUniform<Matrix4<f32>>,
-    lines: GPUVec<Point3<f32>>,
+    effect: LineEffect<Matrix4<f32>>,
+    lines: GPUVec<f32>,
     line_width: f32,
+    style: LineStyle,
 }
 
 impl LineRenderer {
     /// Creates a new lines manager.
     pub fn new() -> LineRenderer {
-        let mut shader = Effect::new_from_str(LINES_VERTEX_SRC, LINES_FRAGMENT_SRC);
-
-        shader.use_program();
-
         LineRenderer {
+            effect: LineEffect::new(),
             lines: GPUVec::new(Vec::new(), BufferType::Array, AllocationType::StreamDraw),
-            pos: shader
-                .get_attrib::<Point3<f32>>("position")
-                .expect("Failed to get shader attribute."),
-            color: shader
-                .get_attrib::<Point3<f32>>("color")
-                .expect("Failed to get shader attribute."),
-            view: shader
-                .get_uniform::<Matrix4<f32>>("view")
-                .expect("Failed to get shader uniform."),
-            proj: shader
-                .get_uniform::<Matrix4<f32>>("proj")
-                .expect("Failed to get shader uniform."),
-            shader: shader,
             line_width: 1.0,
+            style: LineStyle::default(),
         }
     }
 
@@ -54,73 +36,95 @@ impl LineRenderer {
     /// Adds a line to be drawn during the next frame. Lines are not persistent between frames.
     /// This method must be called for each line to draw, and at each update loop iteration.
     pub fn draw_line(&mut self, a: Point3<f32>, b: Point3<f32>, color: Point3<f32>) {
+        self.draw_polyline(&[a, b], &[color], &[], false)
+    }
+
+    /// Adds a polyline to be drawn during the next frame.
+    ///
+    /// Consecutive segments are connected with the join set by `set_line_join`.
+    ///
+    /// # Arguments
+    /// * `points` - the vertices of the polyline.
+    /// * `colors` - either a single color for the whole polyline, or one color per vertex.
+    /// * `widths` - either a single width, or one width per vertex, in pixels. If empty, the
+    /// width set by `set_line_width` is used.
+    /// * `closed` - if `true`, the last vertex is connected to the first one.
+    pub fn draw_polyline(
+        &mut self,
+        points: &[Point3<f32>],
+        colors: &[Point3<f32>],
+        widths: &[f32],
+        closed: bool,
+    ) {
+        let widths = if widths.is_empty() {
+            &[self.line_width][..]
+        } else {
+            widths
+        };
+
         for lines in self.lines.data_mut().iter_mut() {
-            lines.push(a);
-            lines.push(color);
-            lines.push(b);
-            lines.push(color);
+            line_effect::push_polyline(lines, points, colors, widths, closed);
         }
     }
 
-    /// Sets the line width for the rendered lines.
+    /// Sets the width, in pixels, of the lines added after this call.
     pub fn set_line_width(&mut self, line_width: f32) {
         self.line_width = line_width;
     }
-}
 
-impl Renderer for LineRenderer {
-    /// Actually draws the lines.
-    fn render(&mut self, pass: usize, camera: &mut dyn Camera) {
+    /// Sets the shape of the extremities of the rendered lines.
+    pub fn set_line_cap(&mut self, cap: LineCap) {
+        self.style.cap = cap;
+    }
+
+    /// Sets the shape of the joins between consecutive segments of the rendered polylines.
+    pub fn set_line_join(&mut self, join: LineJoin) {
+        self.style.join = join;
+    }
+
+    /// Sets the dash pattern of the rendered lines, as the `(dash, gap)` lengths.
+    ///
+    /// Lengths are expressed in the same unit as the line coordinates. `None` draws solid lines.
+    pub fn set_dash_pattern(&mut self, pattern: Option<(f32, f32)>) {
+        self.style.dash = pattern;
+    }
+
+    /// Enables or disables the depth test for the rendered lines.
+    ///
+    /// Disabling the depth test draws the lines on top of the scene, which is useful for overlays.
+    pub fn set_depth_test(&mut self, enabled: bool) {
+        self.style.depth_test = enabled;
+    }
+
+    /// Draws the lines without removing them, e.g., to draw them in several viewports.
+    pub(crate) fn draw(&mut self, pass: usize, camera: &mut dyn Camera) {
         if self.lines.len() == 0 {
             return;
         }
 
-        self.shader.use_program();
-        self.pos.enable();
-        self.color.enable();
+        self.effect.activate(&Matrix4::identity(), &self.style);
 
-        camera.upload(pass, &mut self.proj, &mut self.view);
-
-        self.color.bind_sub_buffer(&mut self.lines, 1, 1);
-        self.pos.bind_sub_buffer(&mut self.lines, 1, 0);
-
-        let ctxt = Context::get();
-        verify!(ctxt.draw_arrays(Context::LINES, 0, (self.lines.len() / 2) as i32));
-        verify!(ctxt.line_width(self.line_width));
+        {
+            let (proj, view) = self.effect.proj_and_view();
+            camera.upload(pass, proj, view);
+        }
 
-        self.pos.disable();
-        self.color.disable();
+        self.effect.draw(&mut self.lines);
+        self.effect.deactivate();
+    }
 
+    /// Removes the lines drawn since the last frame.
+    pub(crate) fn clear(&mut self) {
         for lines in self.lines.data_mut().iter_mut() {
             lines.clear()
         }
     }
 }
 
-/// Vertex shader used by the material to display line.
-pub static LINES_VERTEX_SRC: &'static str = A_VERY_LONG_STRING;
-/// Fragment shader used by the material to display line.
-pub static LINES_FRAGMENT_SRC: &'static str = ANOTHER_VERY_LONG_STRING;
-
-const A_VERY_LONG_STRING: &'static str = "#version 100
-    attribute vec3 position;
-    attribute vec3 color;
-    varying   vec3 vColor;
-    uniform   mat4 proj;
-    uniform   mat4 view;
-    void main() {
-        gl_Position = proj * view * vec4(position, 1.0);
-        vColor = color;
-    }";
-
-const ANOTHER_VERY_LONG_STRING: &'static str = "#version 100
-#ifdef GL_FRAGMENT_PRECISION_HIGH
-   precision highp float;
-#else
-   precision mediump float;
-#endif
-
-    varying vec3 vColor;
-    void main() {
-        gl_FragColor = vec4(vColor, 1.0);
-    }";
+impl Renderer for LineRenderer {
+    /// Actually draws the lines.
+    fn render(&mut self, pass: usize, camera: &mut dyn Camera) {
+        self.draw(pass, camera);
+        self.clear();
+    }
+}
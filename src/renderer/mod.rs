@@ -2,12 +2,16 @@
 
 #[cfg(feature = "conrod")]
 pub use self::conrod_renderer::ConrodRenderer;
+pub use self::line_effect::{LineCap, LineJoin};
 pub use self::line_renderer::LineRenderer;
-pub use self::point_renderer::PointRenderer;
+pub use self::octree_point_cloud::OctreePointCloud;
+pub use self::point_renderer::{PersistentPointRenderer, PointRenderer, PointShape};
 pub use self::renderer::{PlanarRenderer, Renderer};
 
 #[cfg(feature = "conrod")]
 mod conrod_renderer;
+pub(crate) mod line_effect;
 pub mod line_renderer;
+mod octree_point_cloud;
 pub mod point_renderer;
 mod renderer;
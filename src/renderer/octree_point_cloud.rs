@@ -0,0 +1,596 @@
+//! A point cloud renderer streaming level-of-detail chunks from the disk.
+
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{self, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+use crate::camera::Camera;
+use crate::context::Context;
+use crate::renderer::point_renderer::{PointEffect, POINT_VERTEX_SIZE};
+use crate::renderer::{PointShape, Renderer};
+use crate::resource::{AllocationType, BufferType, GPUVec};
+use na::{Matrix4, Point3, Vector3, Vector4};
+
+const MAGIC: &'static [u8; 8] = b"KISS3DOC";
+const VERSION: u32 = 1;
+const HEADER_SIZE: u64 = 16;
+const NODE_RECORD_SIZE: u64 = 64;
+const POINT_RECORD_SIZE: u64 = 24;
+const NO_CHILD: u32 = u32::MAX;
+/// Number of cells, along each axis, of the grid used to subsample the points of a node.
+const GRID_RESOLUTION: f32 = 128.0;
+const MAX_DEPTH: usize = 20;
+
+struct OctreeNode {
+    min: Point3<f32>,
+    size: f32,
+    spacing: f32,
+    children: [u32; 8],
+    offset: u64,
+    num_points: u32,
+    points: Option<GPUVec<f32>>,
+    last_used: u64,
+    failed: bool,
+}
+
+/// A point cloud organized as an octree, with chunks loaded on demand from a file.
+///
+/// Each node of the octree contains a subsample of the points of its children, so that the point
+/// cloud can be displayed with a coarser level of detail when seen from afar. At each frame, the
+/// nodes are refined until their point spacing, projected on the screen, is smaller than the
+/// maximum screen-space error. The missing nodes are loaded from the file, and the least recently
+/// used ones are removed from the GPU once the point budget is exceeded. The nodes which fail to
+/// load are skipped, and their errors are returned by `OctreePointCloud::take_errors`.
+///
+/// The file is created by `OctreePointCloud::build`.
+pub struct OctreePointCloud {
+    file: File,
+    nodes: Vec<OctreeNode>,
+    effect: PointEffect,
+    frame: u64,
+    loaded_points: usize,
+    point_budget: usize,
+    max_loads_per_frame: usize,
+    max_screen_space_error: f32,
+    visible: Vec<usize>,
+    candidates: Vec<(f32, usize)>,
+    errors: Vec<io::Error>,
+}
+
+impl OctreePointCloud {
+    /// Builds the octree of the given points, and writes it to a file readable by `open`.
+    ///
+    /// # Arguments
+    /// * `path` - the file to create.
+    /// * `points` - the position of each point.
+    /// * `colors` - the color of each point. Must have the same length as `points`.
+    /// * `max_points_per_node` - nodes with more points are subdivided.
+    pub fn build<P: AsRef<Path>>(
+        path: P,
+        points: &[Point3<f32>],
+        colors: &[Point3<f32>],
+        max_points_per_node: usize,
+    ) -> io::Result<()> {
+        assert_eq!(
+            points.len(),
+            colors.len(),
+            "There must be exactly one color per point."
+        );
+
+        let mut min = Point3::new(0.0f32, 0.0, 0.0);
+        let mut max = Point3::new(0.0f32, 0.0, 0.0);
+
+        if let Some(first) = points.first() {
+            min = *first;
+            max = *first;
+
+            for p in points.iter() {
+                min = min.inf(p);
+                max = max.sup(p);
+            }
+        }
+
+        // Enlarge the cube slightly so that the points on its upper faces are inside.
+        let size = (max - min).max().max(1.0e-6) * 1.0001;
+        let indices = (0..points.len() as u32).collect();
+        let mut nodes = Vec::new();
+        let _ = build_node(
+            &mut nodes,
+            points,
+            indices,
+            min,
+            size,
+            0,
+            max_points_per_node.max(1),
+        );
+
+        let mut out = BufWriter::new(File::create(path)?);
+        out.write_all(MAGIC)?;
+        out.write_all(&VERSION.to_le_bytes())?;
+        out.write_all(&(nodes.len() as u32).to_le_bytes())?;
+
+        let mut offset = HEADER_SIZE + NODE_RECORD_SIZE * nodes.len() as u64;
+
+        for node in &nodes {
+            for coord in node.min.iter() {
+                out.write_all(&coord.to_le_bytes())?;
+            }
+
+            out.write_all(&node.size.to_le_bytes())?;
+            out.write_all(&node.spacing.to_le_bytes())?;
+
+            for child in node.children.iter() {
+                out.write_all(&child.to_le_bytes())?;
+            }
+
+            out.write_all(&offset.to_le_bytes())?;
+            out.write_all(&(node.points.len() as u32).to_le_bytes())?;
+            offset += POINT_RECORD_SIZE * node.points.len() as u64;
+        }
+
+        for node in &nodes {
+            for i in node.points.iter() {
+                let (p, c) = (&points[*i as usize], &colors[*i as usize]);
+
+                for val in p.iter().chain(c.iter()) {
+                    out.write_all(&val.to_le_bytes())?;
+                }
+            }
+        }
+
+        out.flush()
+    }
+
+    /// Opens a point cloud file created by `build`.
+    ///
+    /// Only the octree structure is read. The points are loaded when they are needed for
+    /// rendering.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<OctreePointCloud> {
+        let mut file = File::open(path)?;
+        let mut header = [0u8; HEADER_SIZE as usize];
+        file.read_exact(&mut header)?;
+
+        if &header[..8] != &MAGIC[..] || read_u32(&header[8..]) != VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Not a kiss3d octree point cloud file.",
+            ));
+        }
+
+        // The sizes read from the file are checked against its length before allocating.
+        let file_len = file.metadata()?.len();
+        let truncated = || {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                "The octree point cloud file is truncated.",
+            )
+        };
+        let num_nodes = read_u32(&header[12..]) as u64;
+
+        if num_nodes == 0 || HEADER_SIZE + num_nodes * NODE_RECORD_SIZE > file_len {
+            return Err(truncated());
+        }
+
+        let mut records = vec![0u8; (num_nodes * NODE_RECORD_SIZE) as usize];
+        file.read_exact(&mut records)?;
+
+        let nodes: Vec<OctreeNode> = records
+            .chunks(NODE_RECORD_SIZE as usize)
+            .map(|record| {
+                let mut children = [NO_CHILD; 8];
+
+                for (i, child) in children.iter_mut().enumerate() {
+                    *child = read_u32(&record[20 + i * 4..]);
+                }
+
+                OctreeNode {
+                    min: Point3::new(
+                        read_f32(&record[0..]),
+                        read_f32(&record[4..]),
+                        read_f32(&record[8..]),
+                    ),
+                    size: read_f32(&record[12..]),
+                    spacing: read_f32(&record[16..]),
+                    children,
+                    offset: read_u64(&record[52..]),
+                    num_points: read_u32(&record[60..]),
+                    points: None,
+                    last_used: 0,
+                    failed: false,
+                }
+            })
+            .collect();
+
+        for (id, node) in nodes.iter().enumerate() {
+            // The nodes are stored in depth-first order, so children come after their parent.
+            let valid_children = node.children.iter().all(|child| {
+                *child == NO_CHILD || (*child as usize > id && (*child as usize) < nodes.len())
+            });
+
+            if !valid_children {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "The octree point cloud file is corrupted.",
+                ));
+            }
+
+            let end = node
+                .offset
+                .checked_add(node.num_points as u64 * POINT_RECORD_SIZE)
+                .ok_or_else(truncated)?;
+
+            if end > file_len {
+                return Err(truncated());
+            }
+        }
+
+        let mut effect = PointEffect::new();
+        effect.attenuation = true;
+
+        Ok(OctreePointCloud {
+            file,
+            nodes,
+            effect,
+            frame: 0,
+            loaded_points: 0,
+            point_budget: 5_000_000,
+            max_loads_per_frame: 8,
+            max_screen_space_error: 2.0,
+            visible: Vec::new(),
+            candidates: Vec::new(),
+            errors: Vec::new(),
+        })
+    }
+
+    /// The number of nodes of the octree.
+    pub fn num_nodes(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// The number of points currently loaded on the GPU.
+    pub fn num_loaded_points(&self) -> usize {
+        self.loaded_points
+    }
+
+    /// Sets the maximum number of points kept on the GPU.
+    ///
+    /// Once this budget is exceeded, the least recently displayed nodes are unloaded. A node with
+    /// more points than the whole budget is still loaded, after unloading every node which is not
+    /// displayed, so the budget is exceeded while it is kept on the GPU.
+    pub fn set_point_budget(&mut self, budget: usize) {
+        self.point_budget = budget;
+    }
+
+    /// Returns and clears the errors of the nodes which failed to load since the last call.
+    ///
+    /// A node which failed to load is not read again.
+    pub fn take_errors(&mut self) -> Vec<io::Error> {
+        std::mem::replace(&mut self.errors, Vec::new())
+    }
+
+    /// Sets the maximum number of nodes read from the file at each frame.
+    pub fn set_max_loads_per_frame(&mut self, max_loads: usize) {
+        self.max_loads_per_frame = max_loads;
+    }
+
+    /// Sets the maximum screen-space error, in pixels.
+    ///
+    /// A node is refined as long as the spacing between its points, projected on the screen, is
+    /// greater than this error.
+    pub fn set_max_screen_space_error(&mut self, error: f32) {
+        self.max_screen_space_error = error;
+    }
+
+    /// Sets the factor applied to the size of the points.
+    ///
+    /// Points are sized after the spacing of the node they belong to, so a factor of 1 leaves
+    /// no gap between them.
+    pub fn set_point_size(&mut self, pt_size: f32) {
+        self.effect.point_size = pt_size;
+    }
+
+    /// Sets the shape of the rendered points.
+    pub fn set_point_shape(&mut self, shape: PointShape) {
+        self.effect.shape = shape;
+    }
+
+    fn select_nodes(&mut self, camera: &dyn Camera) {
+        let (_, _, _, h) = Context::get().current_viewport();
+        let transform = camera.transformation();
+        let proj = transform * camera.view_transform().inverse().to_homogeneous();
+        let perspective = proj[(3, 3)] == 0.0;
+        let scale = proj[(1, 1)].abs() * h.max(1) as f32 * 0.5;
+        let (znear, _) = camera.clip_planes();
+        let eye = camera.eye();
+        let planes = frustum_planes(&transform);
+        let mut stack = vec![0];
+
+        self.visible.clear();
+        self.candidates.clear();
+
+        while let Some(id) = stack.pop() {
+            let node = &self.nodes[id];
+            let max = node.min + Vector3::repeat(node.size);
+
+            if !intersects_frustum(&planes, &node.min, &max) {
+                continue;
+            }
+
+            let distance = if perspective {
+                let center = node.min + Vector3::repeat(node.size * 0.5);
+                let radius = node.size * 0.5 * 3.0f32.sqrt();
+                (na::distance(&eye, &center) - radius).max(znear)
+            } else {
+                1.0
+            };
+            let error = node.spacing * scale / distance;
+
+            if node.points.is_none() {
+                if !node.failed {
+                    self.candidates.push((error, id));
+                }
+
+                continue;
+            }
+
+            self.visible.push(id);
+
+            if error > self.max_screen_space_error {
+                stack.extend(
+                    node.children
+                        .iter()
+                        .filter(|c| **c != NO_CHILD)
+                        .map(|c| *c as usize),
+                );
+            }
+        }
+
+        for id in self.visible.iter() {
+            self.nodes[*id].last_used = self.frame;
+        }
+    }
+
+    fn load_candidates(&mut self) {
+        let mut candidates = std::mem::replace(&mut self.candidates, Vec::new());
+        candidates.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(Ordering::Equal));
+
+        for (_, id) in candidates.drain(..).take(self.max_loads_per_frame) {
+            let num_points = self.nodes[id].num_points as usize;
+
+            if self.loaded_points + num_points > self.point_budget {
+                self.evict(num_points);
+
+                // A node larger than the whole budget could never be loaded otherwise.
+                if self.loaded_points + num_points > self.point_budget
+                    && num_points <= self.point_budget
+                {
+                    break;
+                }
+            }
+
+            match self.read_node(id) {
+                Ok(points) => {
+                    let node = &mut self.nodes[id];
+                    let mut points =
+                        GPUVec::new(points, BufferType::Array, AllocationType::StaticDraw);
+                    points.load_to_gpu();
+                    points.unload_from_ram();
+                    node.points = Some(points);
+                    node.last_used = self.frame;
+                    self.loaded_points += num_points;
+                    self.visible.push(id);
+                }
+                Err(err) => {
+                    self.errors.push(io::Error::new(
+                        err.kind(),
+                        format!("could not load the octree node {}: {}", id, err),
+                    ));
+                    self.nodes[id].failed = true;
+                }
+            }
+        }
+
+        self.candidates = candidates;
+    }
+
+    /// Unloads the least recently used nodes until `required` points fit in the budget.
+    fn evict(&mut self, required: usize) {
+        let frame = self.frame;
+        let mut loaded: Vec<usize> = (0..self.nodes.len())
+            .filter(|id| self.nodes[*id].points.is_some() && self.nodes[*id].last_used < frame)
+            .collect();
+        loaded.sort_by_key(|id| self.nodes[*id].last_used);
+
+        for id in loaded {
+            if self.loaded_points + required <= self.point_budget {
+                break;
+            }
+
+            let node = &mut self.nodes[id];
+            node.points = None;
+            self.loaded_points -= node.num_points as usize;
+        }
+    }
+
+    fn read_node(&mut self, id: usize) -> io::Result<Vec<f32>> {
+        let node = &self.nodes[id];
+        let mut bytes = vec![0u8; node.num_points as usize * POINT_RECORD_SIZE as usize];
+        let _ = self.file.seek(SeekFrom::Start(node.offset))?;
+        self.file.read_exact(&mut bytes)?;
+
+        let mut points = Vec::with_capacity(node.num_points as usize * POINT_VERTEX_SIZE);
+
+        for record in bytes.chunks(POINT_RECORD_SIZE as usize) {
+            let val = |i: usize| read_f32(&record[i * 4..]);
+            PointEffect::push(
+                &mut points,
+                Point3::new(val(0), val(1), val(2)),
+                Point3::new(val(3), val(4), val(5)),
+                node.spacing,
+            );
+        }
+
+        Ok(points)
+    }
+}
+
+impl Renderer for OctreePointCloud {
+    /// Selects, loads, and draws the nodes required by the given camera.
+    fn render(&mut self, pass: usize, camera: &mut dyn Camera) {
+        if self.nodes.is_empty() {
+            return;
+        }
+
+        if pass == 0 {
+            self.frame += 1;
+        }
+
+        self.select_nodes(camera);
+
+        if pass == 0 {
+            self.load_candidates();
+        }
+
+        self.effect.activate(pass, camera);
+
+        for id in self.visible.iter() {
+            if let Some(ref mut points) = self.nodes[*id].points {
+                self.effect.draw(points);
+            }
+        }
+
+        self.effect.deactivate();
+    }
+}
+
+struct BuildNode {
+    min: Point3<f32>,
+    size: f32,
+    spacing: f32,
+    children: [u32; 8],
+    points: Vec<u32>,
+}
+
+fn build_node(
+    nodes: &mut Vec<BuildNode>,
+    points: &[Point3<f32>],
+    indices: Vec<u32>,
+    min: Point3<f32>,
+    size: f32,
+    depth: usize,
+    max_points_per_node: usize,
+) -> u32 {
+    let id = nodes.len();
+    let spacing = size / GRID_RESOLUTION;
+
+    nodes.push(BuildNode {
+        min,
+        size,
+        spacing,
+        children: [NO_CHILD; 8],
+        points: Vec::new(),
+    });
+
+    if indices.len() <= max_points_per_node || depth == MAX_DEPTH {
+        nodes[id].points = indices;
+        return id as u32;
+    }
+
+    // Keep one point per grid cell, and distribute the others to the children.
+    let max_cell = GRID_RESOLUTION - 1.0;
+    let mut occupied = HashSet::new();
+    let mut kept = Vec::new();
+    let mut octants: [Vec<u32>; 8] = Default::default();
+
+    for i in indices {
+        let local = (points[i as usize] - min) / spacing;
+        let cell = (
+            local.x.max(0.0).min(max_cell) as u32,
+            local.y.max(0.0).min(max_cell) as u32,
+            local.z.max(0.0).min(max_cell) as u32,
+        );
+
+        if occupied.insert(cell) {
+            kept.push(i);
+        } else {
+            let half = GRID_RESOLUTION as u32 / 2;
+            let octant = (cell.0 >= half) as usize
+                | (((cell.1 >= half) as usize) << 1)
+                | (((cell.2 >= half) as usize) << 2);
+            octants[octant].push(i);
+        }
+    }
+
+    nodes[id].points = kept;
+
+    let half = size * 0.5;
+
+    for (octant, child_indices) in octants.iter_mut().enumerate() {
+        if child_indices.is_empty() {
+            continue;
+        }
+
+        let child_min = min
+            + Vector3::new(
+                (octant & 1) as f32 * half,
+                ((octant >> 1) & 1) as f32 * half,
+                ((octant >> 2) & 1) as f32 * half,
+            );
+        let child_indices = std::mem::replace(child_indices, Vec::new());
+        let child = build_node(
+            nodes,
+            points,
+            child_indices,
+            child_min,
+            half,
+            depth + 1,
+            max_points_per_node,
+        );
+        nodes[id].children[octant] = child;
+    }
+
+    id as u32
+}
+
+/// The six planes bounding the view frustum, as `(normal, offset)` stored in a `Vector4`.
+fn frustum_planes(transform: &Matrix4<f32>) -> [Vector4<f32>; 6] {
+    let r0 = transform.row(0).transpose();
+    let r1 = transform.row(1).transpose();
+    let r2 = transform.row(2).transpose();
+    let r3 = transform.row(3).transpose();
+
+    [r3 + r0, r3 - r0, r3 + r1, r3 - r1, r3 + r2, r3 - r2]
+}
+
+fn intersects_frustum(planes: &[Vector4<f32>; 6], min: &Point3<f32>, max: &Point3<f32>) -> bool {
+    planes.iter().all(|plane| {
+        // The corner of the box that is the furthest along the plane normal.
+        let corner = Vector3::new(
+            if plane.x >= 0.0 { max.x } else { min.x },
+            if plane.y >= 0.0 { max.y } else { min.y },
+            if plane.z >= 0.0 { max.z } else { min.z },
+        );
+
+        plane.xyz().dot(&corner) + plane.w >= 0.0
+    })
+}
+
+fn read_u32(bytes: &[u8]) -> u32 {
+    let mut buf = [0u8; 4];
+    buf.copy_from_slice(&bytes[..4]);
+    u32::from_le_bytes(buf)
+}
+
+fn read_u64(bytes: &[u8]) -> u64 {
+    let mut buf = [0u8; 8];
+    buf.copy_from_slice(&bytes[..8]);
+    u64::from_le_bytes(buf)
+}
+
+fn read_f32(bytes: &[u8]) -> f32 {
+    let mut buf = [0u8; 4];
+    buf.copy_from_slice(&bytes[..4]);
+    f32::from_le_bytes(buf)
+}
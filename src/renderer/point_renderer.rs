@@ -1,52 +1,152 @@
 //! A batched point renderer.
 
+use std::rc::Rc;
+
 use crate::camera::Camera;
-use crate::context::Context;
+use crate::context::{Context, Texture};
 use crate::renderer::Renderer;
 use crate::resource::{AllocationType, BufferType, Effect, GPUVec, ShaderAttribute, ShaderUniform};
-use na::{Matrix4, Point3};
+use na::{Matrix4, Point3, Vector3};
 
 #[path = "../error.rs"]
 mod error;
 
-/// Structure which manages the display of short-living points.
-pub struct PointRenderer {
-    shader: Effect,
-    pos: ShaderAttribute<Point3<f32>>,
-    color: ShaderAttribute<Point3<f32>>,
-    proj: ShaderUniform<Matrix4<f32>>,
-    view: ShaderUniform<Matrix4<f32>>,
-    points: GPUVec<Point3<f32>>,
-    point_size: f32,
+/// Number of floats stored per point: the position, the color, and the size.
+pub(crate) const POINT_VERTEX_SIZE: usize = 7;
+
+/// The shape of the rendered points.
+#[derive(Clone)]
+pub enum PointShape {
+    /// Points are rendered as screen-aligned squares.
+    Square,
+    /// Points are rendered as screen-aligned discs.
+    Round,
+    /// Points are rendered as screen-aligned squares textured with the given sprite.
+    ///
+    /// The sprite color is multiplied by the point color, and fragments with an alpha lower than
+    /// 0.5 are discarded.
+    Sprite(Rc<Texture>),
 }
 
-/// Persistent version of the PointRenderer
-pub struct PersistentPointRenderer {
+/// The shader and display parameters shared by all the point renderers.
+pub(crate) struct PointEffect {
     shader: Effect,
     pos: ShaderAttribute<Point3<f32>>,
     color: ShaderAttribute<Point3<f32>>,
+    size: ShaderAttribute<f32>,
     proj: ShaderUniform<Matrix4<f32>>,
     view: ShaderUniform<Matrix4<f32>>,
-    points: GPUVec<Point3<f32>>,
-    point_size: f32,
-    visible: bool,
+    size_params: ShaderUniform<Vector3<f32>>,
+    shape_id: ShaderUniform<i32>,
+    sprite: ShaderUniform<i32>,
+    pub point_size: f32,
+    pub shape: PointShape,
+    pub attenuation: bool,
 }
 
-impl PointRenderer {
-    /// Creates a new points manager.
-    pub fn new() -> PointRenderer {
+impl PointEffect {
+    /// Creates the point shader.
+    pub fn new() -> PointEffect {
         let mut shader = Effect::new_from_str(POINTS_VERTEX_SRC, POINTS_FRAGMENT_SRC);
 
         shader.use_program();
 
-        PointRenderer {
-            points: GPUVec::new(Vec::new(), BufferType::Array, AllocationType::StreamDraw),
+        PointEffect {
             pos: shader.get_attrib::<Point3<f32>>("position").unwrap(),
             color: shader.get_attrib::<Point3<f32>>("color").unwrap(),
+            size: shader.get_attrib::<f32>("size").unwrap(),
             proj: shader.get_uniform::<Matrix4<f32>>("proj").unwrap(),
             view: shader.get_uniform::<Matrix4<f32>>("view").unwrap(),
+            size_params: shader.get_uniform::<Vector3<f32>>("size_params").unwrap(),
+            shape_id: shader.get_uniform::<i32>("shape").unwrap(),
+            sprite: shader.get_uniform::<i32>("sprite").unwrap(),
             shader,
             point_size: 1.0,
+            shape: PointShape::Square,
+            attenuation: false,
+        }
+    }
+
+    /// Appends a point to a buffer laid out as expected by `draw`.
+    pub fn push(points: &mut Vec<f32>, pt: Point3<f32>, color: Point3<f32>, size: f32) {
+        points.extend_from_slice(&[pt.x, pt.y, pt.z, color.x, color.y, color.z, size]);
+    }
+
+    /// Enables this effect and uploads the camera and display parameters.
+    pub fn activate(&mut self, pass: usize, camera: &mut dyn Camera) {
+        let ctxt = Context::get();
+        let (_, _, _, h) = ctxt.current_viewport();
+
+        self.shader.use_program();
+        self.pos.enable();
+        self.color.enable();
+        self.size.enable();
+
+        camera.upload(pass, &mut self.proj, &mut self.view);
+
+        let attenuation = if self.attenuation { 1.0 } else { 0.0 };
+        self.size_params
+            .upload(&Vector3::new(self.point_size, h.max(1) as f32, attenuation));
+
+        let shape_id = match self.shape {
+            PointShape::Square => 0,
+            PointShape::Round => 1,
+            PointShape::Sprite(ref texture) => {
+                verify!(ctxt.active_texture(Context::TEXTURE0));
+                verify!(ctxt.bind_texture(Context::TEXTURE_2D, Some(&*texture)));
+                self.sprite.upload(&0);
+                2
+            }
+        };
+        self.shape_id.upload(&shape_id);
+    }
+
+    /// Draws the points stored in the given buffer.
+    pub fn draw(&mut self, points: &mut GPUVec<f32>) {
+        let npoints = points.len() / POINT_VERTEX_SIZE;
+
+        if npoints == 0 {
+            return;
+        }
+
+        let stride = POINT_VERTEX_SIZE - 1;
+
+        unsafe {
+            self.pos.bind_sub_buffer_generic(points, stride, 0);
+            self.color.bind_sub_buffer_generic(points, stride, 3);
+            self.size.bind_sub_buffer_generic(points, stride, 6);
+        }
+
+        verify!(Context::get().draw_arrays(Context::POINTS, 0, npoints as i32));
+    }
+
+    /// Disables this effect.
+    pub fn deactivate(&mut self) {
+        self.pos.disable();
+        self.color.disable();
+        self.size.disable();
+    }
+}
+
+/// Structure which manages the display of short-living points.
+pub struct PointRenderer {
+    effect: PointEffect,
+    points: GPUVec<f32>,
+}
+
+/// Persistent version of the PointRenderer
+pub struct PersistentPointRenderer {
+    effect: PointEffect,
+    points: GPUVec<f32>,
+    visible: bool,
+}
+
+impl PointRenderer {
+    /// Creates a new points manager.
+    pub fn new() -> PointRenderer {
+        PointRenderer {
+            effect: PointEffect::new(),
+            points: GPUVec::new(Vec::new(), BufferType::Array, AllocationType::StreamDraw),
         }
     }
 
@@ -56,16 +156,56 @@ impl PointRenderer {
     }
 
     /// Sets the point size for the rendered points.
+    ///
+    /// The size of each point is multiplied by this factor. Points added with `draw_point` have
+    /// a size of 1, so this is their actual size.
     pub fn set_point_size(&mut self, pt_size: f32) {
-        self.point_size = pt_size;
+        self.effect.point_size = pt_size;
+    }
+
+    /// Sets the shape of the rendered points.
+    pub fn set_point_shape(&mut self, shape: PointShape) {
+        self.effect.shape = shape;
+    }
+
+    /// Enables or disables the attenuation of the point sizes with their distance to the camera.
+    ///
+    /// If disabled, sizes are expressed in pixels. If enabled, they are expressed in world units
+    /// so that points closer to the camera appear bigger.
+    pub fn set_size_attenuation(&mut self, enabled: bool) {
+        self.effect.attenuation = enabled;
     }
 
     /// Adds a point to be drawn during the next frame. Points are not persistent between frames.
     /// This method must be called for each point to draw, and at each update loop iteration.
     pub fn draw_point(&mut self, pt: Point3<f32>, color: Point3<f32>) {
+        self.draw_point_with_size(pt, color, 1.0)
+    }
+
+    /// Adds a point with the given size to be drawn during the next frame.
+    ///
+    /// The size is multiplied by the factor set with `set_point_size`.
+    pub fn draw_point_with_size(&mut self, pt: Point3<f32>, color: Point3<f32>, size: f32) {
         for points in self.points.data_mut().iter_mut() {
-            points.push(pt);
-            points.push(color);
+            PointEffect::push(points, pt, color, size)
+        }
+    }
+
+    /// Draws the points without removing them, e.g., to draw them in several viewports.
+    pub(crate) fn draw(&mut self, pass: usize, camera: &mut dyn Camera) {
+        if self.points.len() == 0 {
+            return;
+        }
+
+        self.effect.activate(pass, camera);
+        self.effect.draw(&mut self.points);
+        self.effect.deactivate();
+    }
+
+    /// Removes the points drawn since the last frame.
+    pub(crate) fn clear(&mut self) {
+        for points in self.points.data_mut().iter_mut() {
+            points.clear()
         }
     }
 }
@@ -73,18 +213,9 @@ impl PointRenderer {
 impl PersistentPointRenderer {
     /// Creates a new points manager.
     pub fn new() -> PersistentPointRenderer {
-        let mut shader = Effect::new_from_str(POINTS_VERTEX_SRC, POINTS_FRAGMENT_SRC);
-
-        shader.use_program();
-
         PersistentPointRenderer {
-            points: GPUVec::new(Vec::new(), BufferType::Array, AllocationType::StreamDraw),
-            pos: shader.get_attrib::<Point3<f32>>("position").unwrap(),
-            color: shader.get_attrib::<Point3<f32>>("color").unwrap(),
-            proj: shader.get_uniform::<Matrix4<f32>>("proj").unwrap(),
-            view: shader.get_uniform::<Matrix4<f32>>("view").unwrap(),
-            shader,
-            point_size: 1.0,
+            effect: PointEffect::new(),
+            points: GPUVec::new(Vec::new(), BufferType::Array, AllocationType::StaticDraw),
             visible: true,
         }
     }
@@ -95,8 +226,29 @@ impl PersistentPointRenderer {
     }
 
     /// Sets the point size for the rendered points.
+    ///
+    /// The size of each point is multiplied by this factor. Points added with `draw_point` have
+    /// a size of 1, so this is their actual size.
     pub fn set_point_size(&mut self, pt_size: f32) {
-        self.point_size = pt_size;
+        self.effect.point_size = pt_size;
+    }
+
+    /// Sets the shape of the rendered points.
+    pub fn set_point_shape(&mut self, shape: PointShape) {
+        self.effect.shape = shape;
+    }
+
+    /// Enables or disables the attenuation of the point sizes with their distance to the camera.
+    ///
+    /// If disabled, sizes are expressed in pixels. If enabled, they are expressed in world units
+    /// so that points closer to the camera appear bigger.
+    pub fn set_size_attenuation(&mut self, enabled: bool) {
+        self.effect.attenuation = enabled;
+    }
+
+    /// The number of points managed by this renderer.
+    pub fn num_points(&self) -> usize {
+        self.points.len() / POINT_VERTEX_SIZE
     }
 
     /// Clear all the points
@@ -116,12 +268,20 @@ impl PersistentPointRenderer {
         self.visible = true
     }
 
-    /// Adds a point to be drawn during the next frame. Points are not persistent between frames.
-    /// This method must be called for each point to draw, and at each update loop iteration.
+    /// Adds a point to be drawn until the next call to `clear`.
+    ///
+    /// The points are uploaded to the GPU only when they are modified, so large point clouds
+    /// should be added once rather than at each frame.
     pub fn draw_point(&mut self, pt: Point3<f32>, color: Point3<f32>) {
+        self.draw_point_with_size(pt, color, 1.0)
+    }
+
+    /// Adds a point with the given size to be drawn until the next call to `clear`.
+    ///
+    /// The size is multiplied by the factor set with `set_point_size`.
+    pub fn draw_point_with_size(&mut self, pt: Point3<f32>, color: Point3<f32>, size: f32) {
         for points in self.points.data_mut().iter_mut() {
-            points.push(pt);
-            points.push(color);
+            PointEffect::push(points, pt, color, size)
         }
     }
 }
@@ -129,54 +289,21 @@ impl PersistentPointRenderer {
 impl Renderer for PointRenderer {
     /// Actually draws the points.
     fn render(&mut self, pass: usize, camera: &mut dyn Camera) {
-        if self.points.len() == 0 {
-            return;
-        }
-
-        self.shader.use_program();
-        self.pos.enable();
-        self.color.enable();
-
-        camera.upload(pass, &mut self.proj, &mut self.view);
-
-        self.color.bind_sub_buffer(&mut self.points, 1, 1);
-        self.pos.bind_sub_buffer(&mut self.points, 1, 0);
-
-        let ctxt = Context::get();
-        verify!(ctxt.point_size(self.point_size));
-        verify!(ctxt.draw_arrays(Context::POINTS, 0, (self.points.len() / 2) as i32));
-
-        self.pos.disable();
-        self.color.disable();
-
-        for points in self.points.data_mut().iter_mut() {
-            points.clear()
-        }
+        self.draw(pass, camera);
+        self.clear();
     }
 }
 
 impl Renderer for PersistentPointRenderer {
     /// Actually draws the points.
     fn render(&mut self, pass: usize, camera: &mut dyn Camera) {
-        if self.points.len() == 0 {
+        if !self.needs_rendering() {
             return;
         }
 
-        self.shader.use_program();
-        self.pos.enable();
-        self.color.enable();
-
-        camera.upload(pass, &mut self.proj, &mut self.view);
-
-        self.color.bind_sub_buffer(&mut self.points, 1, 1);
-        self.pos.bind_sub_buffer(&mut self.points, 1, 0);
-
-        let ctxt = Context::get();
-        verify!(ctxt.point_size(self.point_size));
-        verify!(ctxt.draw_arrays(Context::POINTS, 0, (self.points.len() / 2) as i32));
-
-        self.pos.disable();
-        self.color.disable();
+        self.effect.activate(pass, camera);
+        self.effect.draw(&mut self.points);
+        self.effect.deactivate();
     }
 }
 
@@ -186,13 +313,24 @@ pub static POINTS_VERTEX_SRC: &'static str = A_VERY_LONG_STRING;
 pub static POINTS_FRAGMENT_SRC: &'static str = ANOTHER_VERY_LONG_STRING;
 
 const A_VERY_LONG_STRING: &'static str = "#version 100
-    attribute vec3 position;
-    attribute vec3 color;
-    varying   vec3 Color;
-    uniform   mat4 proj;
-    uniform   mat4 view;
+    attribute vec3  position;
+    attribute vec3  color;
+    attribute float size;
+    varying   vec3  Color;
+    uniform   mat4  proj;
+    uniform   mat4  view;
+    // x: size multiplier, y: viewport height, z: 1.0 if the size is attenuated with the distance.
+    uniform   vec3  size_params;
     void main() {
         gl_Position = proj * view * vec4(position, 1.0);
+
+        float pixels = size * size_params.x;
+
+        if (size_params.z != 0.0) {
+            pixels *= 0.5 * proj[1][1] * size_params.y / gl_Position.w;
+        }
+
+        gl_PointSize = max(pixels, 1.0);
         Color = color;
     }";
 
@@ -203,7 +341,29 @@ const ANOTHER_VERY_LONG_STRING: &'static str = "#version 100
    precision mediump float;
 #endif
 
-    varying vec3 Color;
+    varying vec3      Color;
+    uniform int       shape;
+    uniform sampler2D sprite;
     void main() {
-        gl_FragColor = vec4(Color, 1.0);
+        if (shape == 1) {
+            vec2 offset = gl_PointCoord - vec2(0.5);
+
+            if (dot(offset, offset) > 0.25) {
+                discard;
+            }
+
+            gl_FragColor = vec4(Color, 1.0);
+        }
+        else if (shape == 2) {
+            vec4 texel = texture2D(sprite, gl_PointCoord);
+
+            if (texel.a < 0.5) {
+                discard;
+            }
+
+            gl_FragColor = vec4(Color * texel.rgb, 1.0);
+        }
+        else {
+            gl_FragColor = vec4(Color, 1.0);
+        }
     }";
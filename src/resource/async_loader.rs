@@ -0,0 +1,182 @@
+//! Loading of resources on a worker thread.
+
+use std::any::Any;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::io;
+use std::panic::{self, AssertUnwindSafe};
+use std::rc::Rc;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+
+thread_local!(static KEY_ASYNC_LOADER: RefCell<AsyncLoader> = RefCell::new(AsyncLoader::new()));
+
+type Job = Box<dyn FnOnce() -> Box<dyn Any + Send> + Send>;
+type Finisher = Box<dyn FnOnce(Box<dyn Any + Send>)>;
+
+/// A value being loaded asynchronously.
+pub struct Loading<T> {
+    value: Rc<RefCell<Option<T>>>,
+}
+
+impl<T> Clone for Loading<T> {
+    fn clone(&self) -> Self {
+        Loading {
+            value: self.value.clone(),
+        }
+    }
+}
+
+impl<T> Loading<T> {
+    pub(crate) fn new() -> Loading<T> {
+        Loading {
+            value: Rc::new(RefCell::new(None)),
+        }
+    }
+
+    pub(crate) fn set(&self, value: T) {
+        *self.value.borrow_mut() = Some(value)
+    }
+
+    /// Whether the value finished loading.
+    pub fn is_loaded(&self) -> bool {
+        self.value.borrow().is_some()
+    }
+}
+
+impl<T: Clone> Loading<T> {
+    /// The loaded value, or `None` if it is still loading.
+    pub fn get(&self) -> Option<T> {
+        self.value.borrow().clone()
+    }
+}
+
+/// The asynchronous resource loader.
+///
+/// Files are read and decoded on a worker thread, one at a time. The results are then finished,
+/// e.g. uploaded to the GPU, on the render thread by `Window::render`. This loader is used by
+/// the `*_async` loading methods, like `SceneNode::add_obj_async` or `TextureManager::add_async`.
+pub struct AsyncLoader {
+    jobs: Option<Sender<(usize, Job)>>,
+    results: Option<Receiver<(usize, Option<Box<dyn Any + Send>>)>>,
+    finishers: HashMap<usize, Finisher>,
+    next_id: usize,
+    errors: Vec<io::Error>,
+}
+
+impl AsyncLoader {
+    /// Creates a new loader. Its worker thread is started by the first load.
+    pub fn new() -> AsyncLoader {
+        AsyncLoader {
+            jobs: None,
+            results: None,
+            finishers: HashMap::new(),
+            next_id: 0,
+            errors: Vec::new(),
+        }
+    }
+
+    /// Mutably applies a function to the global loader.
+    pub fn get_global_loader<T, F: FnOnce(&mut AsyncLoader) -> T>(f: F) -> T {
+        KEY_ASYNC_LOADER.with(|loader| f(&mut *loader.borrow_mut()))
+    }
+
+    /// Runs `load` on the worker thread, then calls `finish` with its result on the render
+    /// thread.
+    pub fn spawn<T, L, F>(&mut self, load: L, finish: F)
+    where
+        T: Send + 'static,
+        L: FnOnce() -> T + Send + 'static,
+        F: FnOnce(T) + 'static,
+    {
+        if self.jobs.is_none() {
+            let (job_sender, job_receiver) = mpsc::channel::<(usize, Job)>();
+            let (result_sender, result_receiver) = mpsc::channel();
+
+            let _ = thread::Builder::new()
+                .name("kiss3d loader".to_string())
+                .spawn(move || {
+                    for (id, job) in job_receiver {
+                        // A panicking job must not stop the other loads.
+                        let result = panic::catch_unwind(AssertUnwindSafe(job)).ok();
+
+                        if result_sender.send((id, result)).is_err() {
+                            break;
+                        }
+                    }
+                })
+                .expect("Failed to start the loader thread.");
+
+            self.jobs = Some(job_sender);
+            self.results = Some(result_receiver);
+        }
+
+        let id = self.next_id;
+        self.next_id += 1;
+
+        let job: Job = Box::new(move || Box::new(load()) as Box<dyn Any + Send>);
+        let finisher: Finisher = Box::new(move |result| {
+            finish(
+                *result
+                    .downcast::<T>()
+                    .expect("Invalid loading result type."),
+            )
+        });
+
+        let _ = self.finishers.insert(id, finisher);
+        self.jobs
+            .as_ref()
+            .unwrap()
+            .send((id, job))
+            .expect("The loader thread stopped.");
+    }
+
+    /// The number of loads that are not finished yet.
+    pub fn num_pending(&self) -> usize {
+        self.finishers.len()
+    }
+
+    /// Records an error that occurred while loading a resource.
+    pub fn push_error(&mut self, error: io::Error) {
+        self.errors.push(error)
+    }
+
+    /// Returns and clears the errors that occurred since the last call.
+    ///
+    /// Resources that failed to load keep their placeholder.
+    pub fn take_errors(&mut self) -> Vec<io::Error> {
+        std::mem::replace(&mut self.errors, Vec::new())
+    }
+
+    fn take_finished(&mut self) -> Vec<(Finisher, Box<dyn Any + Send>)> {
+        let mut res = Vec::new();
+
+        if let Some(results) = &self.results {
+            for (id, result) in results.try_iter() {
+                let finisher = self.finishers.remove(&id);
+
+                match (finisher, result) {
+                    (Some(finisher), Some(result)) => res.push((finisher, result)),
+                    _ => self.errors.push(io::Error::new(
+                        io::ErrorKind::Other,
+                        "A resource loader panicked.",
+                    )),
+                }
+            }
+        }
+
+        res
+    }
+
+    /// Finishes the loads completed by the worker thread.
+    ///
+    /// This is called automatically by `Window::render`.
+    pub fn finish_loads() {
+        // The finishers are run once the loader is released, so they may start new loads.
+        let finished = Self::get_global_loader(|loader| loader.take_finished());
+
+        for (finisher, result) in finished {
+            finisher(result)
+        }
+    }
+}
@@ -0,0 +1,230 @@
+//! Textures updated from CPU buffers.
+
+use std::rc::Rc;
+
+use crate::context::{Context, Texture};
+use crate::resource::TextureManager;
+
+#[path = "../error.rs"]
+mod error;
+
+/// The pixel format of a dynamic texture.
+///
+/// The single component formats, `R8` and `R32F`, are sampled as `(r, 0, 0, 1)` on native
+/// platforms, but as `(r, r, r, 1)` on WebGL 1 where they are stored as luminance textures.
+/// Shaders should only read the red component of those textures to behave the same everywhere.
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub enum TextureFormat {
+    /// One 8-bits red component per pixel.
+    R8,
+    /// Three 8-bits red, green and blue components per pixel.
+    Rgb8,
+    /// Four 8-bits red, green, blue and alpha components per pixel.
+    Rgba8,
+    /// One 32-bits floating point red component per pixel.
+    ///
+    /// On WebGL 1, this requires the `OES_texture_float` extension.
+    R32F,
+}
+
+impl TextureFormat {
+    /// The number of components of each pixel.
+    pub fn num_components(self) -> usize {
+        match self {
+            TextureFormat::R8 | TextureFormat::R32F => 1,
+            TextureFormat::Rgb8 => 3,
+            TextureFormat::Rgba8 => 4,
+        }
+    }
+
+    /// Whether the components of this format are floating point numbers instead of bytes.
+    pub fn is_float(self) -> bool {
+        self == TextureFormat::R32F
+    }
+
+    fn internal_format(self) -> u32 {
+        match self {
+            TextureFormat::R8 => Context::R8,
+            TextureFormat::Rgb8 => Context::RGB,
+            TextureFormat::Rgba8 => Context::RGBA,
+            TextureFormat::R32F => Context::R32F,
+        }
+    }
+
+    fn format(self) -> u32 {
+        match self {
+            TextureFormat::R8 | TextureFormat::R32F => Context::RED,
+            TextureFormat::Rgb8 => Context::RGB,
+            TextureFormat::Rgba8 => Context::RGBA,
+        }
+    }
+}
+
+/// A texture whose content is updated from CPU buffers, e.g., every frame.
+///
+/// The GL texture is allocated once, and every update replaces all or part of its content in
+/// place. Created by `TextureManager::add_dynamic`.
+pub struct DynamicTexture {
+    texture: Rc<Texture>,
+    width: u32,
+    height: u32,
+    format: TextureFormat,
+}
+
+impl DynamicTexture {
+    /// Allocates a texture with the given size and format, filled with zeros.
+    ///
+    /// The texture uses linear filtering without mipmaps, and is clamped to its edges.
+    pub fn new(width: u32, height: u32, format: TextureFormat) -> DynamicTexture {
+        let ctxt = Context::get();
+        let texture = TextureManager::new_texture();
+
+        verify!(ctxt.bind_texture(Context::TEXTURE_2D, Some(&*texture)));
+
+        if format.is_float() {
+            let zeros = vec![0.0; width as usize * height as usize];
+            verify!(ctxt.tex_image2df(
+                Context::TEXTURE_2D,
+                0,
+                format.internal_format() as i32,
+                width as i32,
+                height as i32,
+                0,
+                format.format(),
+                Some(&zeros)
+            ));
+        } else {
+            let zeros = vec![0; width as usize * height as usize * format.num_components()];
+            verify!(ctxt.pixel_storei(Context::UNPACK_ALIGNMENT, 1));
+            verify!(ctxt.tex_image2d(
+                Context::TEXTURE_2D,
+                0,
+                format.internal_format() as i32,
+                width as i32,
+                height as i32,
+                0,
+                format.format(),
+                Some(&zeros)
+            ));
+            verify!(ctxt.pixel_storei(Context::UNPACK_ALIGNMENT, 4));
+        }
+
+        DynamicTexture {
+            texture,
+            width,
+            height,
+            format,
+        }
+    }
+
+    /// The texture updated by this dynamic texture.
+    #[inline]
+    pub fn texture(&self) -> &Rc<Texture> {
+        &self.texture
+    }
+
+    /// The width of this texture, in pixels.
+    #[inline]
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// The height of this texture, in pixels.
+    #[inline]
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// The pixel format of this texture.
+    #[inline]
+    pub fn format(&self) -> TextureFormat {
+        self.format
+    }
+
+    /// Replaces the whole content of this texture.
+    ///
+    /// `data` contains the rows of the texture, from top to bottom, without padding. Panics if
+    /// its length does not match the size of the texture, or if the format of the texture is
+    /// `R32F`.
+    pub fn update(&self, data: &[u8]) {
+        self.update_region(0, 0, self.width, self.height, data)
+    }
+
+    /// Replaces the content of a region of this texture.
+    ///
+    /// `data` contains the rows of the region, from top to bottom, without padding. Panics if
+    /// the region does not fit in the texture, if the length of `data` does not match the size
+    /// of the region, or if the format of the texture is `R32F`.
+    pub fn update_region(&self, x: u32, y: u32, width: u32, height: u32, data: &[u8]) {
+        assert!(
+            !self.format.is_float(),
+            "Byte data cannot be uploaded to a floating point texture."
+        );
+        self.check_region(x, y, width, height, data.len());
+
+        let ctxt = Context::get();
+        verify!(ctxt.bind_texture(Context::TEXTURE_2D, Some(&*self.texture)));
+        verify!(ctxt.pixel_storei(Context::UNPACK_ALIGNMENT, 1));
+        verify!(ctxt.tex_sub_image2d(
+            Context::TEXTURE_2D,
+            0,
+            x as i32,
+            y as i32,
+            width as i32,
+            height as i32,
+            self.format.format(),
+            Some(data)
+        ));
+        verify!(ctxt.pixel_storei(Context::UNPACK_ALIGNMENT, 4));
+
+        self.texture.refresh_mipmaps();
+    }
+
+    /// Replaces the whole content of this floating point texture.
+    ///
+    /// See `update`. Panics if the format of the texture is not `R32F`.
+    pub fn update_f32(&self, data: &[f32]) {
+        self.update_region_f32(0, 0, self.width, self.height, data)
+    }
+
+    /// Replaces the content of a region of this floating point texture.
+    ///
+    /// See `update_region`. Panics if the format of the texture is not `R32F`.
+    pub fn update_region_f32(&self, x: u32, y: u32, width: u32, height: u32, data: &[f32]) {
+        assert!(
+            self.format.is_float(),
+            "Floating point data can only be uploaded to a floating point texture."
+        );
+        self.check_region(x, y, width, height, data.len());
+
+        let ctxt = Context::get();
+        verify!(ctxt.bind_texture(Context::TEXTURE_2D, Some(&*self.texture)));
+        verify!(ctxt.tex_sub_image2df(
+            Context::TEXTURE_2D,
+            0,
+            x as i32,
+            y as i32,
+            width as i32,
+            height as i32,
+            self.format.format(),
+            Some(data)
+        ));
+
+        self.texture.refresh_mipmaps();
+    }
+
+    fn check_region(&self, x: u32, y: u32, width: u32, height: u32, len: usize) {
+        let fits = |start: u32, size: u32, max: u32| {
+            start.checked_add(size).map_or(false, |end| end <= max)
+        };
+        assert!(
+            fits(x, width, self.width) && fits(y, height, self.height),
+            "The region does not fit in the texture."
+        );
+        assert_eq!(
+            len,
+            width as usize * height as usize * self.format.num_components(),
+            "The data length does not match the region size."
+        );
+    }
+}
@@ -1,103 +1,368 @@
+use std::cell::RefCell;
+use std::error::Error;
+use std::fmt;
 use std::fs::File;
-use std::io::Read;
+use std::io::{self, Read};
 use std::marker::PhantomData;
 use std::mem;
 use std::path::Path;
+use std::rc::Rc;
 use std::str;
 
 use crate::context::{Context, GLintptr, Program, Shader, UniformLocation};
-use crate::resource::{GLPrimitive, GPUVec};
+use crate::resource::shader_library::Preprocessed;
+use crate::resource::{GLPrimitive, GPUVec, ShaderLibrary};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::resource::{HotReloader, ReloadError};
 
 #[path = "../error.rs"]
 mod error;
 
+/// A stage of a shader program.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ShaderStage {
+    /// The vertex shader.
+    Vertex,
+    /// The fragment shader.
+    Fragment,
+}
+
+impl fmt::Display for ShaderStage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ShaderStage::Vertex => write!(f, "vertex"),
+            ShaderStage::Fragment => write!(f, "fragment"),
+        }
+    }
+}
+
+/// An error raised while building a shader program or looking up its variables.
+#[derive(Debug)]
+pub enum ShaderError {
+    /// A shader file could not be read.
+    Io(io::Error),
+    /// The shader or program object could not be created.
+    Creation,
+    /// A shader could not be preprocessed.
+    Preprocessing {
+        /// The stage of the shader.
+        stage: ShaderStage,
+        /// The included chunk containing the error, or `None` if it is in the shader itself.
+        chunk: Option<String>,
+        /// The line of the error.
+        line: u32,
+        /// The description of the error.
+        message: String,
+    },
+    /// A shader did not compile.
+    Compilation {
+        /// The stage of the shader.
+        stage: ShaderStage,
+        /// The included chunk containing the first error, or `None` if it is in the shader itself.
+        chunk: Option<String>,
+        /// The line of the first error, if it could be found in the log.
+        line: Option<u32>,
+        /// The compilation log of the driver.
+        log: String,
+    },
+    /// The shaders did not link.
+    Linking {
+        /// The link log of the driver.
+        log: String,
+    },
+    /// The program has no active uniform with this name.
+    UniformNotFound(String),
+    /// The program has no active attribute with this name.
+    AttributeNotFound(String),
+    /// OpenGL reported an error, with this code.
+    Gl(u32),
+}
+
+impl fmt::Display for ShaderError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ShaderError::Io(e) => write!(f, "could not read the shader: {}", e),
+            ShaderError::Creation => write!(f, "could not create the shader program."),
+            ShaderError::Preprocessing {
+                stage,
+                chunk,
+                line,
+                message,
+            } => {
+                write!(f, "{} shader preprocessing failed at line {}", stage, line)?;
+
+                if let Some(chunk) = chunk {
+                    write!(f, " of `{}'", chunk)?;
+                }
+
+                write!(f, ": {}", message)
+            }
+            ShaderError::Compilation {
+                stage,
+                chunk,
+                line,
+                log,
+            } => {
+                write!(f, "{} shader compilation failed", stage)?;
+
+                if let Some(line) = line {
+                    write!(f, " at line {}", line)?;
+                }
+
+                if let Some(chunk) = chunk {
+                    write!(f, " of `{}'", chunk)?;
+                }
+
+                write!(f, ": {}", log)
+            }
+            ShaderError::Linking { log } => write!(f, "shader linking failed: {}", log),
+            ShaderError::UniformNotFound(name) => write!(f, "no active uniform `{}'.", name),
+            ShaderError::AttributeNotFound(name) => write!(f, "no active attribute `{}'.", name),
+            ShaderError::Gl(code) => write!(f, "OpenGL error {:#x}.", code),
+        }
+    }
+}
+
+impl Error for ShaderError {}
+
 /// Structure encapsulating a program.
+///
+/// The shader sources are preprocessed before being compiled, so they can include the chunks
+/// of the `ShaderLibrary`. Cloning an effect does not copy its program.
+#[derive(Clone)]
 pub struct Effect {
+    program: Rc<EffectProgram>,
+}
+
+/// The program of an effect, shared with the hot reloader.
+struct EffectProgram {
     program: Program,
-    vshader: Shader,
-    fshader: Shader,
+    shaders: RefCell<(Shader, Shader)>,
+    /// The attributes queried so far, which keep their location when the program is reloaded.
+    attribs: RefCell<Vec<(String, u32)>>,
 }
 
 impl Effect {
     /// Creates a new shader program from two files containing the vertex and fragment shader.
+    ///
+    /// Returns `None` if the files cannot be read or the shaders are invalid, see `Effect::try_new`
+    /// for the reason of the failure. The files are watched if hot reloading is enabled, see
+    /// `HotReloader`.
     pub fn new(vshader_path: &Path, fshader_path: &Path) -> Option<Effect> {
-        let mut vshader = String::new();
-        let mut fshader = String::new();
-
-        if File::open(vshader_path)
-            .map(|mut v| v.read_to_string(&mut vshader))
-            .is_err()
-        {
-            return None;
-        }
+        Effect::try_new(vshader_path, fshader_path).ok()
+    }
 
-        if File::open(fshader_path)
-            .map(|mut f| f.read_to_string(&mut fshader))
-            .is_err()
+    /// Creates a new shader program from two files containing the vertex and fragment shader.
+    ///
+    /// Returns the read error, or the compilation or link log if the shaders are invalid. The
+    /// files are watched if hot reloading is enabled, see `HotReloader`.
+    pub fn try_new(vshader_path: &Path, fshader_path: &Path) -> Result<Effect, ShaderError> {
+        let (vshader, fshader) =
+            read_shader_files(vshader_path, fshader_path).map_err(ShaderError::Io)?;
+        let effect = Effect::try_new_from_str(&vshader[..], &fshader[..])?;
+
+        #[cfg(not(target_arch = "wasm32"))]
         {
-            return None;
+            let program = Rc::downgrade(&effect.program);
+            let paths = vec![vshader_path.to_path_buf(), fshader_path.to_path_buf()];
+            let (vpath, fpath) = (paths[0].clone(), paths[1].clone());
+
+            HotReloader::get_global_reloader(|reloader| {
+                reloader.watch(paths, move || {
+                    let program = program.upgrade()?;
+                    Some(
+                        read_shader_files(&vpath, &fpath)
+                            .map_err(ReloadError::from)
+                            .and_then(|(vshader, fshader)| {
+                                program
+                                    .reload(&vshader, &fshader)
+                                    .map_err(ReloadError::from)
+                            }),
+                    )
+                })
+            });
         }
 
-        Some(Effect::new_from_str(&vshader[..], &fshader[..]))
+        Ok(effect)
     }
 
     /// Creates a new shader program from strings of the vertex and fragment shader.
+    ///
+    /// Panics with the compilation or link log if the shaders are invalid, use
+    /// `Effect::try_new_from_str` to handle this error instead.
     pub fn new_from_str(vshader: &str, fshader: &str) -> Effect {
-        let (program, vshader, fshader) = load_shader_program(vshader, fshader);
+        Effect::try_new_from_str(vshader, fshader).unwrap_or_else(|e| panic!("{}", e))
+    }
 
-        Effect {
-            program,
-            vshader,
-            fshader,
+    /// Creates a new shader program from strings of the vertex and fragment shader.
+    ///
+    /// Returns the compilation or link log if the shaders are invalid.
+    pub fn try_new_from_str(vshader: &str, fshader: &str) -> Result<Effect, ShaderError> {
+        Effect::try_new_with_defines(vshader, fshader, &[])
+    }
+
+    /// Creates a new shader program from strings of the vertex and fragment shader, preceded by
+    /// the given `#define`s.
+    ///
+    /// Each define is a name and its value, which may be empty.
+    pub fn try_new_with_defines(
+        vshader: &str,
+        fshader: &str,
+        defines: &[(&str, &str)],
+    ) -> Result<Effect, ShaderError> {
+        let (vshader, fshader) = compile_shaders(vshader, fshader, defines)?;
+        let (program, vshader, fshader) = link_shader_program(vshader, fshader)?;
+
+        Ok(Effect {
+            program: Rc::new(EffectProgram {
+                program,
+                shaders: RefCell::new((vshader, fshader)),
+                attribs: RefCell::new(Vec::new()),
+            }),
+        })
+    }
+
+    /// Gets the variant of a shader program for the given `#define`s, creating it if needed.
+    ///
+    /// The variants are cached by the `ShaderLibrary`, so requesting the same sources and
+    /// defines again returns the same program without compiling it.
+    pub fn try_new_variant(
+        vshader: &str,
+        fshader: &str,
+        defines: &[(&str, &str)],
+    ) -> Result<Effect, ShaderError> {
+        let mut sorted: Vec<_> = defines
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        sorted.sort();
+        let key = (vshader.to_string(), fshader.to_string(), sorted);
+
+        if let Some(effect) = ShaderLibrary::get_global_library(|library| library.get_variant(&key))
+        {
+            return Ok(effect);
         }
+
+        let effect = Effect::try_new_with_defines(vshader, fshader, defines)?;
+        ShaderLibrary::get_global_library(|library| library.add_variant(key, effect.clone()));
+
+        Ok(effect)
     }
 
     /// Gets a uniform variable from the shader program.
     pub fn get_uniform<T: GLPrimitive>(&self, name: &str) -> Option<ShaderUniform<T>> {
+        self.try_get_uniform(name).ok()
+    }
+
+    /// Gets a uniform variable from the shader program, or the reason why it is not available.
+    pub fn try_get_uniform<T: GLPrimitive>(
+        &self,
+        name: &str,
+    ) -> Result<ShaderUniform<T>, ShaderError> {
         let ctxt = Context::get();
-        let location = ctxt.get_uniform_location(&self.program, name);
+        let location = ctxt.get_uniform_location(&self.program.program, name);
 
-        if ctxt.get_error() == 0 {
-            if let Some(id) = location {
+        match ctxt.get_error() {
+            0 => {
+                let id = location.ok_or_else(|| ShaderError::UniformNotFound(name.to_string()))?;
                 let data_type = PhantomData;
-                return Some(ShaderUniform { id, data_type });
+                Ok(ShaderUniform { id, data_type })
             }
+            code => Err(ShaderError::Gl(code)),
         }
-
-        return None;
     }
 
     /// Gets an attribute from the shader program.
     pub fn get_attrib<T: GLPrimitive>(&self, name: &str) -> Option<ShaderAttribute<T>> {
+        self.try_get_attrib(name).ok()
+    }
+
+    /// Gets an attribute from the shader program, or the reason why it is not available.
+    pub fn try_get_attrib<T: GLPrimitive>(
+        &self,
+        name: &str,
+    ) -> Result<ShaderAttribute<T>, ShaderError> {
         let ctxt = Context::get();
-        let location = ctxt.get_attrib_location(&self.program, name);
+        let location = ctxt.get_attrib_location(&self.program.program, name);
 
-        if ctxt.get_error() == 0 && location != -1 {
-            let id = location as u32;
-            let data_type = PhantomData;
-            return Some(ShaderAttribute { id, data_type });
-        }
+        match ctxt.get_error() {
+            0 if location == -1 => Err(ShaderError::AttributeNotFound(name.to_string())),
+            0 => {
+                let id = location as u32;
+                let data_type = PhantomData;
+                let mut attribs = self.program.attribs.borrow_mut();
+
+                if attribs.iter().all(|(n, _)| n != name) {
+                    attribs.push((name.to_string(), id));
+                }
 
-        return None;
+                Ok(ShaderAttribute { id, data_type })
+            }
+            code => Err(ShaderError::Gl(code)),
+        }
     }
 
     /// Make this program active.
     pub fn use_program(&mut self) {
-        verify!(Context::get().use_program(Some(&self.program)));
+        verify!(Context::get().use_program(Some(&self.program.program)));
     }
 }
 
-impl Drop for Effect {
+impl EffectProgram {
+    /// Replaces the shaders of this program, keeping the previous ones if the new ones are invalid.
+    ///
+    /// The program object itself is kept, and so are the locations of the attributes queried so
+    /// far. The uniform locations are usually kept as long as the declared uniforms do not change.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn reload(&self, vshader: &str, fshader: &str) -> Result<(), ShaderError> {
+        let ctxt = Context::get();
+        let (vshader, fshader) = compile_shaders(vshader, fshader, &[])?;
+        let mut shaders = self.shaders.borrow_mut();
+        let link = |vshader: &Shader, fshader: &Shader, old: &(Shader, Shader)| {
+            verify!(ctxt.detach_shader(&self.program, &old.0));
+            verify!(ctxt.detach_shader(&self.program, &old.1));
+            verify!(ctxt.attach_shader(&self.program, vshader));
+            verify!(ctxt.attach_shader(&self.program, fshader));
+
+            for (name, id) in self.attribs.borrow().iter() {
+                verify!(ctxt.bind_attrib_location(&self.program, *id, name));
+            }
+
+            verify!(ctxt.link_program(&self.program));
+            ctxt.get_program_link_status(&self.program)
+        };
+
+        if link(&vshader, &fshader, &*shaders) {
+            let old = mem::replace(&mut *shaders, (vshader, fshader));
+            verify!(ctxt.delete_shader(Some(&old.0)));
+            verify!(ctxt.delete_shader(Some(&old.1)));
+            Ok(())
+        } else {
+            let log = ctxt.get_program_info_log(&self.program).unwrap_or_default();
+            let new = (vshader, fshader);
+
+            // Restore the previous program.
+            let _ = link(&shaders.0, &shaders.1, &new);
+            verify!(ctxt.delete_shader(Some(&new.0)));
+            verify!(ctxt.delete_shader(Some(&new.1)));
+            Err(ShaderError::Linking { log })
+        }
+    }
+}
+
+impl Drop for EffectProgram {
     fn drop(&mut self) {
         let ctxt = Context::get();
+        let shaders = self.shaders.borrow();
+
         if verify!(ctxt.is_program(Some(&self.program))) {
             verify!(ctxt.delete_program(Some(&self.program)));
         }
-        if verify!(ctxt.is_shader(Some(&self.fshader))) {
-            verify!(ctxt.delete_shader(Some(&self.fshader)));
+        if verify!(ctxt.is_shader(Some(&shaders.1))) {
+            verify!(ctxt.delete_shader(Some(&shaders.1)));
         }
-        if verify!(ctxt.is_shader(Some(&self.vshader))) {
-            verify!(ctxt.delete_shader(Some(&self.vshader)));
+        if verify!(ctxt.is_shader(Some(&shaders.0))) {
+            verify!(ctxt.delete_shader(Some(&shaders.0)));
         }
     }
 }
@@ -173,48 +438,124 @@ impl<T: GLPrimitive> ShaderAttribute<T> {
     }
 }
 
-/// Loads a shader program using the given source codes for the vertex and fragment shader.
+/// Preprocesses and compiles the vertex and fragment shaders of a program.
+fn compile_shaders(
+    vertex_shader: &str,
+    fragment_shader: &str,
+    defines: &[(&str, &str)],
+) -> Result<(Shader, Shader), ShaderError> {
+    let ctxt = Context::get();
+    let (vsource, fsource) = ShaderLibrary::get_global_library(|library| {
+        let vsource = Preprocessed::new(library, vertex_shader, ShaderStage::Vertex, defines)?;
+        let fsource = Preprocessed::new(library, fragment_shader, ShaderStage::Fragment, defines)?;
+        Ok((vsource, fsource))
+    })?;
+
+    let vshader =
+        compile_shader(ShaderStage::Vertex, &vsource.source).map_err(|e| vsource.map_error(e))?;
+    let fshader = match compile_shader(ShaderStage::Fragment, &fsource.source) {
+        Ok(fshader) => fshader,
+        Err(e) => {
+            verify!(ctxt.delete_shader(Some(&vshader)));
+            return Err(fsource.map_error(e));
+        }
+    };
+
+    Ok((vshader, fshader))
+}
+
+/// Links a vertex and a fragment shader into a shader program.
 ///
-/// Fails after displaying opengl compilation errors if the shaders are invalid.
-fn load_shader_program(vertex_shader: &str, fragment_shader: &str) -> (Program, Shader, Shader) {
-    // Create and compile the vertex shader
+/// Fails with the link log, after deleting the shaders, if they are incompatible.
+fn link_shader_program(
+    vshader: Shader,
+    fshader: Shader,
+) -> Result<(Program, Shader, Shader), ShaderError> {
     let ctxt = Context::get();
-    let vshader = verify!(ctxt
-        .create_shader(Context::VERTEX_SHADER)
-        .expect("Could not create vertex shader."));
-
-    verify!(ctxt.shader_source(&vshader, vertex_shader));
-    verify!(ctxt.compile_shader(&vshader));
-    check_shader_error(&vshader);
-
-    // Create and compile the fragment shader
-    let fshader = verify!(ctxt
-        .create_shader(Context::FRAGMENT_SHADER)
-        .expect("Could not create fragment shader."));
-    verify!(ctxt.shader_source(&fshader, fragment_shader));
-    verify!(ctxt.compile_shader(&fshader));
-    check_shader_error(&fshader);
-
-    // Link the vertex and fragment shader into a shader program
-    let program = verify!(ctxt.create_program().expect("Could not create program."));
+    let program = match ctxt.create_program() {
+        Some(program) => program,
+        None => {
+            verify!(ctxt.delete_shader(Some(&vshader)));
+            verify!(ctxt.delete_shader(Some(&fshader)));
+            return Err(ShaderError::Creation);
+        }
+    };
+
     verify!(ctxt.attach_shader(&program, &vshader));
     verify!(ctxt.attach_shader(&program, &fshader));
     verify!(ctxt.link_program(&program));
-    (program, vshader, fshader)
+
+    if !ctxt.get_program_link_status(&program) {
+        let log = ctxt.get_program_info_log(&program).unwrap_or_default();
+        verify!(ctxt.delete_program(Some(&program)));
+        verify!(ctxt.delete_shader(Some(&vshader)));
+        verify!(ctxt.delete_shader(Some(&fshader)));
+        return Err(ShaderError::Linking { log });
+    }
+
+    Ok((program, vshader, fshader))
 }
 
-/// Checks if a shader handle is valid.
-///
-/// If it is not valid, it fails with a descriptive error message.
-fn check_shader_error(shader: &Shader) {
-    let ctxt = Context::get();
-    let compiles = ctxt.get_shader_parameter_int(shader, Context::COMPILE_STATUS);
+/// Reads the sources of a vertex and a fragment shader.
+fn read_shader_files(vshader_path: &Path, fshader_path: &Path) -> io::Result<(String, String)> {
+    let mut vshader = String::new();
+    let mut fshader = String::new();
 
-    if compiles == Some(0) {
-        if let Some(log) = ctxt.get_shader_info_log(shader) {
-            panic!("Shader compilation failed: {}", log);
-        } else {
-            println!("Shader compilation failed.");
-        }
+    let _ = File::open(vshader_path)?.read_to_string(&mut vshader)?;
+    let _ = File::open(fshader_path)?.read_to_string(&mut fshader)?;
+
+    Ok((vshader, fshader))
+}
+
+/// Compiles a shader, returning its compilation log if it is invalid.
+fn compile_shader(stage: ShaderStage, source: &str) -> Result<Shader, ShaderError> {
+    let ctxt = Context::get();
+    let shader_type = match stage {
+        ShaderStage::Vertex => Context::VERTEX_SHADER,
+        ShaderStage::Fragment => Context::FRAGMENT_SHADER,
+    };
+    let shader = ctxt
+        .create_shader(shader_type)
+        .ok_or(ShaderError::Creation)?;
+
+    verify!(ctxt.shader_source(&shader, source));
+    verify!(ctxt.compile_shader(&shader));
+
+    if ctxt.get_shader_parameter_int(&shader, Context::COMPILE_STATUS) == Some(0) {
+        let log = ctxt.get_shader_info_log(&shader).unwrap_or_default();
+        verify!(ctxt.delete_shader(Some(&shader)));
+
+        Err(ShaderError::Compilation {
+            stage,
+            chunk: None,
+            line: error_line(&log),
+            log,
+        })
+    } else {
+        Ok(shader)
     }
 }
+
+/// Finds the line of the first error of a compilation log.
+///
+/// Drivers prefix their messages with the source string and line numbers, as `0:12(5):` (Mesa),
+/// `0(12) :` (NVIDIA), or `ERROR: 0:12:` (AMD, Apple, ANGLE).
+fn error_line(log: &str) -> Option<u32> {
+    let line_of = |message: &str| -> Option<u32> {
+        let start = message.find(|c: char| c.is_ascii_digit())?;
+        let rest = &message[start..];
+        let rest = rest.trim_start_matches(|c: char| c.is_ascii_digit());
+        let rest = rest.strip_prefix(':').or_else(|| rest.strip_prefix('('))?;
+        let end = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or_else(|| rest.len());
+        rest[..end].parse().ok()
+    };
+
+    // Warnings may be reported before the errors.
+    log.lines()
+        .filter(|l| l.to_lowercase().contains("error"))
+        .chain(log.lines())
+        .filter_map(line_of)
+        .next()
+}
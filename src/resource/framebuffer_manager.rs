@@ -2,6 +2,7 @@
 
 use crate::context::{Context, Framebuffer, Renderbuffer, Texture};
 use either::Either;
+use std::rc::Rc;
 
 #[path = "../error.rs"]
 mod error;
@@ -16,8 +17,9 @@ pub enum RenderTarget {
 
 /// OpenGL identifiers to an off-screen buffer.
 pub struct OffscreenBuffers {
-    texture: Texture,
+    texture: Rc<Texture>,
     depth: Either<Texture, Renderbuffer>,
+    size: (usize, usize),
 }
 
 impl RenderTarget {
@@ -27,7 +29,28 @@ impl RenderTarget {
     pub fn texture_id(&self) -> Option<&Texture> {
         match *self {
             RenderTarget::Screen => None,
-            RenderTarget::Offscreen(ref o) => Some(&o.texture),
+            RenderTarget::Offscreen(ref o) => Some(&*o.texture),
+        }
+    }
+
+    /// Returns the off-screen color texture, which can be applied to scene nodes.
+    ///
+    /// The texture keeps the last image rendered to this target, e.g., by
+    /// `Window::render_to_texture`. Returns `None` if the target is the screen.
+    pub fn texture(&self) -> Option<Rc<Texture>> {
+        match *self {
+            RenderTarget::Screen => None,
+            RenderTarget::Offscreen(ref o) => Some(o.texture.clone()),
+        }
+    }
+
+    /// Returns the size of the off-screen buffer, in pixels.
+    ///
+    /// Returns `None` if the target is the screen.
+    pub fn size(&self) -> Option<(usize, usize)> {
+        match *self {
+            RenderTarget::Screen => None,
+            RenderTarget::Offscreen(ref o) => Some(o.size),
         }
     }
 
@@ -50,9 +73,11 @@ impl RenderTarget {
             RenderTarget::Screen => {
                 verify!(ctxt.viewport(0, 0, w as i32, h as i32));
             }
-            RenderTarget::Offscreen(ref o) => {
+            RenderTarget::Offscreen(ref mut o) => {
+                o.size = (w as usize, h as usize);
+
                 // Update the fbo
-                verify!(ctxt.bind_texture(Context::TEXTURE_2D, Some(&o.texture)));
+                verify!(ctxt.bind_texture(Context::TEXTURE_2D, Some(&*o.texture)));
                 verify!(ctxt.tex_image2d(
                     Context::TEXTURE_2D,
                     0,
@@ -206,8 +231,9 @@ impl FramebufferManager {
             verify!(ctxt.bind_texture(Context::TEXTURE_2D, None));
 
             RenderTarget::Offscreen(OffscreenBuffers {
-                texture: fbo_texture,
+                texture: Rc::new(fbo_texture),
                 depth: Either::Left(fbo_depth),
+                size: (width, height),
             })
         } else {
             // Create a renderbuffer instead of the texture for the depth.
@@ -222,8 +248,9 @@ impl FramebufferManager {
             verify!(ctxt.bind_renderbuffer(None));
 
             RenderTarget::Offscreen(OffscreenBuffers {
-                texture: fbo_texture,
+                texture: Rc::new(fbo_texture),
                 depth: Either::Right(renderbuffer),
+                size: (width, height),
             })
         }
     }
@@ -249,7 +276,7 @@ impl FramebufferManager {
                     Context::FRAMEBUFFER,
                     Context::COLOR_ATTACHMENT0,
                     Context::TEXTURE_2D,
-                    Some(&o.texture),
+                    Some(&*o.texture),
                     0
                 ));
 
@@ -297,10 +324,8 @@ impl Drop for FramebufferManager {
 
 impl Drop for OffscreenBuffers {
     fn drop(&mut self) {
+        // The color texture is deleted once it is no longer used by scene nodes either.
         let ctxt = Context::get();
-        if verify!(ctxt.is_texture(Some(&self.texture))) {
-            verify!(ctxt.delete_texture(Some(&self.texture)));
-        }
 
         match &self.depth {
             Either::Left(texture) => {
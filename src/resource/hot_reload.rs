@@ -0,0 +1,198 @@
+//! Reloading of resources whose files changed on disk.
+
+use std::cell::RefCell;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::resource::ShaderError;
+
+thread_local!(static KEY_HOT_RELOADER: RefCell<HotReloader> = RefCell::new(HotReloader::new()));
+
+/// Reloads a resource, returning `None` if the resource has been dropped.
+type Reload = Box<dyn FnMut() -> Option<Result<(), ReloadError>>>;
+
+/// The reason a resource failed to reload.
+#[derive(Debug)]
+pub enum ReloadError {
+    /// A file could not be read or decoded.
+    Io(io::Error),
+    /// A shader did not compile or link.
+    Shader(ShaderError),
+}
+
+impl fmt::Display for ReloadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReloadError::Io(e) => e.fmt(f),
+            ReloadError::Shader(e) => e.fmt(f),
+        }
+    }
+}
+
+impl Error for ReloadError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ReloadError::Io(e) => Some(e),
+            ReloadError::Shader(e) => Some(e),
+        }
+    }
+}
+
+impl From<io::Error> for ReloadError {
+    fn from(e: io::Error) -> ReloadError {
+        ReloadError::Io(e)
+    }
+}
+
+impl From<ShaderError> for ReloadError {
+    fn from(e: ShaderError) -> ReloadError {
+        ReloadError::Shader(e)
+    }
+}
+
+struct Watch {
+    paths: Vec<PathBuf>,
+    modified: Vec<Option<SystemTime>>,
+    reload: Reload,
+}
+
+/// The hot reloader, which watches the files of resources and reloads them when they change.
+///
+/// Hot reloading is disabled by default. Once enabled, the textures loaded by
+/// `TextureManager::add`, the meshes loaded by `MeshManager::load_obj`, and the shaders loaded
+/// by `Effect::new` are watched. Their files are polled by `Window::render`, and a changed
+/// resource is reloaded in place, so that every handle to it sees the new data. A resource that
+/// fails to reload, e.g., because its shader does not compile, keeps its previous content and
+/// the error is returned by `HotReloader::take_errors`.
+pub struct HotReloader {
+    enabled: bool,
+    poll_interval: Duration,
+    last_poll: Instant,
+    watches: Vec<Watch>,
+    errors: Vec<(PathBuf, ReloadError)>,
+}
+
+impl HotReloader {
+    /// Creates a new, disabled, hot reloader.
+    pub fn new() -> HotReloader {
+        HotReloader {
+            enabled: false,
+            poll_interval: Duration::from_millis(500),
+            last_poll: Instant::now(),
+            watches: Vec::new(),
+            errors: Vec::new(),
+        }
+    }
+
+    /// Mutably applies a function to the global hot reloader.
+    pub fn get_global_reloader<T, F: FnOnce(&mut HotReloader) -> T>(f: F) -> T {
+        KEY_HOT_RELOADER.with(|reloader| f(&mut *reloader.borrow_mut()))
+    }
+
+    /// Enables or disables hot reloading.
+    ///
+    /// Only the resources loaded while hot reloading is enabled are watched. Disabling it stops
+    /// watching every resource.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+
+        if !enabled {
+            self.watches.clear();
+        }
+    }
+
+    /// Whether hot reloading is enabled.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Sets the minimum duration between two checks of the watched files.
+    ///
+    /// Defaults to 500 milliseconds.
+    pub fn set_poll_interval(&mut self, interval: Duration) {
+        self.poll_interval = interval
+    }
+
+    /// The minimum duration between two checks of the watched files.
+    pub fn poll_interval(&self) -> Duration {
+        self.poll_interval
+    }
+
+    /// Watches the files of a resource, calling `reload` whenever one of them changes.
+    ///
+    /// `reload` must return `None` once the resource has been dropped, so that it stops being
+    /// watched. Nothing is done if hot reloading is disabled.
+    pub(crate) fn watch<F>(&mut self, paths: Vec<PathBuf>, reload: F)
+    where
+        F: FnMut() -> Option<Result<(), ReloadError>> + 'static,
+    {
+        if self.enabled {
+            let modified = paths.iter().map(|p| modification_time(p)).collect();
+
+            self.watches.push(Watch {
+                paths,
+                modified,
+                reload: Box::new(reload),
+            })
+        }
+    }
+
+    /// Reloads the resources whose files changed since the last poll.
+    ///
+    /// This is called automatically by `Window::render`.
+    pub fn poll(&mut self) {
+        if !self.enabled || self.last_poll.elapsed() < self.poll_interval {
+            return;
+        }
+
+        self.last_poll = Instant::now();
+
+        let mut i = 0;
+
+        while i < self.watches.len() {
+            let watch = &mut self.watches[i];
+            let mut changed = false;
+
+            for (path, modified) in watch.paths.iter().zip(watch.modified.iter_mut()) {
+                let time = modification_time(path);
+
+                // A file being rewritten may be missing for a moment, so it is ignored until it
+                // is back.
+                if time.is_some() && time != *modified {
+                    *modified = time;
+                    changed = true;
+                }
+            }
+
+            if changed {
+                match (watch.reload)() {
+                    None => {
+                        let _ = self.watches.swap_remove(i);
+                        continue;
+                    }
+                    Some(Ok(())) => {}
+                    Some(Err(e)) => self.errors.push((watch.paths[0].clone(), e)),
+                }
+            }
+
+            i += 1;
+        }
+    }
+
+    /// Returns and clears the errors of the resources which failed to reload since the last call.
+    ///
+    /// Each error is given with the first file of its resource, e.g., the vertex shader of an
+    /// effect.
+    pub fn take_errors(&mut self) -> Vec<(PathBuf, ReloadError)> {
+        mem::replace(&mut self.errors, Vec::new())
+    }
+}
+
+fn modification_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
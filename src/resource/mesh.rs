@@ -1,7 +1,9 @@
 //! Data structure of a scene node geometry.
+use std::collections::HashSet;
 use std::iter;
 use std::sync::{Arc, RwLock};
 
+use crate::renderer::line_effect;
 use crate::resource::gpu_vector::{AllocationType, BufferType, GPUVec};
 use crate::resource::ShaderAttribute;
 use na::{self, Point2, Point3, Vector3};
@@ -11,7 +13,7 @@ use num::Zero;
 #[path = "../error.rs"]
 mod error;
 
-/// Aggregation of vertices, indices, normals and texture coordinates.
+/// Aggregation of vertices, indices, normals, texture coordinates, and optional vertex colors.
 ///
 /// It also contains the GPU location of those buffers.
 pub struct Mesh {
@@ -19,7 +21,9 @@ pub struct Mesh {
     faces: Arc<RwLock<GPUVec<Point3<u16>>>>,
     normals: Arc<RwLock<GPUVec<Vector3<f32>>>>,
     uvs: Arc<RwLock<GPUVec<Point2<f32>>>>,
+    colors: Option<Arc<RwLock<GPUVec<Point3<f32>>>>>,
     edges: Option<Arc<RwLock<GPUVec<Point2<u16>>>>>,
+    wireframe: Option<GPUVec<f32>>,
 }
 
 impl Mesh {
@@ -186,13 +190,21 @@ impl Mesh {
             faces: faces,
             normals: normals,
             uvs: uvs,
+            colors: None,
             edges: None,
+            wireframe: None,
         }
     }
 
     /// Binds this mesh vertex coordinates buffer to a vertex attribute.
     pub fn bind_coords(&mut self, coords: &mut ShaderAttribute<Point3<f32>>) {
-        coords.bind(&mut *self.coords.write().unwrap());
+        let mut gpu_coords = self.coords.write().unwrap();
+
+        if gpu_coords.trash() {
+            self.wireframe = None;
+        }
+
+        coords.bind(&mut *gpu_coords);
     }
 
     /// Binds this mesh vertex normals buffer to a vertex attribute.
@@ -205,9 +217,28 @@ impl Mesh {
         uvs.bind(&mut *self.uvs.write().unwrap());
     }
 
+    /// Binds this mesh vertex colors buffer to a vertex attribute.
+    ///
+    /// Returns `false`, without binding anything, if this mesh has no vertex colors.
+    pub fn bind_colors(&mut self, colors: &mut ShaderAttribute<Point3<f32>>) -> bool {
+        match self.colors {
+            Some(ref gpu_colors) => {
+                colors.bind(&mut *gpu_colors.write().unwrap());
+                true
+            }
+            None => false,
+        }
+    }
+
     /// Binds this mesh index buffer to a vertex attribute.
     pub fn bind_faces(&mut self) {
-        self.faces.write().unwrap().bind();
+        let mut gpu_faces = self.faces.write().unwrap();
+
+        if gpu_faces.trash() {
+            self.wireframe = None;
+        }
+
+        gpu_faces.bind();
     }
 
     /// Binds this mesh buffers to vertex attributes.
@@ -223,11 +254,18 @@ impl Mesh {
         self.bind_faces();
     }
 
-    /// Binds this mesh buffers to vertex attributes.
-    pub fn bind_edges(&mut self) {
+    /// Binds the edges of this mesh faces, as an element array of lines.
+    ///
+    /// Returns `false` if the edges cannot be built because the faces were unloaded from RAM.
+    pub fn bind_edges(&mut self) -> bool {
         if self.edges.is_none() {
+            let faces = self.faces.read().unwrap();
+            let faces = match faces.data() {
+                Some(faces) => faces,
+                None => return false,
+            };
             let mut edges = Vec::new();
-            for face in self.faces.read().unwrap().data().as_ref().unwrap() {
+            for face in faces {
                 edges.push(Point2::new(face.x, face.y));
                 edges.push(Point2::new(face.y, face.z));
                 edges.push(Point2::new(face.z, face.x));
@@ -238,6 +276,44 @@ impl Mesh {
         }
 
         self.edges.as_mut().unwrap().write().unwrap().bind();
+        true
+    }
+
+    /// The screen-space line quads used to draw the wireframe of this mesh.
+    ///
+    /// Each edge is stored once, with a unit width, and the buffer is rebuilt whenever the
+    /// vertex coordinates or faces are modified. Returns `None` if the wireframe has to be built
+    /// but the vertex coordinates or faces were unloaded from RAM.
+    pub(crate) fn wireframe(&mut self) -> Option<&mut GPUVec<f32>> {
+        if self.wireframe.is_none() {
+            let coords = self.coords.read().unwrap();
+            let faces = self.faces.read().unwrap();
+            let (coords, faces) = match (coords.data(), faces.data()) {
+                (Some(coords), Some(faces)) => (coords, faces),
+                _ => return None,
+            };
+            let mut visited = HashSet::new();
+            let mut lines = Vec::new();
+            let color = [Point3::new(1.0, 1.0, 1.0)];
+
+            for face in faces {
+                for &(a, b) in &[(face.x, face.y), (face.y, face.z), (face.z, face.x)] {
+                    // Degenerate faces, used to store segments, have zero-length edges.
+                    if a != b && visited.insert((a.min(b), a.max(b))) {
+                        let pts = [coords[a as usize], coords[b as usize]];
+                        line_effect::push_polyline(&mut lines, &pts, &color, &[1.0], false);
+                    }
+                }
+            }
+
+            self.wireframe = Some(GPUVec::new(
+                lines,
+                BufferType::Array,
+                AllocationType::StaticDraw,
+            ));
+        }
+
+        self.wireframe.as_mut()
     }
 
     /// Unbind this mesh buffers to vertex attributes.
@@ -246,6 +322,10 @@ impl Mesh {
         self.normals.write().unwrap().unbind();
         self.uvs.write().unwrap().unbind();
         self.faces.write().unwrap().unbind();
+
+        for colors in self.colors.iter() {
+            colors.write().unwrap().unbind();
+        }
     }
 
     /// Number of points needed to draw this mesh.
@@ -282,6 +362,25 @@ impl Mesh {
         &self.uvs
     }
 
+    /// This mesh vertex colors, if any.
+    pub fn colors(&self) -> Option<&Arc<RwLock<GPUVec<Point3<f32>>>>> {
+        self.colors.as_ref()
+    }
+
+    /// Sets the color of each vertex of this mesh.
+    ///
+    /// The vertex colors are multiplied by the color of the object displaying this mesh. Setting
+    /// `None` removes the vertex colors.
+    pub fn set_colors(&mut self, colors: Option<Vec<Point3<f32>>>) {
+        self.colors = colors.map(|colors| {
+            Arc::new(RwLock::new(GPUVec::new(
+                colors,
+                BufferType::Array,
+                AllocationType::StaticDraw,
+            )))
+        });
+    }
+
     /// Computes normals from a set of faces.
     pub fn compute_normals_array(
         coordinates: &[Point3<f32>],
@@ -1,7 +1,9 @@
 //! A resource manager to load meshes.
 
 use crate::loader::mtl::MtlMaterial;
-use crate::loader::obj;
+use crate::loader::obj::{self, ElementKind, ParseWarning};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::resource::HotReloader;
 use crate::resource::Mesh;
 use ncollide3d::procedural;
 use ncollide3d::procedural::TriMesh;
@@ -74,22 +76,80 @@ impl MeshManager {
     }
 
     // FIXME: is this the right place to put this?
-    /// Loads the meshes described by an obj file.
+    /// Loads the meshes described by an obj file, and the warnings emitted while parsing it.
+    ///
+    /// The file is watched if hot reloading is enabled, see `HotReloader`.
     pub fn load_obj(
         path: &Path,
         mtl_dir: &Path,
         geometry_name: &str,
-    ) -> IoResult<Vec<(String, Rc<RefCell<Mesh>>, Option<MtlMaterial>)>> {
-        obj::parse_file(path, mtl_dir, geometry_name).map(|ms| {
+    ) -> IoResult<(
+        Vec<(String, Rc<RefCell<Mesh>>, Option<MtlMaterial>, ElementKind)>,
+        Vec<ParseWarning>,
+    )> {
+        obj::parse_file(path, mtl_dir, geometry_name).map(|(ms, warnings)| {
             let mut res = Vec::new();
 
-            for (n, m, mat) in ms.into_iter() {
+            for (n, m, mat, kind) in ms.into_iter() {
                 let m = Rc::new(RefCell::new(m));
 
-                res.push((n, m, mat));
+                res.push((n, m, mat, kind));
             }
 
-            res
+            #[cfg(not(target_arch = "wasm32"))]
+            MeshManager::watch_obj(path, mtl_dir, geometry_name, &res);
+
+            (res, warnings)
+        })
+    }
+
+    /// Reloads the meshes of an obj file in place when it changes, if hot reloading is enabled.
+    ///
+    /// The reloaded meshes replace the previous meshes with the same group name and element kind.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn watch_obj(
+        path: &Path,
+        mtl_dir: &Path,
+        geometry_name: &str,
+        meshes: &[(String, Rc<RefCell<Mesh>>, Option<MtlMaterial>, ElementKind)],
+    ) {
+        let meshes: Vec<_> = meshes
+            .iter()
+            .map(|(n, m, _, kind)| (n.clone(), Rc::downgrade(m), *kind))
+            .collect();
+        let file = path.to_path_buf();
+        let mtl_dir = mtl_dir.to_path_buf();
+        let geometry_name = geometry_name.to_string();
+
+        HotReloader::get_global_reloader(|reloader| {
+            reloader.watch(vec![path.to_path_buf()], move || {
+                if meshes.iter().all(|(_, m, _)| m.upgrade().is_none()) {
+                    return None;
+                }
+
+                let (reloaded, _) = match obj::parse_file(&file, &mtl_dir, &geometry_name) {
+                    Ok(res) => res,
+                    Err(e) => return Some(Err(e.into())),
+                };
+                let mut used = vec![false; meshes.len()];
+
+                for (name, mesh, _, kind) in reloaded {
+                    let old = meshes
+                        .iter()
+                        .zip(used.iter_mut())
+                        .find(|((n, _, k), used)| !**used && *n == name && *k == kind);
+
+                    if let Some(((_, old, _), used)) = old {
+                        *used = true;
+
+                        if let Some(old) = old.upgrade() {
+                            *old.borrow_mut() = mesh;
+                        }
+                    }
+                }
+
+                Some(Ok(()))
+            })
         })
     }
 }
@@ -1,12 +1,19 @@
 //! GPU resource managers
 
 pub use crate::context::Texture;
-pub use crate::resource::effect::{Effect, ShaderAttribute, ShaderUniform};
+#[cfg(not(target_arch = "wasm32"))]
+pub use crate::resource::async_loader::{AsyncLoader, Loading};
+pub use crate::resource::dynamic_texture::{DynamicTexture, TextureFormat};
+pub use crate::resource::effect::{
+    Effect, ShaderAttribute, ShaderError, ShaderStage, ShaderUniform,
+};
 pub use crate::resource::framebuffer_manager::{
     FramebufferManager, OffscreenBuffers, RenderTarget,
 };
 pub use crate::resource::gl_primitive::{GLPrimitive, PrimitiveArray};
 pub use crate::resource::gpu_vector::{AllocationType, BufferType, GPUVec};
+#[cfg(not(target_arch = "wasm32"))]
+pub use crate::resource::hot_reload::{HotReloader, ReloadError};
 pub use crate::resource::material::{Material, PlanarMaterial};
 pub use crate::resource::material_manager::MaterialManager;
 pub use crate::resource::mesh::Mesh;
@@ -14,12 +21,18 @@ pub use crate::resource::mesh_manager::MeshManager;
 pub use crate::resource::planar_material_manager::PlanarMaterialManager;
 pub use crate::resource::planar_mesh::PlanarMesh;
 pub use crate::resource::planar_mesh_manager::PlanarMeshManager;
-pub use crate::resource::texture_manager::{TextureManager, TextureWrapping};
+pub use crate::resource::shader_library::ShaderLibrary;
+pub use crate::resource::texture_manager::{TextureFilter, TextureManager, TextureWrapping};
 
+#[cfg(not(target_arch = "wasm32"))]
+mod async_loader;
+mod dynamic_texture;
 mod effect;
 mod framebuffer_manager;
 mod gl_primitive;
 mod gpu_vector;
+#[cfg(not(target_arch = "wasm32"))]
+mod hot_reload;
 pub mod material;
 mod material_manager;
 mod mesh;
@@ -27,4 +40,5 @@ mod mesh_manager;
 mod planar_material_manager;
 mod planar_mesh;
 mod planar_mesh_manager;
+mod shader_library;
 mod texture_manager;
@@ -1,7 +1,9 @@
 //! Data structure of a scene node geometry.
+use std::collections::HashSet;
 use std::iter;
 use std::sync::{Arc, RwLock};
 
+use crate::renderer::line_effect;
 use crate::resource::gpu_vector::{AllocationType, BufferType, GPUVec};
 use crate::resource::ShaderAttribute;
 use na::{Point2, Point3};
@@ -17,6 +19,7 @@ pub struct PlanarMesh {
     faces: Arc<RwLock<GPUVec<Point3<u16>>>>,
     uvs: Arc<RwLock<GPUVec<Point2<f32>>>>,
     edges: Option<Arc<RwLock<GPUVec<Point2<u16>>>>>,
+    wireframe: Option<GPUVec<f32>>,
 }
 
 impl PlanarMesh {
@@ -65,12 +68,19 @@ impl PlanarMesh {
             faces: faces,
             uvs: uvs,
             edges: None,
+            wireframe: None,
         }
     }
 
     /// Binds this mesh vertex coordinates buffer to a vertex attribute.
     pub fn bind_coords(&mut self, coords: &mut ShaderAttribute<Point2<f32>>) {
-        coords.bind(&mut *self.coords.write().unwrap());
+        let mut gpu_coords = self.coords.write().unwrap();
+
+        if gpu_coords.trash() {
+            self.wireframe = None;
+        }
+
+        coords.bind(&mut *gpu_coords);
     }
 
     /// Binds this mesh vertex uvs buffer to a vertex attribute.
@@ -80,7 +90,13 @@ impl PlanarMesh {
 
     /// Binds this mesh vertex uvs buffer to a vertex attribute.
     pub fn bind_faces(&mut self) {
-        self.faces.write().unwrap().bind();
+        let mut gpu_faces = self.faces.write().unwrap();
+
+        if gpu_faces.trash() {
+            self.wireframe = None;
+        }
+
+        gpu_faces.bind();
     }
 
     /// Binds this mesh buffers to vertex attributes.
@@ -94,12 +110,19 @@ impl PlanarMesh {
         self.bind_faces();
     }
 
-    /// Binds this mesh buffers to vertex attributes.
-    pub fn bind_edges(&mut self) {
+    /// Binds the edges of this mesh faces, as an element array of lines.
+    ///
+    /// Returns `false` if the edges cannot be built because the faces were unloaded from RAM.
+    pub fn bind_edges(&mut self) -> bool {
         if self.edges.is_none() {
             // FIXME: remove internal edges.
+            let faces = self.faces.read().unwrap();
+            let faces = match faces.data() {
+                Some(faces) => faces,
+                None => return false,
+            };
             let mut edges = Vec::new();
-            for face in self.faces.read().unwrap().data().as_ref().unwrap() {
+            for face in faces {
                 edges.push(Point2::new(face.x, face.y));
                 edges.push(Point2::new(face.y, face.z));
                 edges.push(Point2::new(face.z, face.x));
@@ -110,6 +133,46 @@ impl PlanarMesh {
         }
 
         self.edges.as_mut().unwrap().write().unwrap().bind();
+        true
+    }
+
+    /// The screen-space line quads used to draw the wireframe of this mesh.
+    ///
+    /// Each edge is stored once, with a unit width, and the buffer is rebuilt whenever the
+    /// vertex coordinates or faces are modified. Returns `None` if the wireframe has to be built
+    /// but the vertex coordinates or faces were unloaded from RAM.
+    pub(crate) fn wireframe(&mut self) -> Option<&mut GPUVec<f32>> {
+        if self.wireframe.is_none() {
+            let coords = self.coords.read().unwrap();
+            let faces = self.faces.read().unwrap();
+            let (coords, faces) = match (coords.data(), faces.data()) {
+                (Some(coords), Some(faces)) => (coords, faces),
+                _ => return None,
+            };
+            let mut visited = HashSet::new();
+            let mut lines = Vec::new();
+            let color = [Point3::new(1.0, 1.0, 1.0)];
+
+            for face in faces {
+                for &(a, b) in &[(face.x, face.y), (face.y, face.z), (face.z, face.x)] {
+                    if visited.insert((a.min(b), a.max(b))) {
+                        let pts = [
+                            Point3::new(coords[a as usize].x, coords[a as usize].y, 0.0),
+                            Point3::new(coords[b as usize].x, coords[b as usize].y, 0.0),
+                        ];
+                        line_effect::push_polyline(&mut lines, &pts, &color, &[1.0], false);
+                    }
+                }
+            }
+
+            self.wireframe = Some(GPUVec::new(
+                lines,
+                BufferType::Array,
+                AllocationType::StaticDraw,
+            ));
+        }
+
+        self.wireframe.as_mut()
     }
 
     /// Unbind this mesh buffers to vertex attributes.
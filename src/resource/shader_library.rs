@@ -0,0 +1,218 @@
+//! GLSL chunks and preprocessing of the shaders of effects.
+
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+
+use crate::resource::{Effect, ShaderError, ShaderStage};
+
+thread_local!(static KEY_SHADER_LIBRARY: RefCell<ShaderLibrary> = RefCell::new(ShaderLibrary::new()));
+
+/// The variants of effects, indexed by their vertex shader, fragment shader and defines.
+type VariantKey = (String, String, Vec<(String, String)>);
+
+/// The library of GLSL chunks included by the shaders of effects.
+///
+/// The sources given to `Effect` are preprocessed: each `#include "name"` line is replaced by
+/// the chunk registered as `name`, and every chunk is included at most once per shader. Upon
+/// construction, the library contains the builtin chunks:
+///
+/// * `kiss3d/lighting.glsl`: the `kiss3d_lighting` function, which computes the same lighting
+///   as the default object material.
+///
+/// The builtin shaders, like `OBJECT_FRAGMENT_SRC`, do not include any chunk, so they can be
+/// compiled without this preprocessor.
+///
+/// It also caches the effect variants created by `Effect::try_new_variant`.
+pub struct ShaderLibrary {
+    chunks: HashMap<String, String>,
+    variants: HashMap<VariantKey, Effect>,
+}
+
+impl ShaderLibrary {
+    /// Creates a new library containing the builtin chunks.
+    pub fn new() -> ShaderLibrary {
+        let mut res = ShaderLibrary {
+            chunks: HashMap::new(),
+            variants: HashMap::new(),
+        };
+
+        res.add_chunk(
+            "kiss3d/lighting.glsl",
+            include_str!("../builtin/lighting.glsl"),
+        );
+
+        res
+    }
+
+    /// Mutably applies a function to the global shader library.
+    pub fn get_global_library<T, F: FnOnce(&mut ShaderLibrary) -> T>(f: F) -> T {
+        KEY_SHADER_LIBRARY.with(|library| f(&mut *library.borrow_mut()))
+    }
+
+    /// Registers a chunk that shaders can include as `name`, replacing any chunk with the same
+    /// name.
+    ///
+    /// The cached variants are not affected.
+    pub fn add_chunk(&mut self, name: &str, source: &str) {
+        let _ = self.chunks.insert(name.to_string(), source.to_string());
+    }
+
+    /// Gets the chunk registered as `name`.
+    pub fn get_chunk(&self, name: &str) -> Option<&str> {
+        self.chunks.get(name).map(|c| &c[..])
+    }
+
+    /// Removes the chunk registered as `name`.
+    pub fn remove_chunk(&mut self, name: &str) {
+        let _ = self.chunks.remove(name);
+    }
+
+    /// Clears the cache of effect variants.
+    ///
+    /// The effects already created are kept alive by their users.
+    pub fn clear_variants(&mut self) {
+        self.variants.clear()
+    }
+
+    /// Preprocesses the source of a shader, resolving its includes and injecting `defines` after
+    /// its `#version` directive.
+    pub fn preprocess(
+        &self,
+        source: &str,
+        stage: ShaderStage,
+        defines: &[(&str, &str)],
+    ) -> Result<String, ShaderError> {
+        Preprocessed::new(self, source, stage, defines).map(|p| p.source)
+    }
+
+    pub(crate) fn get_variant(&self, key: &VariantKey) -> Option<Effect> {
+        self.variants.get(key).cloned()
+    }
+
+    pub(crate) fn add_variant(&mut self, key: VariantKey, effect: Effect) {
+        let _ = self.variants.insert(key, effect);
+    }
+}
+
+/// A preprocessed shader, with the origin of each of its lines.
+pub(crate) struct Preprocessed {
+    pub source: String,
+    /// The chunk, or `None` for the main source, and the line each line comes from.
+    origins: Vec<(Option<String>, u32)>,
+}
+
+impl Preprocessed {
+    pub fn new(
+        library: &ShaderLibrary,
+        source: &str,
+        stage: ShaderStage,
+        defines: &[(&str, &str)],
+    ) -> Result<Preprocessed, ShaderError> {
+        let mut res = Preprocessed {
+            source: String::new(),
+            origins: Vec::new(),
+        };
+        let mut included = HashSet::new();
+        let mut lines = source.lines().enumerate().peekable();
+
+        // The `#version` directive must stay first, so the defines are injected after it.
+        if let Some((_, first)) = lines.peek() {
+            if first.trim_start().starts_with("#version") {
+                res.push(first, None, 1);
+                let _ = lines.next();
+            }
+        }
+
+        for (name, value) in defines {
+            let define = format!("#define {} {}", name, value);
+            res.push(define.trim_end(), None, 0);
+        }
+
+        for (i, line) in lines {
+            res.include(library, line, None, i as u32 + 1, stage, &mut included)?;
+        }
+
+        Ok(res)
+    }
+
+    fn push(&mut self, line: &str, chunk: Option<&str>, line_number: u32) {
+        self.source.push_str(line);
+        self.source.push('\n');
+        self.origins
+            .push((chunk.map(|c| c.to_string()), line_number));
+    }
+
+    /// Appends a line, replacing it by the corresponding chunk if it is an include directive.
+    fn include(
+        &mut self,
+        library: &ShaderLibrary,
+        line: &str,
+        chunk: Option<&str>,
+        line_number: u32,
+        stage: ShaderStage,
+        included: &mut HashSet<String>,
+    ) -> Result<(), ShaderError> {
+        let directive = line.trim_start();
+
+        if !directive.starts_with("#include") {
+            self.push(line, chunk, line_number);
+            return Ok(());
+        }
+
+        let error = |message: String| ShaderError::Preprocessing {
+            stage,
+            chunk: chunk.map(|c| c.to_string()),
+            line: line_number,
+            message,
+        };
+
+        let arg = directive["#include".len()..].trim();
+        let name = if arg.len() >= 2
+            && ((arg.starts_with('"') && arg.ends_with('"'))
+                || (arg.starts_with('<') && arg.ends_with('>')))
+        {
+            &arg[1..arg.len() - 1]
+        } else {
+            return Err(error(format!("invalid include directive `{}'.", directive)));
+        };
+
+        let source = library
+            .get_chunk(name)
+            .ok_or_else(|| error(format!("unknown chunk `{}'.", name)))?;
+
+        // Each chunk is included once, which also prevents include cycles.
+        if included.insert(name.to_string()) {
+            for (i, line) in source.lines().enumerate() {
+                self.include(library, line, Some(name), i as u32 + 1, stage, included)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Maps the lines of a compilation error to the chunk and line they come from.
+    pub fn map_error(&self, error: ShaderError) -> ShaderError {
+        match error {
+            ShaderError::Compilation {
+                stage,
+                line: Some(line),
+                log,
+                ..
+            } => match self.origins.get(line.wrapping_sub(1) as usize) {
+                Some((chunk, line)) => ShaderError::Compilation {
+                    stage,
+                    chunk: chunk.clone(),
+                    line: Some(*line),
+                    log,
+                },
+                None => ShaderError::Compilation {
+                    stage,
+                    chunk: None,
+                    line: Some(line),
+                    log,
+                },
+            },
+            error => error,
+        }
+    }
+}
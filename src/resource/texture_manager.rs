@@ -4,10 +4,14 @@ use image::{self, DynamicImage};
 use std::cell::RefCell;
 use std::collections::hash_map::Entry;
 use std::collections::HashMap;
+use std::io;
 use std::path::Path;
 use std::rc::Rc;
 
 use crate::context::{Context, Texture};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::resource::{AsyncLoader, DynamicTexture, HotReloader, ReloadError, TextureFormat};
+use na::Point4;
 
 #[path = "../error.rs"]
 mod error;
@@ -21,6 +25,10 @@ pub enum TextureWrapping {
     MirroredRepeat,
     /// Repeats the nearest edge point texture color when a texture coordinate is out of bounds.
     ClampToEdge,
+    /// Uses the border color of the texture when a texture coordinate is out of bounds.
+    ///
+    /// Not supported by OpenGL ES and WebGL.
+    ClampToBorder,
 }
 
 impl Into<u32> for TextureWrapping {
@@ -30,10 +38,42 @@ impl Into<u32> for TextureWrapping {
             TextureWrapping::Repeat => Context::REPEAT,
             TextureWrapping::MirroredRepeat => Context::MIRRORED_REPEAT,
             TextureWrapping::ClampToEdge => Context::CLAMP_TO_EDGE,
+            TextureWrapping::ClampToBorder => Context::CLAMP_TO_BORDER,
+        }
+    }
+}
+
+/// Filtering parameters for a texture.
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub enum TextureFilter {
+    /// Uses the texel nearest to the texture coordinates, giving sharp texels.
+    Nearest,
+    /// Interpolates linearly between the texels nearest to the texture coordinates.
+    Linear,
+}
+
+impl TextureFilter {
+    fn min_filter(self, mipmap: Option<TextureFilter>) -> u32 {
+        match (self, mipmap) {
+            (TextureFilter::Nearest, None) => Context::NEAREST,
+            (TextureFilter::Linear, None) => Context::LINEAR,
+            (TextureFilter::Nearest, Some(TextureFilter::Nearest)) => {
+                Context::NEAREST_MIPMAP_NEAREST
+            }
+            (TextureFilter::Linear, Some(TextureFilter::Nearest)) => Context::LINEAR_MIPMAP_NEAREST,
+            (TextureFilter::Nearest, Some(TextureFilter::Linear)) => Context::NEAREST_MIPMAP_LINEAR,
+            (TextureFilter::Linear, Some(TextureFilter::Linear)) => Context::LINEAR_MIPMAP_LINEAR,
         }
     }
 }
 
+impl Into<u32> for TextureFilter {
+    #[inline]
+    fn into(self) -> u32 {
+        self.min_filter(None)
+    }
+}
+
 impl Texture {
     /// Allocates a new texture on the gpu. The texture is not configured.
     pub fn new() -> Rc<Texture> {
@@ -44,7 +84,7 @@ impl Texture {
     }
 
     /// Sets the wrapping of this texture along the `s` texture coordinate.
-    pub fn set_wrapping_s(&mut self, wrapping: TextureWrapping) {
+    pub fn set_wrapping_s(&self, wrapping: TextureWrapping) {
         let ctxt = Context::get();
         verify!(ctxt.bind_texture(Context::TEXTURE_2D, Some(&self)));
         let wrap: u32 = wrapping.into();
@@ -52,12 +92,124 @@ impl Texture {
     }
 
     /// Sets the wrapping of this texture along the `t` texture coordinate.
-    pub fn set_wrapping_t(&mut self, wrapping: TextureWrapping) {
+    pub fn set_wrapping_t(&self, wrapping: TextureWrapping) {
         let ctxt = Context::get();
         verify!(ctxt.bind_texture(Context::TEXTURE_2D, Some(&self)));
         let wrap: u32 = wrapping.into();
         verify!(ctxt.tex_parameteri(Context::TEXTURE_2D, Context::TEXTURE_WRAP_T, wrap as i32));
     }
+
+    /// Sets the color used outside of this texture by the `ClampToBorder` wrapping.
+    ///
+    /// Not supported by OpenGL ES and WebGL.
+    pub fn set_border_color(&self, color: Point4<f32>) {
+        let ctxt = Context::get();
+        verify!(ctxt.bind_texture(Context::TEXTURE_2D, Some(&self)));
+        verify!(ctxt.tex_parameterfv(
+            Context::TEXTURE_2D,
+            Context::TEXTURE_BORDER_COLOR,
+            color.coords.as_slice()
+        ));
+    }
+
+    /// Sets the filter used when this texture is displayed larger than its size.
+    ///
+    /// Textures are linearly filtered by default. Use `TextureFilter::Nearest` to keep the
+    /// texels sharp, e.g., for pixel art or data.
+    pub fn set_mag_filter(&self, filter: TextureFilter) {
+        let ctxt = Context::get();
+        verify!(ctxt.bind_texture(Context::TEXTURE_2D, Some(&self)));
+        let filter: u32 = filter.into();
+        verify!(ctxt.tex_parameteri(
+            Context::TEXTURE_2D,
+            Context::TEXTURE_MAG_FILTER,
+            filter as i32
+        ));
+    }
+
+    /// Sets the filter used when this texture is displayed smaller than its size.
+    ///
+    /// If `mipmap` is set, the mipmaps of this texture are used and `mipmap` is the filter used
+    /// between its two nearest mipmap levels. The mipmaps are generated if needed, and again
+    /// each time the texture content is replaced by the texture manager.
+    ///
+    /// On WebGL 1 and OpenGL ES 2, only textures with power-of-two sizes support mipmaps.
+    pub fn set_min_filter(&self, filter: TextureFilter, mipmap: Option<TextureFilter>) {
+        let ctxt = Context::get();
+        verify!(ctxt.bind_texture(Context::TEXTURE_2D, Some(&self)));
+        verify!(ctxt.tex_parameteri(
+            Context::TEXTURE_2D,
+            Context::TEXTURE_MIN_FILTER,
+            filter.min_filter(mipmap) as i32
+        ));
+
+        // This fails on empty textures, whose mipmaps are generated when they are filled.
+        if mipmap.is_some() {
+            ignore!(ctxt.generate_mipmap(Context::TEXTURE_2D));
+        }
+    }
+
+    /// Sets both the minification and magnification filters of this texture, without mipmaps.
+    pub fn set_filter(&self, filter: TextureFilter) {
+        self.set_mag_filter(filter);
+        self.set_min_filter(filter, None);
+    }
+
+    /// Generates the mipmaps of this texture from its current content.
+    ///
+    /// This is needed after modifying the texture content directly with OpenGL.
+    pub fn generate_mipmaps(&self) {
+        let ctxt = Context::get();
+        verify!(ctxt.bind_texture(Context::TEXTURE_2D, Some(&self)));
+        verify!(ctxt.generate_mipmap(Context::TEXTURE_2D));
+    }
+
+    /// Regenerates the mipmaps of this texture if its minification filter uses them, since they
+    /// would otherwise keep the previous content.
+    pub(crate) fn refresh_mipmaps(&self) {
+        let ctxt = Context::get();
+        verify!(ctxt.bind_texture(Context::TEXTURE_2D, Some(&self)));
+        let min_filter =
+            ctxt.get_tex_parameteri(Context::TEXTURE_2D, Context::TEXTURE_MIN_FILTER) as u32;
+
+        if min_filter != Context::NEAREST && min_filter != Context::LINEAR {
+            ignore!(ctxt.generate_mipmap(Context::TEXTURE_2D));
+        }
+    }
+
+    /// Sets the level of anisotropic filtering of this texture.
+    ///
+    /// The level is clamped to `Texture::max_anisotropy()`, and the level actually set is
+    /// returned. This does nothing and returns `1.0` if anisotropic filtering is not supported.
+    pub fn set_anisotropy(&self, level: f32) -> f32 {
+        let max = Texture::max_anisotropy();
+
+        if max <= 1.0 {
+            return 1.0;
+        }
+
+        let level = level.max(1.0).min(max);
+        let ctxt = Context::get();
+        verify!(ctxt.bind_texture(Context::TEXTURE_2D, Some(&self)));
+        verify!(ctxt.tex_parameterf(Context::TEXTURE_2D, Context::TEXTURE_MAX_ANISOTROPY, level));
+        level
+    }
+
+    /// The maximum level of anisotropic filtering, or `1.0` if it is not supported.
+    ///
+    /// Anisotropic filtering requires OpenGL 4.6, or the `EXT_texture_filter_anisotropic`
+    /// extension.
+    pub fn max_anisotropy() -> f32 {
+        let ctxt = Context::get();
+        let max = ctxt.get_parameteri(Context::MAX_TEXTURE_MAX_ANISOTROPY);
+
+        // The parameter is an invalid enum without the extension.
+        if ctxt.get_error() != 0 {
+            1.0
+        } else {
+            max.max(1) as f32
+        }
+    }
 }
 
 impl Drop for Texture {
@@ -130,7 +282,7 @@ impl TextureManager {
     }
 
     /// Mutably applies a function to the texture manager.
-    pub fn get_global_manager<T, F: FnMut(&mut TextureManager) -> T>(mut f: F) -> T {
+    pub fn get_global_manager<T, F: FnOnce(&mut TextureManager) -> T>(f: F) -> T {
         KEY_TEXTURE_MANAGER.with(|manager| f(&mut *manager.borrow_mut()))
     }
 
@@ -151,6 +303,16 @@ impl TextureManager {
             .map(|t| (t.0.clone(), t.1))
     }
 
+    /// Finds the name and size of a texture registered to this manager.
+    ///
+    /// Returns `None` if the texture was not created by this manager.
+    pub fn find(&self, texture: &Rc<Texture>) -> Option<(&str, (u32, u32))> {
+        self.textures
+            .iter()
+            .find(|(_, (tex, _))| Rc::ptr_eq(tex, texture))
+            .map(|(name, (_, size))| (&name[..], *size))
+    }
+
     /// Allocates a new texture that is not yet configured.
     ///
     /// If a texture with same name exists, nothing is created and the old texture is returned.
@@ -161,6 +323,25 @@ impl TextureManager {
         }
     }
 
+    /// Allocates a new texture of the given size and format, updated from CPU buffers.
+    ///
+    /// The texture is filled with zeros, and registered under `name` like the other textures of
+    /// this manager. If a texture with same name exists, it is replaced, but remains alive as long
+    /// as it is used.
+    pub fn add_dynamic(
+        &mut self,
+        name: &str,
+        width: u32,
+        height: u32,
+        format: TextureFormat,
+    ) -> DynamicTexture {
+        let res = DynamicTexture::new(width, height, format);
+        let _ = self
+            .textures
+            .insert(name.to_string(), (res.texture().clone(), (width, height)));
+        res
+    }
+
     /// Allocates a new texture read from a `DynamicImage` object.
     ///
     /// If a texture with same name exists, nothing is created and the old texture is returned.
@@ -191,14 +372,33 @@ impl TextureManager {
     fn load_texture_into_context(
         dynamic_image: DynamicImage,
     ) -> Result<(Rc<Texture>, (u32, u32)), &'static str> {
-        let ctxt = Context::get();
+        let tex = TextureManager::new_texture();
+        let size = TextureManager::upload_image(&tex, dynamic_image)?;
+        Ok((tex, size))
+    }
+
+    /// Allocates a new texture with the default parameters of loaded textures: linear filtering
+    /// without mipmaps, and clamping to the edges.
+    pub(crate) fn new_texture() -> Rc<Texture> {
         let tex = Texture::new();
+        tex.set_wrapping_s(TextureWrapping::ClampToEdge);
+        tex.set_wrapping_t(TextureWrapping::ClampToEdge);
+        tex.set_filter(TextureFilter::Linear);
+        tex
+    }
+
+    /// Uploads an image to an existing texture, replacing its content.
+    fn upload_image(
+        tex: &Texture,
+        dynamic_image: DynamicImage,
+    ) -> Result<(u32, u32), &'static str> {
+        let ctxt = Context::get();
         let width;
         let height;
 
         unsafe {
             verify!(ctxt.active_texture(Context::TEXTURE0));
-            verify!(ctxt.bind_texture(Context::TEXTURE_2D, Some(&*tex)));
+            verify!(ctxt.bind_texture(Context::TEXTURE_2D, Some(tex)));
 
             match dynamic_image {
                 DynamicImage::ImageRgb8(image) => {
@@ -236,37 +436,139 @@ impl TextureManager {
                 }
             }
 
-            verify!(ctxt.tex_parameteri(
-                Context::TEXTURE_2D,
-                Context::TEXTURE_WRAP_S,
-                Context::CLAMP_TO_EDGE as i32
-            ));
-            verify!(ctxt.tex_parameteri(
-                Context::TEXTURE_2D,
-                Context::TEXTURE_WRAP_T,
-                Context::CLAMP_TO_EDGE as i32
-            ));
-            verify!(ctxt.tex_parameteri(
-                Context::TEXTURE_2D,
-                Context::TEXTURE_MIN_FILTER,
-                Context::LINEAR as i32
-            ));
-            verify!(ctxt.tex_parameteri(
-                Context::TEXTURE_2D,
-                Context::TEXTURE_MAG_FILTER,
-                Context::LINEAR as i32
-            ));
+            tex.refresh_mipmaps();
         }
-        Ok((tex, (width, height)))
+        Ok((width, height))
     }
 
     /// Allocates a new texture read from a file. If a texture with same name exists, nothing is
     /// created and the old texture is returned.
+    ///
+    /// The file is watched if hot reloading is enabled, see `HotReloader`.
     pub fn add(&mut self, path: &Path, name: &str) -> Rc<Texture> {
         self.textures
             .entry(name.to_string())
-            .or_insert_with(|| TextureManager::load_texture_from_file(path))
+            .or_insert_with(|| {
+                let res = TextureManager::load_texture_from_file(path);
+                #[cfg(not(target_arch = "wasm32"))]
+                TextureManager::watch(path, name, &res.0);
+                res
+            })
             .0
             .clone()
     }
+
+    /// Allocates a new texture read from a file, returning an error if it cannot be decoded. If a
+    /// texture with same name exists, nothing is created and the old texture is returned.
+    ///
+    /// The file is watched if hot reloading is enabled, see `HotReloader`.
+    pub fn try_add(&mut self, path: &Path, name: &str) -> io::Result<Rc<Texture>> {
+        if let Some(tex) = self.get(name) {
+            return Ok(tex);
+        }
+
+        let image = TextureManager::decode_image(path)?;
+        let res = TextureManager::load_texture_into_context(image).map_err(|e| {
+            io::Error::new(io::ErrorKind::Other, format!("{}: {}", path.display(), e))
+        })?;
+        #[cfg(not(target_arch = "wasm32"))]
+        TextureManager::watch(path, name, &res.0);
+        let tex = res.0.clone();
+        let _ = self.textures.insert(name.to_string(), res);
+
+        Ok(tex)
+    }
+
+    /// Reloads the texture in place when its file changes, if hot reloading is enabled.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn watch(path: &Path, name: &str, tex: &Rc<Texture>) {
+        let tex = Rc::downgrade(tex);
+        let file = path.to_path_buf();
+        let name = name.to_string();
+
+        HotReloader::get_global_reloader(|reloader| {
+            reloader.watch(vec![path.to_path_buf()], move || {
+                let tex = tex.upgrade()?;
+                let res = TextureManager::decode_image(&file)
+                    .and_then(|image| {
+                        TextureManager::upload_image(&tex, image).map_err(|e| {
+                            io::Error::new(
+                                io::ErrorKind::Other,
+                                format!("{}: {}", file.display(), e),
+                            )
+                        })
+                    })
+                    .map_err(ReloadError::from)
+                    .map(|size| {
+                        TextureManager::get_global_manager(|tm| {
+                            if let Some(entry) = tm.textures.get_mut(&name) {
+                                if Rc::ptr_eq(&entry.0, &tex) {
+                                    entry.1 = size;
+                                }
+                            }
+                        })
+                    });
+
+                Some(res)
+            })
+        })
+    }
+
+    /// Allocates a new texture read from a file on a worker thread.
+    ///
+    /// The returned texture is white until the image is decoded, then it is filled by
+    /// `Window::render`. If a texture with same name exists, nothing is created and the old
+    /// texture is returned.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn add_async(&mut self, path: &Path, name: &str) -> Rc<Texture> {
+        if let Some(tex) = self.get(name) {
+            return tex;
+        }
+
+        let tex = TextureManager::new_texture();
+        let white =
+            DynamicImage::ImageRgb8(image::RgbImage::from_pixel(1, 1, image::Rgb([255; 3])));
+        let size = TextureManager::upload_image(&tex, white).unwrap();
+        let _ = self.textures.insert(name.to_string(), (tex.clone(), size));
+
+        let path = path.to_path_buf();
+        let name = name.to_string();
+        let placeholder = tex.clone();
+
+        AsyncLoader::get_global_loader(|loader| {
+            loader.spawn(
+                move || TextureManager::decode_image(&path),
+                move |image| match image {
+                    Ok(image) => TextureManager::get_global_manager(|tm| {
+                        let size = TextureManager::upload_image(&placeholder, image).unwrap();
+
+                        // The texture may have been replaced in the meantime.
+                        if let Some(entry) = tm.textures.get_mut(&name) {
+                            if Rc::ptr_eq(&entry.0, &placeholder) {
+                                entry.1 = size;
+                            }
+                        }
+                    }),
+                    Err(e) => AsyncLoader::get_global_loader(|loader| loader.push_error(e)),
+                },
+            )
+        });
+
+        tex
+    }
+
+    /// Reads and decodes an image, converting it to a format supported by `upload_image`.
+    pub(crate) fn decode_image(path: &Path) -> io::Result<DynamicImage> {
+        let image = image::open(path).map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}: {}", path.display(), e),
+            )
+        })?;
+
+        match image {
+            DynamicImage::ImageRgb8(_) | DynamicImage::ImageRgba8(_) => Ok(image),
+            image => Ok(DynamicImage::ImageRgba8(image.to_rgba8())),
+        }
+    }
 }
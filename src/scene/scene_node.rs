@@ -1,5 +1,12 @@
 use crate::camera::Camera;
 use crate::light::Light;
+use crate::loader::mtl::MtlMaterial;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::loader::obj;
+use crate::loader::obj::{ElementKind, ParseWarning};
+use crate::loader::{ply, stl, PointSet};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::resource::AsyncLoader;
 use crate::resource::{Material, MaterialManager, Mesh, MeshManager, Texture, TextureManager};
 use crate::scene::Object;
 use na;
@@ -7,6 +14,7 @@ use na::{Isometry3, Point2, Point3, Translation3, UnitQuaternion, Vector3};
 use ncollide3d::procedural;
 use ncollide3d::procedural::TriMesh;
 use std::cell::{Ref, RefCell, RefMut};
+use std::io::Result as IoResult;
 use std::mem;
 use std::path::{Path, PathBuf};
 use std::rc::Rc;
@@ -300,6 +308,12 @@ impl SceneNodeData {
         self.visible
     }
 
+    /// The children of this node.
+    #[inline]
+    pub fn children(&self) -> &[SceneNode] {
+        &self.children[..]
+    }
+
     /// Sets the visibility of this node.
     ///
     /// The node and its children are not rendered if it is not visible.
@@ -442,6 +456,17 @@ impl SceneNodeData {
         self.world_transform.clone()
     }
 
+    /// This node world scaling factors.
+    ///
+    /// This will force an update of the world transformation of its parents if they have been
+    /// invalidated.
+    #[inline]
+    pub fn world_scale(&self) -> Vector3<f32> {
+        // The world scale is updated together with the world transformation.
+        let _ = self.world_transformation();
+        self.world_scale
+    }
+
     /// The inverse of this node world transformation.
     ///
     /// This will force an update of the world transformation of its parents if they have been
@@ -572,8 +597,8 @@ impl SceneNodeData {
                     let mut dp = (**p).borrow_mut();
 
                     dp.update();
-                    self.world_transform = self.local_transform * dp.world_transform;
-                    self.world_scale = self.local_scale.component_mul(&dp.local_scale);
+                    self.world_transform = dp.world_transform * self.local_transform;
+                    self.world_scale = self.local_scale.component_mul(&dp.world_scale);
                     self.up_to_date = true;
                     return;
                 },
@@ -800,69 +825,233 @@ impl SceneNode {
         )
     }
 
-    /// Creates and adds multiple nodes created from an obj file.
+    /// Creates and adds a group of nodes displaying a point set.
     ///
-    /// This will create a new node serving as a root of the scene described by the obj file. This
-    /// newly created node is added to this node's children.
-    pub fn add_obj(&mut self, path: &Path, mtl_dir: &Path, scale: Vector3<f32>) -> SceneNode {
+    /// The per-vertex colors of the point set are used. If it has no face, its points are
+    /// displayed instead of its surface.
+    pub fn add_point_set(&mut self, point_set: &PointSet, scale: Vector3<f32>) -> SceneNode {
         let tex = TextureManager::get_global_manager(|tm| tm.get_default());
         let mat = MaterialManager::get_global_manager(|mm| mm.get_default());
+        let mut root = self.add_group();
+        root.set_local_scale(scale.x, scale.y, scale.z);
 
-        // FIXME: is there some error-handling stuff to do here instead of the `let _`.
-        let result = MeshManager::load_obj(path, mtl_dir, path.to_str().unwrap()).map(|objs| {
-            let mut root;
+        for mesh in point_set.to_meshes() {
+            let mesh = Rc::new(RefCell::new(mesh));
+            let mut object = Object::new(mesh, 1.0, 1.0, 1.0, tex.clone(), mat.clone());
 
-            let self_root = objs.len() == 1;
-            let child_scale;
-
-            if self_root {
-                root = self.clone();
-                child_scale = scale;
-            } else {
-                root = SceneNode::new(scale, na::one(), None);
-                self.add_child(root.clone());
-                child_scale = Vector3::from_element(1.0);
+            if point_set.faces.is_empty() {
+                object.set_surface_rendering_activation(false);
+                object.set_points_size(1.0);
             }
 
-            for (_, mesh, mtl) in objs.into_iter() {
-                let mut object = Object::new(mesh, 1.0, 1.0, 1.0, tex.clone(), mat.clone());
+            let _ = root.add_object(Vector3::from_element(1.0), na::one(), object);
+        }
 
-                match mtl {
-                    None => {}
-                    Some(mtl) => {
-                        object.set_color(mtl.diffuse.x, mtl.diffuse.y, mtl.diffuse.z);
+        root
+    }
 
-                        for t in mtl.diffuse_texture.iter() {
-                            let mut tpath = PathBuf::new();
-                            tpath.push(mtl_dir);
-                            tpath.push(&t[..]);
-                            object.set_texture_from_file(&tpath, tpath.to_str().unwrap())
-                        }
+    /// Creates and adds a group of nodes displaying the content of a PLY file.
+    ///
+    /// See `add_point_set` for details.
+    pub fn add_ply(&mut self, path: &Path, scale: Vector3<f32>) -> IoResult<SceneNode> {
+        let point_set = ply::parse_file(path)?;
 
-                        for t in mtl.ambiant_texture.iter() {
-                            let mut tpath = PathBuf::new();
-                            tpath.push(mtl_dir);
-                            tpath.push(&t[..]);
-                            object.set_texture_from_file(&tpath, tpath.to_str().unwrap())
+        Ok(self.add_point_set(&point_set, scale))
+    }
+
+    /// Creates and adds a group of nodes displaying the content of a STL file.
+    ///
+    /// If `weld` is `true`, the vertices sharing the same position are merged so the mesh is
+    /// smoothly shaded. Otherwise, each facet is flat.
+    pub fn add_stl(&mut self, path: &Path, weld: bool, scale: Vector3<f32>) -> IoResult<SceneNode> {
+        let point_set = stl::parse_file(path, weld)?;
+
+        Ok(self.add_point_set(&point_set, scale))
+    }
+
+    /// Creates and adds multiple nodes created from an obj file.
+    ///
+    /// This will create a new node serving as a root of the scene described by the obj file. This
+    /// newly created node is added to this node's children. Each group or object of the file gets
+    /// its own node. Its line and point elements get separate nodes too, drawn with a line width
+    /// and a point size of 1.
+    ///
+    /// Panics if the file cannot be loaded, and prints the parsing warnings. See `try_add_obj` for
+    /// a fallible version.
+    pub fn add_obj(&mut self, path: &Path, mtl_dir: &Path, scale: Vector3<f32>) -> SceneNode {
+        let (node, warnings) = self.try_add_obj(path, mtl_dir, scale).unwrap();
+
+        for warning in warnings.iter() {
+            println!("Warning: {}", warning);
+        }
+
+        node
+    }
+
+    /// Creates and adds multiple nodes created from an obj file.
+    ///
+    /// Same as `add_obj` but returns an error if the file cannot be read or is malformed. The
+    /// warnings emitted while parsing the file are returned with the created node, along with a
+    /// warning for each texture which cannot be loaded and is skipped.
+    pub fn try_add_obj(
+        &mut self,
+        path: &Path,
+        mtl_dir: &Path,
+        scale: Vector3<f32>,
+    ) -> IoResult<(SceneNode, Vec<ParseWarning>)> {
+        let (objs, mut warnings) = MeshManager::load_obj(path, mtl_dir, &path.to_string_lossy())?;
+        let node = self.add_obj_meshes(objs, mtl_dir, scale, &mut warnings);
+
+        Ok((node, warnings))
+    }
+
+    /// Creates and adds multiple nodes created from an obj file, loaded on a worker thread.
+    ///
+    /// The returned node is empty until the obj file and its textures are loaded, then the nodes
+    /// of the obj file are added to it by `Window::render`. Loading errors are reported by
+    /// `AsyncLoader::take_errors`.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn add_obj_async(&mut self, path: &Path, mtl_dir: &Path, scale: Vector3<f32>) -> SceneNode {
+        let mut node = self.add_group();
+        node.set_local_scale(scale.x, scale.y, scale.z);
+
+        let path = path.to_path_buf();
+        let mtl_dir = mtl_dir.to_path_buf();
+        let texture_dir = mtl_dir.clone();
+        let mut placeholder = node.clone();
+
+        AsyncLoader::get_global_loader(|loader| {
+            loader.spawn(
+                move || -> IoResult<_> {
+                    let (objs, _) = obj::parse_file(&path, &texture_dir, &path.to_string_lossy())?;
+                    let mut images = Vec::new();
+
+                    for (_, _, mtl, _) in objs.iter() {
+                        let textures = mtl.iter().flat_map(|mtl| {
+                            mtl.diffuse_texture.iter().chain(mtl.ambiant_texture.iter())
+                        });
+
+                        for t in textures {
+                            let tpath = texture_dir.join(t);
+                            let name = tpath.to_string_lossy().into_owned();
+
+                            if images.iter().all(|(n, _)| *n != name) {
+                                images.push((name, TextureManager::decode_image(&tpath)?));
+                            }
                         }
                     }
-                }
 
-                let _ = root.add_object(child_scale, na::one(), object);
+                    Ok((objs, images))
+                },
+                move |result| match result {
+                    Ok((objs, images)) => {
+                        // The textures are registered first so the objects do not read them again.
+                        TextureManager::get_global_manager(|tm| {
+                            for (name, image) in images {
+                                let _ = tm.add_image(image, &name);
+                            }
+                        });
+
+                        let objs = objs
+                            .into_iter()
+                            .map(|(n, m, mat, kind)| (n, Rc::new(RefCell::new(m)), mat, kind))
+                            .collect();
+                        // The textures were all decoded, so no warning is emitted.
+                        let _ = placeholder.add_obj_meshes(
+                            objs,
+                            &mtl_dir,
+                            Vector3::repeat(1.0),
+                            &mut Vec::new(),
+                        );
+                    }
+                    Err(e) => AsyncLoader::get_global_loader(|loader| loader.push_error(e)),
+                },
+            )
+        });
+
+        node
+    }
+
+    fn add_obj_meshes(
+        &mut self,
+        objs: Vec<(String, Rc<RefCell<Mesh>>, Option<MtlMaterial>, ElementKind)>,
+        mtl_dir: &Path,
+        scale: Vector3<f32>,
+        warnings: &mut Vec<ParseWarning>,
+    ) -> SceneNode {
+        let tex = TextureManager::get_global_manager(|tm| tm.get_default());
+        let mat = MaterialManager::get_global_manager(|mm| mm.get_default());
+        let mut root;
+
+        let self_root = objs.len() == 1;
+        let child_scale;
+
+        if self_root {
+            root = self.clone();
+            child_scale = scale;
+        } else {
+            root = SceneNode::new(scale, na::one(), None);
+            self.add_child(root.clone());
+            child_scale = Vector3::from_element(1.0);
+        }
+
+        for (_, mesh, mtl, kind) in objs.into_iter() {
+            let mut object = Object::new(mesh, 1.0, 1.0, 1.0, tex.clone(), mat.clone());
+
+            match kind {
+                ElementKind::Faces => {}
+                ElementKind::Lines => {
+                    object.set_surface_rendering_activation(false);
+                    object.set_lines_width(1.0);
+                }
+                ElementKind::Points => {
+                    object.set_surface_rendering_activation(false);
+                    object.set_points_size(1.0);
+                }
             }
 
-            if self_root {
-                root.data()
-                    .children
-                    .last()
-                    .expect("There was nothing on this obj file.")
-                    .clone()
-            } else {
-                root
+            match mtl {
+                None => {}
+                Some(mtl) => {
+                    object.set_color(mtl.diffuse.x, mtl.diffuse.y, mtl.diffuse.z);
+
+                    let textures = mtl.diffuse_texture.iter().chain(mtl.ambiant_texture.iter());
+
+                    for t in textures {
+                        let mut tpath = PathBuf::new();
+                        tpath.push(mtl_dir);
+                        tpath.push(&t[..]);
+                        let texture = TextureManager::get_global_manager(|tm| {
+                            tm.try_add(&tpath, &tpath.to_string_lossy())
+                        });
+
+                        match texture {
+                            Ok(texture) => object.set_texture(texture),
+                            Err(e) => {
+                                let message = format!("texture skipped: {}", e);
+
+                                // Several objects may use the same texture.
+                                if warnings.iter().all(|w| w.message != message) {
+                                    warnings.push(ParseWarning { line: 0, message });
+                                }
+                            }
+                        }
+                    }
+                }
             }
-        });
 
-        result.unwrap()
+            let _ = root.add_object(child_scale, na::one(), object);
+        }
+
+        if self_root {
+            root.data()
+                .children
+                .last()
+                .expect("There was nothing on this obj file.")
+                .clone()
+        } else {
+            root
+        }
     }
 
     /// Applies a closure to each object contained by this node and its children.
@@ -1,12 +1,17 @@
 use std::borrow::Borrow;
 use std::fs::File;
 use std::io::Read;
+#[cfg(not(target_arch = "wasm32"))]
+use std::io::{self, Result as IoResult};
 use std::path::Path;
 use std::rc::Rc;
 use std::sync::Once;
 
 use rusttype;
 
+#[cfg(not(target_arch = "wasm32"))]
+use crate::resource::{AsyncLoader, Loading};
+
 #[path = "../error.rs"]
 mod error;
 
@@ -24,9 +29,48 @@ impl Font {
         Font::from_bytes(&memory)
     }
 
+    /// Loads a new ttf font from a file on a worker thread.
+    ///
+    /// The font is available once the returned value is loaded, which happens during a later
+    /// call to `Window::render`. Loading errors are reported by `AsyncLoader::take_errors`.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn new_async(path: &Path) -> Loading<Rc<Font>> {
+        let path = path.to_path_buf();
+        let font_path = path.clone();
+        let res = Loading::new();
+        let placeholder = res.clone();
+
+        AsyncLoader::get_global_loader(|loader| {
+            loader.spawn(
+                move || -> IoResult<Vec<u8>> {
+                    let mut memory = Vec::new();
+                    let _ = File::open(&path)?.read_to_end(&mut memory)?;
+                    Ok(memory)
+                },
+                move |memory| {
+                    let font = memory.and_then(|memory| {
+                        Font::from_bytes(&memory).ok_or_else(|| {
+                            io::Error::new(
+                                io::ErrorKind::InvalidData,
+                                format!("{}: not a valid ttf font.", font_path.display()),
+                            )
+                        })
+                    });
+
+                    match font {
+                        Ok(font) => placeholder.set(font),
+                        Err(e) => AsyncLoader::get_global_loader(|loader| loader.push_error(e)),
+                    }
+                },
+            )
+        });
+
+        res
+    }
+
     /// Loads a new ttf font from the memory.
     pub fn from_bytes(memory: &[u8]) -> Option<Rc<Font>> {
-        let font = rusttype::Font::from_bytes(memory.to_vec()).unwrap();
+        let font = rusttype::Font::from_bytes(memory.to_vec()).ok()?;
         Some(Rc::new(Font { font }))
     }
 
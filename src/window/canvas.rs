@@ -1,6 +1,7 @@
+use std::path::PathBuf;
 use std::sync::mpsc::Sender;
 
-use crate::event::{Action, Key, MouseButton, WindowEvent};
+use crate::event::{Action, ActionMap, DroppedFile, Key, MouseButton, WindowEvent};
 #[cfg(not(target_arch = "wasm32"))]
 use crate::window::GLCanvas as CanvasImpl;
 #[cfg(target_arch = "wasm32")]
@@ -49,9 +50,123 @@ pub struct CanvasSetup {
     pub samples: NumSamples,
 }
 
+/// The fullscreen modes of a window.
+///
+/// Monitors are identified by their index in the list returned by `Window::monitors`, or the
+/// monitor currently containing the window if `None`.
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum Fullscreen {
+    /// A borderless window covering the whole monitor.
+    Borderless(Option<usize>),
+    /// Exclusive fullscreen, with the video mode of the monitor having the highest resolution and
+    /// refresh rate.
+    Exclusive(Option<usize>),
+}
+
+/// A monitor connected to the computer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Monitor {
+    /// The name of the monitor, if known.
+    pub name: Option<String>,
+    /// The resolution of the monitor, in pixels.
+    pub size: (u32, u32),
+    /// The position of the top-left corner of the monitor on the desktop, in pixels.
+    pub position: (i32, i32),
+    /// The scale factor of the monitor.
+    pub scale_factor: f64,
+}
+
+/// The icons of the cursor.
+///
+/// They follow the names of the CSS `cursor` property, and are rendered by the platform.
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum CursorIcon {
+    /// The default cursor of the platform, usually an arrow.
+    Default,
+    /// A cross, usually used for precise selection.
+    Crosshair,
+    /// A hand with a pointing finger, usually used for links.
+    Hand,
+    /// An arrow.
+    Arrow,
+    /// Something can be moved.
+    Move,
+    /// Text can be selected.
+    Text,
+    /// The application is busy and cannot be interacted with.
+    Wait,
+    /// Help is available.
+    Help,
+    /// The application is busy but can still be interacted with.
+    Progress,
+    /// The requested action cannot be performed.
+    NotAllowed,
+    /// A context menu is available.
+    ContextMenu,
+    /// A cell or a set of cells can be selected.
+    Cell,
+    /// Vertical text can be selected.
+    VerticalText,
+    /// An alias or shortcut is to be created.
+    Alias,
+    /// Something is to be copied.
+    Copy,
+    /// The dragged item cannot be dropped here.
+    NoDrop,
+    /// Something can be grabbed.
+    Grab,
+    /// Something is being grabbed.
+    Grabbing,
+    /// Something can be scrolled in any direction.
+    AllScroll,
+    /// Something can be zoomed in.
+    ZoomIn,
+    /// Something can be zoomed out.
+    ZoomOut,
+    /// An edge is to be moved to the right.
+    EResize,
+    /// An edge is to be moved up.
+    NResize,
+    /// An edge is to be moved up and to the right.
+    NeResize,
+    /// An edge is to be moved up and to the left.
+    NwResize,
+    /// An edge is to be moved down.
+    SResize,
+    /// An edge is to be moved down and to the right.
+    SeResize,
+    /// An edge is to be moved down and to the left.
+    SwResize,
+    /// An edge is to be moved to the left.
+    WResize,
+    /// Something can be resized horizontally.
+    EwResize,
+    /// Something can be resized vertically.
+    NsResize,
+    /// Something can be resized along the up-right diagonal.
+    NeswResize,
+    /// Something can be resized along the up-left diagonal.
+    NwseResize,
+    /// A column can be resized horizontally.
+    ColResize,
+    /// A row can be resized vertically.
+    RowResize,
+}
+
+impl Default for CursorIcon {
+    fn default() -> Self {
+        CursorIcon::Default
+    }
+}
+
 /// An abstract structure representing a window for native applications, and a canvas for web applications.
 pub struct Canvas {
     canvas: CanvasImpl,
+    // The input state set by injected events, which takes precedence over the real one.
+    injected_keys: [Option<Action>; Key::Unknown as usize + 1],
+    injected_buttons: [Option<Action>; MouseButton::Button8 as usize + 1],
+    injected_cursor_pos: Option<(f64, f64)>,
+    action_map: ActionMap,
 }
 
 impl Canvas {
@@ -66,6 +181,10 @@ impl Canvas {
     ) -> Self {
         Canvas {
             canvas: CanvasImpl::open(title, hide, width, height, canvas_setup, out_events),
+            injected_keys: [None; Key::Unknown as usize + 1],
+            injected_buttons: [None; MouseButton::Button8 as usize + 1],
+            injected_cursor_pos: None,
+            action_map: ActionMap::new(),
         }
     }
 
@@ -79,6 +198,12 @@ impl Canvas {
         self.canvas.poll_events()
     }
 
+    /// Makes the OpenGL context of this canvas the current one, so that the next rendering
+    /// calls draw on this canvas.
+    pub fn make_current(&mut self) {
+        self.canvas.make_current()
+    }
+
     /// If double-buffering is supported, swap the buffers.
     pub fn swap_buffers(&mut self) {
         self.canvas.swap_buffers()
@@ -94,7 +219,8 @@ impl Canvas {
     /// This position may not be known if, e.g., the cursor has not been moved since the
     /// window was open.
     pub fn cursor_pos(&self) -> Option<(f64, f64)> {
-        self.canvas.cursor_pos()
+        self.injected_cursor_pos
+            .or_else(|| self.canvas.cursor_pos())
     }
 
     /// The scale factor.
@@ -125,6 +251,20 @@ impl Canvas {
         self.canvas.hide_cursor(hide);
     }
 
+    /// Set the icon of the cursor.
+    pub fn set_cursor_icon(&self, icon: CursorIcon) {
+        self.canvas.set_cursor_icon(icon);
+    }
+
+    /// Set a custom image as the cursor. See `Window::set_cursor_image` for details.
+    pub fn set_cursor_image(
+        &self,
+        image: impl GenericImage<Pixel = impl Pixel<Subpixel = u8>>,
+        hotspot: (u32, u32),
+    ) {
+        self.canvas.set_cursor_image(image, hotspot);
+    }
+
     /// Hide the window.
     pub fn hide(&mut self) {
         self.canvas.hide()
@@ -135,14 +275,154 @@ impl Canvas {
         self.canvas.show()
     }
 
+    /// The monitors connected to the computer.
+    pub fn monitors(&self) -> Vec<Monitor> {
+        self.canvas.monitors()
+    }
+
+    /// Set the fullscreen mode of the window, or make it windowed if `None`.
+    ///
+    /// Returns `false` if the mode could not be set.
+    pub fn set_fullscreen(&mut self, fullscreen: Option<Fullscreen>) -> bool {
+        self.canvas.set_fullscreen(fullscreen)
+    }
+
+    /// The fullscreen mode of the window, or `None` if it is windowed.
+    pub fn fullscreen(&self) -> Option<Fullscreen> {
+        self.canvas.fullscreen()
+    }
+
+    /// Set whether the window can be resized by the user.
+    pub fn set_resizable(&mut self, resizable: bool) {
+        self.canvas.set_resizable(resizable)
+    }
+
+    /// Whether the window can be resized by the user.
+    pub fn is_resizable(&self) -> bool {
+        self.canvas.is_resizable()
+    }
+
+    /// Set whether the window has a title bar and borders.
+    pub fn set_decorations(&mut self, decorations: bool) {
+        self.canvas.set_decorations(decorations)
+    }
+
+    /// Whether the window has a title bar and borders.
+    pub fn has_decorations(&self) -> bool {
+        self.canvas.has_decorations()
+    }
+
+    /// Set the position of the top-left corner of the window on the desktop, in pixels.
+    pub fn set_position(&mut self, x: i32, y: i32) {
+        self.canvas.set_position(x, y)
+    }
+
+    /// The position of the top-left corner of the window on the desktop, in pixels, if known.
+    pub fn position(&self) -> Option<(i32, i32)> {
+        self.canvas.position()
+    }
+
+    /// Maximize or restore the window.
+    pub fn set_maximized(&mut self, maximized: bool) {
+        self.canvas.set_maximized(maximized)
+    }
+
+    /// Minimize or restore the window.
+    pub fn set_minimized(&mut self, minimized: bool) {
+        self.canvas.set_minimized(minimized)
+    }
+
+    /// Set the minimum size of the window, in pixels, or remove it if `None`.
+    pub fn set_min_size(&mut self, size: Option<(u32, u32)>) {
+        self.canvas.set_min_size(size)
+    }
+
+    /// Set the maximum size of the window, in pixels, or remove it if `None`.
+    pub fn set_max_size(&mut self, size: Option<(u32, u32)>) {
+        self.canvas.set_max_size(size)
+    }
+
+    /// Set whether the window stays on top of the other windows.
+    pub fn set_always_on_top(&mut self, always_on_top: bool) {
+        self.canvas.set_always_on_top(always_on_top)
+    }
+
+    /// Removes and returns the files dropped onto the window during the last frame.
+    pub fn take_dropped_files(&mut self) -> Vec<DroppedFile> {
+        self.canvas.take_dropped_files()
+    }
+
+    /// The files currently dragged over the window.
+    pub fn hovered_files(&self) -> Vec<PathBuf> {
+        self.canvas.hovered_files()
+    }
+
+    /// The text contained by the clipboard, if any.
+    pub fn clipboard_text(&self) -> Option<String> {
+        self.canvas.clipboard_text()
+    }
+
+    /// Replace the content of the clipboard with some text.
+    pub fn set_clipboard_text(&self, text: &str) {
+        self.canvas.set_clipboard_text(text)
+    }
+
     /// The state of a mouse button.
     pub fn get_mouse_button(&self, button: MouseButton) -> Action {
-        self.canvas.get_mouse_button(button)
+        self.injected_buttons[button as usize]
+            .unwrap_or_else(|| self.canvas.get_mouse_button(button))
     }
 
     /// The state of a key.
     pub fn get_key(&self, key: Key) -> Action {
-        self.canvas.get_key(key)
+        self.injected_keys[key as usize].unwrap_or_else(|| self.canvas.get_key(key))
+    }
+
+    /// The action map of this canvas, updated by the events handled by its window.
+    pub fn action_map(&self) -> &ActionMap {
+        &self.action_map
+    }
+
+    pub(crate) fn action_map_mut(&mut self) -> &mut ActionMap {
+        &mut self.action_map
+    }
+
+    /// Updates the input state as if `event` had been received from the platform.
+    pub(crate) fn inject_event(&mut self, event: &WindowEvent) {
+        match *event {
+            WindowEvent::Key(key, action, _) => self.injected_keys[key as usize] = Some(action),
+            WindowEvent::MouseButton(button, action, _) => {
+                self.injected_buttons[button as usize] = Some(action)
+            }
+            WindowEvent::CursorPos(x, y, _) => self.injected_cursor_pos = Some((x, y)),
+            _ => {}
+        }
+    }
+
+    /// Overrides the state of a key, or reverts it to the real one if `action` is `None`.
+    pub(crate) fn set_injected_key(&mut self, key: Key, action: Option<Action>) {
+        self.injected_keys[key as usize] = action
+    }
+
+    /// Overrides the state of a mouse button, or reverts it to the real one if `action` is `None`.
+    pub(crate) fn set_injected_mouse_button(
+        &mut self,
+        button: MouseButton,
+        action: Option<Action>,
+    ) {
+        self.injected_buttons[button as usize] = action
+    }
+
+    /// Overrides the cursor position, or reverts it to the real one if `pos` is `None`.
+    pub(crate) fn set_injected_cursor_pos(&mut self, pos: Option<(f64, f64)>) {
+        self.injected_cursor_pos = pos
+    }
+
+    /// Reverts the input state to the one received from the platform.
+    pub(crate) fn clear_injected_state(&mut self) {
+        self.injected_keys = [None; Key::Unknown as usize + 1];
+        self.injected_buttons = [None; MouseButton::Button8 as usize + 1];
+        self.injected_cursor_pos = None;
     }
 }
 
@@ -157,6 +437,7 @@ pub(crate) trait AbstractCanvas {
     ) -> Self;
     fn render_loop(data: impl FnMut(f64) -> bool + 'static);
     fn poll_events(&mut self);
+    fn make_current(&mut self);
     fn swap_buffers(&mut self);
     fn size(&self) -> (u32, u32);
     fn cursor_pos(&self) -> Option<(f64, f64)>;
@@ -167,9 +448,35 @@ pub(crate) trait AbstractCanvas {
     fn set_cursor_grab(&self, grab: bool);
     fn set_cursor_position(&self, x: f64, y: f64);
     fn hide_cursor(&self, hide: bool);
+    fn set_cursor_icon(&self, icon: CursorIcon);
+    fn set_cursor_image(
+        &self,
+        image: impl GenericImage<Pixel = impl Pixel<Subpixel = u8>>,
+        hotspot: (u32, u32),
+    );
     fn hide(&mut self);
     fn show(&mut self);
 
+    fn monitors(&self) -> Vec<Monitor>;
+    fn set_fullscreen(&mut self, fullscreen: Option<Fullscreen>) -> bool;
+    fn fullscreen(&self) -> Option<Fullscreen>;
+    fn set_resizable(&mut self, resizable: bool);
+    fn is_resizable(&self) -> bool;
+    fn set_decorations(&mut self, decorations: bool);
+    fn has_decorations(&self) -> bool;
+    fn set_position(&mut self, x: i32, y: i32);
+    fn position(&self) -> Option<(i32, i32)>;
+    fn set_maximized(&mut self, maximized: bool);
+    fn set_minimized(&mut self, minimized: bool);
+    fn set_min_size(&mut self, size: Option<(u32, u32)>);
+    fn set_max_size(&mut self, size: Option<(u32, u32)>);
+    fn set_always_on_top(&mut self, always_on_top: bool);
+
+    fn take_dropped_files(&mut self) -> Vec<DroppedFile>;
+    fn hovered_files(&self) -> Vec<PathBuf>;
+    fn clipboard_text(&self) -> Option<String>;
+    fn set_clipboard_text(&self, text: &str);
+
     fn get_mouse_button(&self, button: MouseButton) -> Action;
     fn get_key(&self, key: Key) -> Action;
 }
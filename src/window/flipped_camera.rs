@@ -0,0 +1,81 @@
+use crate::camera::Camera;
+use crate::event::WindowEvent;
+use crate::resource::ShaderUniform;
+use crate::window::Canvas;
+use na::{Isometry3, Matrix4, Point3, Vector3};
+
+/// A camera rendering the view of another camera upside-down.
+///
+/// This is used to render into textures, so that the rendered image has its origin at the
+/// top-left corner, like images loaded from files. The projection and view are uploaded from
+/// `Camera::transformation` and `Camera::view_transform`, so cameras which upload a different
+/// view at each pass are rendered with their main view at every pass.
+pub(crate) struct FlippedCamera<'a> {
+    camera: &'a mut dyn Camera,
+    flip: Matrix4<f32>,
+}
+
+impl<'a> FlippedCamera<'a> {
+    pub fn new(camera: &'a mut dyn Camera) -> FlippedCamera<'a> {
+        FlippedCamera {
+            camera,
+            flip: Matrix4::new_nonuniform_scaling(&Vector3::new(1.0, -1.0, 1.0)),
+        }
+    }
+}
+
+impl<'a> Camera for FlippedCamera<'a> {
+    fn handle_event(&mut self, canvas: &Canvas, event: &WindowEvent) {
+        self.camera.handle_event(canvas, event)
+    }
+
+    fn eye(&self) -> Point3<f32> {
+        self.camera.eye()
+    }
+
+    fn view_transform(&self) -> Isometry3<f32> {
+        self.camera.view_transform()
+    }
+
+    fn transformation(&self) -> Matrix4<f32> {
+        self.flip * self.camera.transformation()
+    }
+
+    fn inverse_transformation(&self) -> Matrix4<f32> {
+        self.camera.inverse_transformation() * self.flip
+    }
+
+    fn clip_planes(&self) -> (f32, f32) {
+        self.camera.clip_planes()
+    }
+
+    fn update(&mut self, canvas: &Canvas) {
+        self.camera.update(canvas)
+    }
+
+    fn upload(
+        &self,
+        _: usize,
+        proj: &mut ShaderUniform<Matrix4<f32>>,
+        view: &mut ShaderUniform<Matrix4<f32>>,
+    ) {
+        // The projection is recovered from the full transformation, since the one uploaded by
+        // the camera cannot be read back.
+        let view_transform = self.camera.view_transform();
+        let proj_mat = self.transformation() * view_transform.inverse().to_homogeneous();
+        proj.upload(&proj_mat);
+        view.upload(&view_transform.to_homogeneous());
+    }
+
+    fn num_passes(&self) -> usize {
+        self.camera.num_passes()
+    }
+
+    fn start_pass(&self, pass: usize, canvas: &Canvas) {
+        self.camera.start_pass(pass, canvas)
+    }
+
+    fn render_complete(&self, canvas: &Canvas) {
+        self.camera.render_complete(canvas)
+    }
+}
@@ -1,29 +1,226 @@
+use std::cell::RefCell;
+use std::mem;
+use std::path::PathBuf;
+use std::rc::{Rc, Weak};
 use std::sync::mpsc::Sender;
 
 use crate::context::Context;
-use crate::event::{Action, Key, Modifiers, MouseButton, TouchAction, WindowEvent};
-use crate::window::canvas::{CanvasSetup, NumSamples};
+use crate::event::{Action, DroppedFile, Key, Modifiers, MouseButton, TouchAction, WindowEvent};
+#[cfg(feature = "gamepad")]
+use crate::event::{GamepadAxis, GamepadButton};
+use crate::window::canvas::{CanvasSetup, CursorIcon, Fullscreen, Monitor, NumSamples};
 use crate::window::AbstractCanvas;
+#[cfg(feature = "clipboard")]
+use copypasta::{ClipboardContext, ClipboardProvider};
 use glutin::{
     self,
-    dpi::LogicalSize,
+    dpi::{LogicalSize, PhysicalPosition, PhysicalSize},
     event::TouchPhase,
     event_loop::{ControlFlow, EventLoop},
     platform::run_return::EventLoopExtRunReturn,
-    window::WindowBuilder,
+    window::{WindowBuilder, WindowId},
     ContextBuilder, GlRequest, PossiblyCurrent, WindowedContext,
 };
 use image::{GenericImage, Pixel};
 
-/// A canvas based on glutin and OpenGL.
-pub struct GLCanvas {
-    window: WindowedContext<PossiblyCurrent>,
-    events: EventLoop<()>,
+thread_local!(static KEY_EVENT_LOOP: RefCell<SharedEventLoop> = RefCell::new(SharedEventLoop::new()));
+
+/// The event loop shared by every window of the thread.
+///
+/// Most platforms do not support several event loops, so the first window creates it and the
+/// events it receives are dispatched to the window they target.
+struct SharedEventLoop {
+    events: Option<EventLoop<()>>,
+    canvases: Vec<Weak<RefCell<CanvasState>>>,
+    #[cfg(feature = "gamepad")]
+    gamepads: Option<gilrs::Gilrs>,
+    #[cfg(feature = "clipboard")]
+    clipboard: Option<ClipboardContext>,
+}
+
+impl SharedEventLoop {
+    fn new() -> SharedEventLoop {
+        SharedEventLoop {
+            events: None,
+            canvases: Vec::new(),
+            #[cfg(feature = "gamepad")]
+            gamepads: match gilrs::Gilrs::new() {
+                Ok(gamepads) => Some(gamepads),
+                Err(e) => {
+                    println!("Gamepad support is not available: {}", e);
+                    None
+                }
+            },
+            #[cfg(feature = "clipboard")]
+            clipboard: match ClipboardContext::new() {
+                Ok(clipboard) => Some(clipboard),
+                Err(e) => {
+                    println!("Clipboard support is not available: {}", e);
+                    None
+                }
+            },
+        }
+    }
+
+    /// The windows still open.
+    fn canvases(&mut self) -> Vec<Rc<RefCell<CanvasState>>> {
+        self.canvases.retain(|c| c.strong_count() != 0);
+        self.canvases.iter().filter_map(|c| c.upgrade()).collect()
+    }
+}
+
+/// The state of a window, updated by the shared event loop.
+struct CanvasState {
+    id: WindowId,
+    // Only `None` while the context is being made current.
+    window: Option<WindowedContext<PossiblyCurrent>>,
     cursor_pos: Option<(f64, f64)>,
     key_states: [Action; Key::Unknown as usize + 1],
     button_states: [Action; MouseButton::Button8 as usize + 1],
     out_events: Sender<WindowEvent>,
-    // listeners: Vec<EventListenerHandle>,
+    // The window attributes which cannot be queried from glutin.
+    fullscreen: Option<Fullscreen>,
+    resizable: bool,
+    decorations: bool,
+    // The files dropped during the last frame, and the ones dragged over the window.
+    dropped_files: Vec<DroppedFile>,
+    hovered_files: Vec<PathBuf>,
+}
+
+impl CanvasState {
+    fn window(&self) -> &WindowedContext<PossiblyCurrent> {
+        self.window
+            .as_ref()
+            .expect("The window context is not available.")
+    }
+
+    fn handle_event(&mut self, event: glutin::event::WindowEvent) {
+        let out_events = &mut self.out_events;
+
+        match event {
+            glutin::event::WindowEvent::CloseRequested => {
+                let _ = out_events.send(WindowEvent::Close);
+            }
+            glutin::event::WindowEvent::Resized(physical_size) => {
+                if let Some(window) = &self.window {
+                    window.resize(physical_size);
+                }
+                let fb_size: (u32, u32) = physical_size.into();
+                let _ = out_events.send(WindowEvent::FramebufferSize(fb_size.0, fb_size.1));
+            }
+            glutin::event::WindowEvent::CursorMoved {
+                position,
+                modifiers,
+                ..
+            } => {
+                let modifiers = translate_modifiers(modifiers);
+                self.cursor_pos = Some(position.into());
+                let _ = out_events.send(WindowEvent::CursorPos(position.x, position.y, modifiers));
+            }
+            glutin::event::WindowEvent::MouseInput {
+                state,
+                button,
+                modifiers,
+                ..
+            } => {
+                let action = translate_action(state);
+                let button = translate_mouse_button(button);
+                let modifiers = translate_modifiers(modifiers);
+                self.button_states[button as usize] = action;
+                let _ = out_events.send(WindowEvent::MouseButton(button, action, modifiers));
+            }
+            glutin::event::WindowEvent::Touch(touch) => {
+                let action = match touch.phase {
+                    TouchPhase::Started => TouchAction::Start,
+                    TouchPhase::Ended => TouchAction::End,
+                    TouchPhase::Moved => TouchAction::Move,
+                    TouchPhase::Cancelled => TouchAction::Cancel,
+                };
+
+                let _ = out_events.send(WindowEvent::Touch(
+                    touch.id,
+                    touch.location.x,
+                    touch.location.y,
+                    action,
+                    Modifiers::empty(),
+                ));
+            }
+            glutin::event::WindowEvent::MouseWheel {
+                delta, modifiers, ..
+            } => {
+                let (x, y) = match delta {
+                    glutin::event::MouseScrollDelta::LineDelta(dx, dy) => {
+                        (dx as f64 * 10.0, dy as f64 * 10.0)
+                    }
+                    glutin::event::MouseScrollDelta::PixelDelta(delta) => delta.into(),
+                };
+                let modifiers = translate_modifiers(modifiers);
+                let _ = out_events.send(WindowEvent::Scroll(x, y, modifiers));
+            }
+            glutin::event::WindowEvent::KeyboardInput { input, .. } => {
+                let action = translate_action(input.state);
+                let key = translate_key(input.virtual_keycode);
+                let modifiers = translate_modifiers(input.modifiers);
+                self.key_states[key as usize] = action;
+                let _ = out_events.send(WindowEvent::Key(key, action, modifiers));
+            }
+            glutin::event::WindowEvent::ReceivedCharacter(c) => {
+                let _ = out_events.send(WindowEvent::Char(c));
+            }
+            glutin::event::WindowEvent::DroppedFile(path) => {
+                self.hovered_files.clear();
+                self.dropped_files.push(DroppedFile::Path(path));
+                let _ = out_events.send(WindowEvent::DroppedFile);
+            }
+            glutin::event::WindowEvent::HoveredFile(path) => {
+                self.hovered_files.push(path);
+                let _ = out_events.send(WindowEvent::HoveredFile);
+            }
+            glutin::event::WindowEvent::HoveredFileCancelled => {
+                self.hovered_files.clear();
+                let _ = out_events.send(WindowEvent::HoveredFileCancelled);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// A canvas based on glutin and OpenGL.
+///
+/// Every canvas of a thread shares the same event loop, and their OpenGL contexts share their
+/// resources (buffers, textures, shaders), so that several windows can be open at the same time.
+pub struct GLCanvas {
+    state: Rc<RefCell<CanvasState>>,
+}
+
+fn new_event_loop() -> EventLoop<()> {
+    #[cfg(any(
+        target_os = "linux",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "netbsd",
+        target_os = "openbsd"
+    ))]
+    let events = {
+        use glutin::platform::unix::EventLoopExtUnix;
+        EventLoop::new_any_thread()
+    };
+    #[cfg(windows)]
+    let events = {
+        use glutin::platform::windows::EventLoopExtWindows;
+        EventLoop::new_any_thread()
+    };
+    #[cfg(not(any(
+        target_os = "linux",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "netbsd",
+        target_os = "openbsd",
+        windows
+    )))]
+    let events = EventLoop::new();
+
+    events
 }
 
 impl AbstractCanvas for GLCanvas {
@@ -35,31 +232,11 @@ impl AbstractCanvas for GLCanvas {
         canvas_setup: Option<CanvasSetup>,
         out_events: Sender<WindowEvent>,
     ) -> Self {
-        #[cfg(any(
-            target_os = "linux",
-            target_os = "dragonfly",
-            target_os = "freebsd",
-            target_os = "netbsd",
-            target_os = "openbsd"
-        ))]
-        let events = {
-            use glutin::platform::unix::EventLoopExtUnix;
-            EventLoop::new_any_thread()
-        };
-        #[cfg(windows)]
-        let events = {
-            use glutin::platform::windows::EventLoopExtWindows;
-            EventLoop::new_any_thread()
-        };
-        #[cfg(not(any(
-            target_os = "linux",
-            target_os = "dragonfly",
-            target_os = "freebsd",
-            target_os = "netbsd",
-            target_os = "openbsd",
-            windows
-        )))]
-        let events = EventLoop::new();
+        let (events, shared) = KEY_EVENT_LOOP.with(|l| {
+            let mut l = l.borrow_mut();
+            let events = l.events.take().unwrap_or_else(new_event_loop);
+            (events, l.canvases().into_iter().next())
+        });
 
         let window = WindowBuilder::new()
             .with_title(title)
@@ -69,32 +246,52 @@ impl AbstractCanvas for GLCanvas {
             vsync: true,
             samples: NumSamples::Zero,
         });
-        let window = ContextBuilder::new()
+        let builder = ContextBuilder::new()
             .with_vsync(canvas_setup.vsync)
             .with_multisampling(canvas_setup.samples as u16)
             .with_gl(GlRequest::GlThenGles {
                 opengl_version: (3, 2),
                 opengles_version: (2, 0),
-            })
-            .build_windowed(window, &events)
-            .unwrap();
+            });
+        // Share the resources with the other windows, which use the same `Context`.
+        let window = match &shared {
+            Some(shared) => builder
+                .with_shared_lists(shared.borrow().window().context())
+                .build_windowed(window, &events),
+            None => builder.build_windowed(window, &events),
+        }
+        .unwrap();
         let window = unsafe { window.make_current().unwrap() };
         Context::init(|| unsafe {
             glow::Context::from_loader_function(|name| window.get_proc_address(name) as *const _)
         });
 
+        // Vertex arrays are not shared between contexts.
         let ctxt = Context::get();
         let vao = ctxt.create_vertex_array();
         ctxt.bind_vertex_array(vao.as_ref());
 
-        GLCanvas {
-            window,
-            events,
+        let state = Rc::new(RefCell::new(CanvasState {
+            id: window.window().id(),
+            window: Some(window),
             cursor_pos: None,
             key_states: [Action::Release; Key::Unknown as usize + 1],
             button_states: [Action::Release; MouseButton::Button8 as usize + 1],
             out_events,
-        }
+            fullscreen: None,
+            resizable: true,
+            decorations: true,
+            dropped_files: Vec::new(),
+            hovered_files: Vec::new(),
+        }));
+
+        KEY_EVENT_LOOP.with(|l| {
+            let mut l = l.borrow_mut();
+            l.events = Some(events);
+            l.canvases.push(Rc::downgrade(&state));
+        });
+
+        GLCanvas { state }
     }
 
     fn render_loop(mut callback: impl FnMut(f64) -> bool + 'static) {
@@ -106,114 +303,86 @@ impl AbstractCanvas for GLCanvas {
     }
 
     fn poll_events(&mut self) {
-        let out_events = &mut self.out_events;
-        let window = &mut self.window;
-        let button_states = &mut self.button_states;
-        let key_states = &mut self.key_states;
-        let cursor_pos = &mut self.cursor_pos;
+        let (events, canvases) = KEY_EVENT_LOOP.with(|l| {
+            let mut l = l.borrow_mut();
+            (l.events.take(), l.canvases())
+        });
+        let mut events = events.expect("The event loop is already running.");
+
+        // The dropped files are only kept until the next frame, like their events.
+        for canvas in &canvases {
+            canvas.borrow_mut().dropped_files.clear();
+        }
 
-        self.events.run_return(|event, _, control_flow| {
+        events.run_return(|event, _, control_flow| {
             use glutin::event::Event;
 
             match event {
-                Event::WindowEvent { event, .. } => match event {
-                    glutin::event::WindowEvent::CloseRequested => {
-                        let _ = out_events.send(WindowEvent::Close);
-                    }
-                    glutin::event::WindowEvent::Resized(physical_size) => {
-                        window.resize(physical_size);
-                        let fb_size: (u32, u32) = physical_size.into();
-                        let _ = out_events.send(WindowEvent::FramebufferSize(fb_size.0, fb_size.1));
-                    }
-                    glutin::event::WindowEvent::CursorMoved {
-                        position,
-                        modifiers,
-                        ..
-                    } => {
-                        let modifiers = translate_modifiers(modifiers);
-                        *cursor_pos = Some(position.into());
-                        let _ = out_events
-                            .send(WindowEvent::CursorPos(position.x, position.y, modifiers));
-                    }
-                    glutin::event::WindowEvent::MouseInput {
-                        state,
-                        button,
-                        modifiers,
-                        ..
-                    } => {
-                        let action = translate_action(state);
-                        let button = translate_mouse_button(button);
-                        let modifiers = translate_modifiers(modifiers);
-                        button_states[button as usize] = action;
-                        let _ =
-                            out_events.send(WindowEvent::MouseButton(button, action, modifiers));
-                    }
-                    glutin::event::WindowEvent::Touch(touch) => {
-                        let action = match touch.phase {
-                            TouchPhase::Started => TouchAction::Start,
-                            TouchPhase::Ended => TouchAction::End,
-                            TouchPhase::Moved => TouchAction::Move,
-                            TouchPhase::Cancelled => TouchAction::Cancel,
-                        };
-
-                        let _ = out_events.send(WindowEvent::Touch(
-                            touch.id,
-                            touch.location.x,
-                            touch.location.y,
-                            action,
-                            Modifiers::empty(),
-                        ));
-                    }
-                    glutin::event::WindowEvent::MouseWheel {
-                        delta, modifiers, ..
-                    } => {
-                        let (x, y) = match delta {
-                            glutin::event::MouseScrollDelta::LineDelta(dx, dy) => {
-                                (dx as f64 * 10.0, dy as f64 * 10.0)
-                            }
-                            glutin::event::MouseScrollDelta::PixelDelta(delta) => delta.into(),
-                        };
-                        let modifiers = translate_modifiers(modifiers);
-                        let _ = out_events.send(WindowEvent::Scroll(x, y, modifiers));
-                    }
-                    glutin::event::WindowEvent::KeyboardInput { input, .. } => {
-                        let action = translate_action(input.state);
-                        let key = translate_key(input.virtual_keycode);
-                        let modifiers = translate_modifiers(input.modifiers);
-                        key_states[key as usize] = action;
-                        let _ = out_events.send(WindowEvent::Key(key, action, modifiers));
-                    }
-                    glutin::event::WindowEvent::ReceivedCharacter(c) => {
-                        let _ = out_events.send(WindowEvent::Char(c));
+                Event::WindowEvent { window_id, event } => {
+                    if let Some(canvas) = canvases.iter().find(|c| c.borrow().id == window_id) {
+                        canvas.borrow_mut().handle_event(event)
                     }
-                    _ => {}
-                },
+                }
                 Event::RedrawEventsCleared => {
                     *control_flow = ControlFlow::Exit;
                 }
                 _ => {}
             };
-        })
+        });
+
+        KEY_EVENT_LOOP.with(|l| {
+            let mut l = l.borrow_mut();
+            l.events = Some(events);
+
+            // Gamepads are not attached to a window, so their events are sent to all of them.
+            #[cfg(feature = "gamepad")]
+            {
+                if let Some(gamepads) = &mut l.gamepads {
+                    while let Some(event) = gamepads.next_event() {
+                        if let Some(event) = translate_gamepad_event(event) {
+                            for canvas in &canvases {
+                                let _ = canvas.borrow().out_events.send(event);
+                            }
+                        }
+                    }
+                }
+            }
+        });
+    }
+
+    fn make_current(&mut self) {
+        let mut state = self.state.borrow_mut();
+
+        if !state.window().is_current() {
+            let window = state.window.take().unwrap();
+            state.window = Some(match unsafe { window.make_current() } {
+                Ok(window) => window,
+                Err((window, e)) => {
+                    println!("Failed to make the window context current: {}", e);
+                    window
+                }
+            });
+        }
     }
 
     fn swap_buffers(&mut self) {
-        let _ = self.window.swap_buffers();
+        let _ = self.state.borrow().window().swap_buffers();
     }
 
     fn size(&self) -> (u32, u32) {
-        self.window.window().inner_size().into()
+        self.state.borrow().window().window().inner_size().into()
     }
 
     fn cursor_pos(&self) -> Option<(f64, f64)> {
-        self.cursor_pos
+        self.state.borrow().cursor_pos
     }
 
     fn scale_factor(&self) -> f64 {
-        self.window.window().scale_factor() as f64
+        self.state.borrow().window().window().scale_factor() as f64
     }
 
     fn set_title(&mut self, title: &str) {
-        self.window.window().set_title(title)
+        self.state.borrow().window().window().set_title(title)
     }
 
     fn set_icon(&mut self, icon: impl GenericImage<Pixel = impl Pixel<Subpixel = u8>>) {
@@ -223,37 +392,231 @@ impl AbstractCanvas for GLCanvas {
             rgba.extend_from_slice(&pixel.to_rgba().0);
         }
         let icon = glutin::window::Icon::from_rgba(rgba, width, height).unwrap();
-        self.window.window().set_window_icon(Some(icon))
+        self.state
+            .borrow()
+            .window()
+            .window()
+            .set_window_icon(Some(icon))
     }
 
     fn set_cursor_grab(&self, grab: bool) {
-        let _ = self.window.window().set_cursor_grab(grab);
+        let _ = self.state.borrow().window().window().set_cursor_grab(grab);
     }
 
     fn set_cursor_position(&self, x: f64, y: f64) {
-        self.window
+        self.state
+            .borrow()
             .window()
-            .set_cursor_position(glutin::dpi::PhysicalPosition::new(x, y))
+            .window()
+            .set_cursor_position(PhysicalPosition::new(x, y))
             .unwrap();
     }
 
     fn hide_cursor(&self, hide: bool) {
-        self.window.window().set_cursor_visible(!hide)
+        self.state
+            .borrow()
+            .window()
+            .window()
+            .set_cursor_visible(!hide)
+    }
+
+    fn set_cursor_icon(&self, icon: CursorIcon) {
+        self.state
+            .borrow()
+            .window()
+            .window()
+            .set_cursor_icon(translate_cursor_icon(icon))
+    }
+
+    fn set_cursor_image(
+        &self,
+        _: impl GenericImage<Pixel = impl Pixel<Subpixel = u8>>,
+        _: (u32, u32),
+    ) {
+        // Not supported by glutin.
     }
 
     fn hide(&mut self) {
-        self.window.window().set_visible(false)
+        self.state.borrow().window().window().set_visible(false)
     }
 
     fn show(&mut self) {
-        self.window.window().set_visible(true)
+        self.state.borrow().window().window().set_visible(true)
     }
 
+    fn monitors(&self) -> Vec<Monitor> {
+        self.state
+            .borrow()
+            .window()
+            .window()
+            .available_monitors()
+            .map(|m| Monitor {
+                name: m.name(),
+                size: m.size().into(),
+                position: m.position().into(),
+                scale_factor: m.scale_factor(),
+            })
+            .collect()
+    }
+
+    fn set_fullscreen(&mut self, fullscreen: Option<Fullscreen>) -> bool {
+        let mut state = self.state.borrow_mut();
+        let window = state.window().window();
+        let monitor = |i: Option<usize>| match i {
+            Some(i) => window.available_monitors().nth(i),
+            None => window.current_monitor(),
+        };
+
+        let mode = match fullscreen {
+            None => None,
+            Some(Fullscreen::Borderless(i)) => {
+                Some(glutin::window::Fullscreen::Borderless(monitor(i)))
+            }
+            Some(Fullscreen::Exclusive(i)) => monitor(i)
+                .and_then(|m| {
+                    m.video_modes().max_by_key(|v| {
+                        let size = v.size();
+                        (size.width * size.height, v.refresh_rate())
+                    })
+                })
+                .map(glutin::window::Fullscreen::Exclusive),
+        };
+
+        // The monitor was not found.
+        if fullscreen.is_some() && mode.is_none() {
+            return false;
+        }
+
+        window.set_fullscreen(mode);
+        state.fullscreen = fullscreen;
+        true
+    }
+
+    fn fullscreen(&self) -> Option<Fullscreen> {
+        let state = self.state.borrow();
+
+        // The window may have left fullscreen mode from the platform, e.g., with a shortcut.
+        state.window().window().fullscreen().and(state.fullscreen)
+    }
+
+    fn set_resizable(&mut self, resizable: bool) {
+        let mut state = self.state.borrow_mut();
+        state.window().window().set_resizable(resizable);
+        state.resizable = resizable;
+    }
+
+    fn is_resizable(&self) -> bool {
+        self.state.borrow().resizable
+    }
+
+    fn set_decorations(&mut self, decorations: bool) {
+        let mut state = self.state.borrow_mut();
+        state.window().window().set_decorations(decorations);
+        state.decorations = decorations;
+    }
+
+    fn has_decorations(&self) -> bool {
+        self.state.borrow().decorations
+    }
+
+    fn set_position(&mut self, x: i32, y: i32) {
+        self.state
+            .borrow()
+            .window()
+            .window()
+            .set_outer_position(PhysicalPosition::new(x, y))
+    }
+
+    fn position(&self) -> Option<(i32, i32)> {
+        self.state
+            .borrow()
+            .window()
+            .window()
+            .outer_position()
+            .ok()
+            .map(|p| p.into())
+    }
+
+    fn set_maximized(&mut self, maximized: bool) {
+        self.state
+            .borrow()
+            .window()
+            .window()
+            .set_maximized(maximized)
+    }
+
+    fn set_minimized(&mut self, minimized: bool) {
+        self.state
+            .borrow()
+            .window()
+            .window()
+            .set_minimized(minimized)
+    }
+
+    fn set_min_size(&mut self, size: Option<(u32, u32)>) {
+        self.state
+            .borrow()
+            .window()
+            .window()
+            .set_min_inner_size(size.map(|(w, h)| PhysicalSize::new(w, h)))
+    }
+
+    fn set_max_size(&mut self, size: Option<(u32, u32)>) {
+        self.state
+            .borrow()
+            .window()
+            .window()
+            .set_max_inner_size(size.map(|(w, h)| PhysicalSize::new(w, h)))
+    }
+
+    fn set_always_on_top(&mut self, always_on_top: bool) {
+        self.state
+            .borrow()
+            .window()
+            .window()
+            .set_always_on_top(always_on_top)
+    }
+
+    fn take_dropped_files(&mut self) -> Vec<DroppedFile> {
+        mem::replace(&mut self.state.borrow_mut().dropped_files, Vec::new())
+    }
+
+    fn hovered_files(&self) -> Vec<PathBuf> {
+        self.state.borrow().hovered_files.clone()
+    }
+
+    #[cfg(feature = "clipboard")]
+    fn clipboard_text(&self) -> Option<String> {
+        KEY_EVENT_LOOP.with(|l| {
+            let mut l = l.borrow_mut();
+            l.clipboard.as_mut()?.get_contents().ok()
+        })
+    }
+
+    #[cfg(not(feature = "clipboard"))]
+    fn clipboard_text(&self) -> Option<String> {
+        None
+    }
+
+    #[cfg(feature = "clipboard")]
+    fn set_clipboard_text(&self, text: &str) {
+        KEY_EVENT_LOOP.with(|l| {
+            if let Some(clipboard) = &mut l.borrow_mut().clipboard {
+                if let Err(e) = clipboard.set_contents(text.to_string()) {
+                    println!("Failed to set the clipboard contents: {}", e);
+                }
+            }
+        })
+    }
+
+    #[cfg(not(feature = "clipboard"))]
+    fn set_clipboard_text(&self, _: &str) {}
+
     fn get_mouse_button(&self, button: MouseButton) -> Action {
-        self.button_states[button as usize]
+        self.state.borrow().button_states[button as usize]
     }
     fn get_key(&self, key: Key) -> Action {
-        self.key_states[key as usize]
+        self.state.borrow().key_states[key as usize]
     }
 }
 
@@ -264,6 +627,46 @@ fn translate_action(action: glutin::event::ElementState) -> Action {
     }
 }
 
+fn translate_cursor_icon(icon: CursorIcon) -> glutin::window::CursorIcon {
+    match icon {
+        CursorIcon::Default => glutin::window::CursorIcon::Default,
+        CursorIcon::Crosshair => glutin::window::CursorIcon::Crosshair,
+        CursorIcon::Hand => glutin::window::CursorIcon::Hand,
+        CursorIcon::Arrow => glutin::window::CursorIcon::Arrow,
+        CursorIcon::Move => glutin::window::CursorIcon::Move,
+        CursorIcon::Text => glutin::window::CursorIcon::Text,
+        CursorIcon::Wait => glutin::window::CursorIcon::Wait,
+        CursorIcon::Help => glutin::window::CursorIcon::Help,
+        CursorIcon::Progress => glutin::window::CursorIcon::Progress,
+        CursorIcon::NotAllowed => glutin::window::CursorIcon::NotAllowed,
+        CursorIcon::ContextMenu => glutin::window::CursorIcon::ContextMenu,
+        CursorIcon::Cell => glutin::window::CursorIcon::Cell,
+        CursorIcon::VerticalText => glutin::window::CursorIcon::VerticalText,
+        CursorIcon::Alias => glutin::window::CursorIcon::Alias,
+        CursorIcon::Copy => glutin::window::CursorIcon::Copy,
+        CursorIcon::NoDrop => glutin::window::CursorIcon::NoDrop,
+        CursorIcon::Grab => glutin::window::CursorIcon::Grab,
+        CursorIcon::Grabbing => glutin::window::CursorIcon::Grabbing,
+        CursorIcon::AllScroll => glutin::window::CursorIcon::AllScroll,
+        CursorIcon::ZoomIn => glutin::window::CursorIcon::ZoomIn,
+        CursorIcon::ZoomOut => glutin::window::CursorIcon::ZoomOut,
+        CursorIcon::EResize => glutin::window::CursorIcon::EResize,
+        CursorIcon::NResize => glutin::window::CursorIcon::NResize,
+        CursorIcon::NeResize => glutin::window::CursorIcon::NeResize,
+        CursorIcon::NwResize => glutin::window::CursorIcon::NwResize,
+        CursorIcon::SResize => glutin::window::CursorIcon::SResize,
+        CursorIcon::SeResize => glutin::window::CursorIcon::SeResize,
+        CursorIcon::SwResize => glutin::window::CursorIcon::SwResize,
+        CursorIcon::WResize => glutin::window::CursorIcon::WResize,
+        CursorIcon::EwResize => glutin::window::CursorIcon::EwResize,
+        CursorIcon::NsResize => glutin::window::CursorIcon::NsResize,
+        CursorIcon::NeswResize => glutin::window::CursorIcon::NeswResize,
+        CursorIcon::NwseResize => glutin::window::CursorIcon::NwseResize,
+        CursorIcon::ColResize => glutin::window::CursorIcon::ColResize,
+        CursorIcon::RowResize => glutin::window::CursorIcon::RowResize,
+    }
+}
+
 fn translate_modifiers(modifiers: glutin::event::ModifiersState) -> Modifiers {
     let mut res = Modifiers::empty();
     if modifiers.shift() {
@@ -462,3 +865,73 @@ fn translate_key(button: Option<glutin::event::VirtualKeyCode>) -> Key {
         Key::Unknown
     }
 }
+
+#[cfg(feature = "gamepad")]
+fn translate_gamepad_event(event: gilrs::Event) -> Option<WindowEvent> {
+    use gilrs::EventType;
+
+    let id: usize = event.id.into();
+
+    match event.event {
+        EventType::Connected => Some(WindowEvent::GamepadConnected(id)),
+        EventType::Disconnected => Some(WindowEvent::GamepadDisconnected(id)),
+        EventType::ButtonPressed(button, _) => Some(WindowEvent::GamepadButton(
+            id,
+            translate_gamepad_button(button),
+            Action::Press,
+        )),
+        EventType::ButtonReleased(button, _) => Some(WindowEvent::GamepadButton(
+            id,
+            translate_gamepad_button(button),
+            Action::Release,
+        )),
+        // Analog triggers are reported as buttons with a value by gilrs.
+        EventType::ButtonChanged(gilrs::Button::LeftTrigger2, value, _) => Some(
+            WindowEvent::GamepadAxis(id, GamepadAxis::LeftTrigger, value as f64),
+        ),
+        EventType::ButtonChanged(gilrs::Button::RightTrigger2, value, _) => Some(
+            WindowEvent::GamepadAxis(id, GamepadAxis::RightTrigger, value as f64),
+        ),
+        EventType::AxisChanged(axis, value, _) => Some(WindowEvent::GamepadAxis(
+            id,
+            translate_gamepad_axis(axis),
+            value as f64,
+        )),
+        _ => None,
+    }
+}
+
+#[cfg(feature = "gamepad")]
+fn translate_gamepad_axis(axis: gilrs::Axis) -> GamepadAxis {
+    match axis {
+        gilrs::Axis::LeftStickX => GamepadAxis::LeftStickX,
+        gilrs::Axis::LeftStickY => GamepadAxis::LeftStickY,
+        gilrs::Axis::RightStickX => GamepadAxis::RightStickX,
+        gilrs::Axis::RightStickY => GamepadAxis::RightStickY,
+        _ => GamepadAxis::Unknown,
+    }
+}
+
+#[cfg(feature = "gamepad")]
+fn translate_gamepad_button(button: gilrs::Button) -> GamepadButton {
+    match button {
+        gilrs::Button::South => GamepadButton::South,
+        gilrs::Button::East => GamepadButton::East,
+        gilrs::Button::North => GamepadButton::North,
+        gilrs::Button::West => GamepadButton::West,
+        gilrs::Button::LeftTrigger => GamepadButton::LeftTrigger,
+        gilrs::Button::LeftTrigger2 => GamepadButton::LeftTrigger2,
+        gilrs::Button::RightTrigger => GamepadButton::RightTrigger,
+        gilrs::Button::RightTrigger2 => GamepadButton::RightTrigger2,
+        gilrs::Button::Select => GamepadButton::Select,
+        gilrs::Button::Start => GamepadButton::Start,
+        gilrs::Button::Mode => GamepadButton::Mode,
+        gilrs::Button::LeftThumb => GamepadButton::LeftThumb,
+        gilrs::Button::RightThumb => GamepadButton::RightThumb,
+        gilrs::Button::DPadUp => GamepadButton::DPadUp,
+        gilrs::Button::DPadDown => GamepadButton::DPadDown,
+        gilrs::Button::DPadLeft => GamepadButton::DPadLeft,
+        gilrs::Button::DPadRight => GamepadButton::DPadRight,
+        _ => GamepadButton::Unknown,
+    }
+}
@@ -1,10 +1,11 @@
 //! The window, and things to handle the rendering loop and events.
 
 pub(crate) use self::canvas::AbstractCanvas;
-pub use self::canvas::{Canvas, CanvasSetup, NumSamples};
+pub use self::canvas::{Canvas, CanvasSetup, CursorIcon, Fullscreen, Monitor, NumSamples};
 #[cfg(not(target_arch = "wasm32"))]
 pub use self::gl_canvas::GLCanvas;
 pub use self::state::{ExtendedState, State};
+pub use self::viewport::Viewport;
 #[cfg(target_arch = "wasm32")]
 pub use self::webgl_canvas::WebGLCanvas;
 pub use self::window::Window;
@@ -12,9 +13,11 @@ pub use self::windows_custom::CustomWindow;
 pub use self::windows_custom::RenderMode;
 
 mod canvas;
+mod flipped_camera;
 #[cfg(not(target_arch = "wasm32"))]
 mod gl_canvas;
 mod state;
+mod viewport;
 #[cfg(target_arch = "wasm32")]
 mod webgl_canvas;
 mod window;
@@ -0,0 +1,140 @@
+//! Rectangular regions of a window rendered with their own camera.
+
+use crate::camera::Camera;
+use crate::post_processing::PostProcessingEffect;
+use crate::resource::{FramebufferManager, RenderTarget};
+use na::Vector3;
+
+/// A rectangular region of a window where the 3D scene is rendered with its own camera.
+///
+/// The rectangle is expressed as fractions of the window size, with its origin at the top-left
+/// corner of the window, so that the layout follows the window when it is resized. Viewports are
+/// added to a window with `Window::add_viewport`, and the window events are sent to the camera
+/// of the viewport under the cursor.
+pub struct Viewport {
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+    camera: Box<dyn Camera>,
+    background: Option<Vector3<f32>>,
+    post_processing: Option<Box<dyn PostProcessingEffect>>,
+    render_target: Option<RenderTarget>,
+}
+
+impl Viewport {
+    /// Creates a viewport covering the given fraction of the window and rendered by `camera`.
+    ///
+    /// For example, `Viewport::new(0.5, 0.0, 0.5, 0.5, camera)` covers the top-right quarter of
+    /// the window.
+    pub fn new<C: Camera + 'static>(
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+        camera: C,
+    ) -> Viewport {
+        Viewport {
+            x,
+            y,
+            width,
+            height,
+            camera: Box::new(camera),
+            background: None,
+            post_processing: None,
+            render_target: None,
+        }
+    }
+
+    /// Sets the rectangle covered by this viewport, as fractions of the window size.
+    pub fn set_rect(&mut self, x: f32, y: f32, width: f32, height: f32) {
+        self.x = x;
+        self.y = y;
+        self.width = width;
+        self.height = height;
+    }
+
+    /// The rectangle covered by this viewport, as fractions of the window size.
+    pub fn rect(&self) -> (f32, f32, f32, f32) {
+        (self.x, self.y, self.width, self.height)
+    }
+
+    /// The camera of this viewport.
+    pub fn camera(&self) -> &dyn Camera {
+        &*self.camera
+    }
+
+    /// The mutable camera of this viewport.
+    pub fn camera_mut(&mut self) -> &mut dyn Camera {
+        &mut *self.camera
+    }
+
+    /// Sets the background color of this viewport.
+    ///
+    /// Viewports use the background color of the window by default.
+    pub fn set_background_color(&mut self, r: f32, g: f32, b: f32) {
+        self.background = Some(Vector3::new(r, g, b))
+    }
+
+    /// Sets the post-processing effect applied to this viewport, if any.
+    pub fn set_post_processing_effect(&mut self, effect: Option<Box<dyn PostProcessingEffect>>) {
+        self.post_processing = effect;
+
+        if self.post_processing.is_none() {
+            self.render_target = None;
+        }
+    }
+
+    /// Whether the point `(x, y)`, in pixels from the top-left corner of a window of size
+    /// `(w, h)`, lies in this viewport.
+    pub fn contains(&self, x: f64, y: f64, w: u32, h: u32) -> bool {
+        let (rx, ry, rw, rh) = self.pixel_rect(w, h);
+        let y = h as f64 - y;
+
+        x >= rx as f64 && x < (rx + rw) as f64 && y >= ry as f64 && y < (ry + rh) as f64
+    }
+
+    /// The rectangle covered by this viewport in a window of size `(w, h)`, in pixels from the
+    /// bottom-left corner of the window, as expected by OpenGL.
+    pub(crate) fn pixel_rect(&self, w: u32, h: u32) -> (i32, i32, i32, i32) {
+        let (w, h) = (w as f32, h as f32);
+        let x0 = (self.x * w).round() as i32;
+        let x1 = ((self.x + self.width) * w).round() as i32;
+        let y0 = ((1.0 - self.y - self.height) * h).round() as i32;
+        let y1 = ((1.0 - self.y) * h).round() as i32;
+
+        (x0, y0, (x1 - x0).max(1), (y1 - y0).max(1))
+    }
+
+    pub(crate) fn background(&self) -> Option<Vector3<f32>> {
+        self.background
+    }
+
+    /// The camera, the post-processing effect, and the render target of this viewport with the
+    /// given size, allocated if needed.
+    pub(crate) fn parts_mut(
+        &mut self,
+        w: i32,
+        h: i32,
+    ) -> (
+        &mut (dyn Camera + 'static),
+        Option<(&mut (dyn PostProcessingEffect + 'static), &mut RenderTarget)>,
+    ) {
+        let post_processing = match self.post_processing {
+            Some(ref mut p) => {
+                let target = self.render_target.get_or_insert_with(|| {
+                    FramebufferManager::new_render_target(w as usize, h as usize, true)
+                });
+
+                if target.size() != Some((w as usize, h as usize)) {
+                    target.resize(w as f32, h as f32);
+                }
+
+                Some((&mut **p, target))
+            }
+            None => None,
+        };
+
+        (&mut *self.camera, post_processing)
+    }
+}
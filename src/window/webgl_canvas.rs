@@ -1,19 +1,26 @@
 #![allow(unused_results)]
 
 use std::cell::RefCell;
+use std::mem;
 use std::ops::DerefMut;
+use std::path::PathBuf;
 use std::rc::Rc;
 use std::sync::mpsc::Sender;
 
 use crate::context::Context;
-use crate::event::{Action, Key, Modifiers, MouseButton, TouchAction, WindowEvent};
-use crate::window::{AbstractCanvas, CanvasSetup};
+use crate::event::{Action, DroppedFile, Key, Modifiers, MouseButton, TouchAction, WindowEvent};
+#[cfg(feature = "gamepad")]
+use crate::event::{GamepadAxis, GamepadButton};
+use crate::window::{AbstractCanvas, CanvasSetup, CursorIcon, Fullscreen, Monitor};
 use image::{GenericImage, Pixel};
 use wasm_bindgen::closure::Closure;
-use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen::{Clamped, JsCast, JsValue};
 use web_sys::{
-    EventTarget, HtmlCanvasElement, KeyboardEvent, MouseEvent, TouchEvent, UiEvent, WheelEvent,
+    CanvasRenderingContext2d, ClipboardEvent, DragEvent, EventTarget, File, FileReader,
+    HtmlCanvasElement, ImageData, KeyboardEvent, MouseEvent, TouchEvent, UiEvent, WheelEvent,
 };
+#[cfg(feature = "gamepad")]
+use web_sys::{Gamepad, GamepadMappingType};
 
 struct WebGLCanvasData {
     canvas: HtmlCanvasElement,
@@ -24,6 +31,25 @@ struct WebGLCanvasData {
     out_events: Sender<WindowEvent>,
     scale_factor: f64,
     mouse_capture_state: MouseCaptureState,
+    fullscreen: Option<Fullscreen>,
+    // The last text set by the application or pasted by the user, since the clipboard of the
+    // browser can only be read asynchronously.
+    clipboard: Option<String>,
+    // The files dropped during the last frame, and the ones loaded since then.
+    dropped_files: Vec<DroppedFile>,
+    pending_dropped_files: Vec<DroppedFile>,
+    // The CSS value of the cursor set by the application, or `None` to keep the one of the page.
+    cursor: Option<String>,
+    cursor_hidden: bool,
+    #[cfg(feature = "gamepad")]
+    gamepads: Vec<Option<GamepadSnapshot>>,
+}
+
+/// The last known state of a gamepad, compared with its current state to generate events.
+#[cfg(feature = "gamepad")]
+struct GamepadSnapshot {
+    axes: Vec<f64>,
+    buttons: Vec<(bool, f64)>,
 }
 
 #[derive(PartialEq, Eq)]
@@ -39,6 +65,8 @@ enum EventListener {
     Touch(EventListenerHandle<dyn FnMut(TouchEvent)>),
     Wheel(EventListenerHandle<dyn FnMut(WheelEvent)>),
     Keyboard(EventListenerHandle<dyn FnMut(KeyboardEvent)>),
+    Drag(EventListenerHandle<dyn FnMut(DragEvent)>),
+    Clipboard(EventListenerHandle<dyn FnMut(ClipboardEvent)>),
 }
 
 struct EventListenerHandle<T: ?Sized> {
@@ -154,6 +182,14 @@ impl AbstractCanvas for WebGLCanvas {
             out_events,
             scale_factor: initial_scale_factor,
             mouse_capture_state: MouseCaptureState::NotCaptured,
+            fullscreen: None,
+            clipboard: None,
+            dropped_files: Vec::new(),
+            pending_dropped_files: Vec::new(),
+            cursor: None,
+            cursor_hidden: false,
+            #[cfg(feature = "gamepad")]
+            gamepads: Vec::new(),
         }));
 
         let mut event_listeners = Vec::new();
@@ -418,6 +454,41 @@ impl AbstractCanvas for WebGLCanvas {
         let listener = EventListenerHandle::new(&data.borrow().canvas, "keyup", callback);
         event_listeners.push(EventListener::Keyboard(listener));
 
+        // The default behavior of the browser must be prevented for the drop event to be
+        // triggered, and for the dropped file not to be opened.
+        let callback = Closure::wrap(Box::new(move |e: DragEvent| {
+            e.prevent_default();
+        }) as Box<dyn FnMut(_)>);
+        let listener = EventListenerHandle::new(&data.borrow().canvas, "dragover", callback);
+        event_listeners.push(EventListener::Drag(listener));
+
+        let edata = data.clone();
+        let callback = Closure::wrap(Box::new(move |e: DragEvent| {
+            e.prevent_default();
+
+            if let Some(files) = e.data_transfer().and_then(|d| d.files()) {
+                for i in 0..files.length() {
+                    if let Some(file) = files.item(i) {
+                        read_dropped_file(file, edata.clone());
+                    }
+                }
+            }
+        }) as Box<dyn FnMut(_)>);
+        let listener = EventListenerHandle::new(&data.borrow().canvas, "drop", callback);
+        event_listeners.push(EventListener::Drag(listener));
+
+        let edata = data.clone();
+        let callback = Closure::wrap(Box::new(move |e: ClipboardEvent| {
+            if let Some(text) = e
+                .clipboard_data()
+                .and_then(|d| d.get_data("text/plain").ok())
+            {
+                edata.borrow_mut().clipboard = Some(text);
+            }
+        }) as Box<dyn FnMut(_)>);
+        let listener = EventListenerHandle::new(&window, "paste", callback);
+        event_listeners.push(EventListener::Clipboard(listener));
+
         WebGLCanvas {
             data,
             event_listeners,
@@ -457,6 +528,17 @@ impl AbstractCanvas for WebGLCanvas {
         for e in data.pending_events.drain(..) {
             let _ = data.out_events.send(e);
         }
+
+        // The dropped files are only kept until the next frame, like their events.
+        data.dropped_files = mem::replace(&mut data.pending_dropped_files, Vec::new());
+
+        // The Gamepad API has no events for axes and buttons, so the gamepads are polled.
+        #[cfg(feature = "gamepad")]
+        poll_gamepads(&mut data.gamepads, &data.out_events);
+    }
+
+    fn make_current(&mut self) {
+        // Only one canvas is supported, so its context is always current.
     }
 
     fn swap_buffers(&mut self) {
@@ -491,8 +573,38 @@ impl AbstractCanvas for WebGLCanvas {
         // Not supported.
     }
 
-    fn hide_cursor(&self, _: bool) {
-        // Not supported.
+    fn hide_cursor(&self, hide: bool) {
+        let mut data = self.data.borrow_mut();
+        data.cursor_hidden = hide;
+        update_cursor(&data);
+    }
+
+    fn set_cursor_icon(&self, icon: CursorIcon) {
+        let mut data = self.data.borrow_mut();
+        data.cursor = Some(cursor_icon_css(icon).to_string());
+        update_cursor(&data);
+    }
+
+    fn set_cursor_image(
+        &self,
+        image: impl GenericImage<Pixel = impl Pixel<Subpixel = u8>>,
+        hotspot: (u32, u32),
+    ) {
+        let (width, height) = image.dimensions();
+        let mut rgba = Vec::with_capacity((width * height) as usize * 4);
+        for (_, _, pixel) in image.pixels() {
+            rgba.extend_from_slice(&pixel.to_rgba().0);
+        }
+
+        match image_data_url(&rgba, width, height) {
+            Ok(url) => {
+                let mut data = self.data.borrow_mut();
+                // The browser falls back to the default cursor if the image cannot be used.
+                data.cursor = Some(format!("url({}) {} {}, auto", url, hotspot.0, hotspot.1));
+                update_cursor(&data);
+            }
+            Err(e) => web_sys::console::error_2(&"Failed to create the cursor image".into(), &e),
+        }
     }
 
     fn hide(&mut self) {
@@ -503,6 +615,136 @@ impl AbstractCanvas for WebGLCanvas {
         // Not supported.
     }
 
+    fn monitors(&self) -> Vec<Monitor> {
+        let window = web_sys::window().unwrap();
+        let scale_factor = window.device_pixel_ratio();
+
+        match window.screen() {
+            Ok(screen) => vec![Monitor {
+                name: None,
+                size: (
+                    (screen.width().unwrap_or(0) as f64 * scale_factor) as u32,
+                    (screen.height().unwrap_or(0) as f64 * scale_factor) as u32,
+                ),
+                position: (0, 0),
+                scale_factor,
+            }],
+            Err(_) => Vec::new(),
+        }
+    }
+
+    fn set_fullscreen(&mut self, fullscreen: Option<Fullscreen>) -> bool {
+        let mut data = self.data.borrow_mut();
+        let document = web_sys::window().unwrap().document().unwrap();
+
+        match fullscreen {
+            Some(_) => {
+                if data.canvas.request_fullscreen().is_err() {
+                    return false;
+                }
+            }
+            None => {
+                if document.fullscreen_element().is_some() {
+                    document.exit_fullscreen();
+                }
+            }
+        }
+
+        data.fullscreen = fullscreen;
+        true
+    }
+
+    fn fullscreen(&self) -> Option<Fullscreen> {
+        let document = web_sys::window().unwrap().document().unwrap();
+
+        // The user may have left fullscreen mode from the browser, e.g., with the escape key.
+        if document.fullscreen_element().is_some() {
+            Some(
+                self.data
+                    .borrow()
+                    .fullscreen
+                    .unwrap_or(Fullscreen::Borderless(None)),
+            )
+        } else {
+            None
+        }
+    }
+
+    fn set_resizable(&mut self, _: bool) {
+        // Not supported.
+    }
+
+    fn is_resizable(&self) -> bool {
+        // The size of the canvas is set by the page.
+        false
+    }
+
+    fn set_decorations(&mut self, _: bool) {
+        // Not supported.
+    }
+
+    fn has_decorations(&self) -> bool {
+        false
+    }
+
+    fn set_position(&mut self, _: i32, _: i32) {
+        // Not supported.
+    }
+
+    fn position(&self) -> Option<(i32, i32)> {
+        let data = self.data.borrow();
+        let rect = data.canvas.get_bounding_client_rect();
+        Some((
+            (rect.left() * data.scale_factor) as i32,
+            (rect.top() * data.scale_factor) as i32,
+        ))
+    }
+
+    fn set_maximized(&mut self, _: bool) {
+        // Not supported.
+    }
+
+    fn set_minimized(&mut self, _: bool) {
+        // Not supported.
+    }
+
+    fn set_min_size(&mut self, _: Option<(u32, u32)>) {
+        // Not supported.
+    }
+
+    fn set_max_size(&mut self, _: Option<(u32, u32)>) {
+        // Not supported.
+    }
+
+    fn set_always_on_top(&mut self, _: bool) {
+        // Not supported.
+    }
+
+    fn take_dropped_files(&mut self) -> Vec<DroppedFile> {
+        mem::replace(&mut self.data.borrow_mut().dropped_files, Vec::new())
+    }
+
+    fn hovered_files(&self) -> Vec<PathBuf> {
+        // The names of the dragged files are only available once they are dropped.
+        Vec::new()
+    }
+
+    fn clipboard_text(&self) -> Option<String> {
+        self.data.borrow().clipboard.clone()
+    }
+
+    fn set_clipboard_text(&self, text: &str) {
+        self.data.borrow_mut().clipboard = Some(text.to_string());
+
+        // The clipboard API is only available in secure contexts.
+        let clipboard = web_sys::window().unwrap().navigator().clipboard();
+        let clipboard_value: &JsValue = clipboard.as_ref();
+
+        if !clipboard_value.is_undefined() {
+            let _ = clipboard.write_text(text);
+        }
+    }
+
     fn get_mouse_button(&self, button: MouseButton) -> Action {
         self.data.borrow().button_states[button as usize]
     }
@@ -511,6 +753,111 @@ impl AbstractCanvas for WebGLCanvas {
     }
 }
 
+/// Applies the cursor of the application to the canvas.
+fn update_cursor(data: &WebGLCanvasData) {
+    let style = data.canvas.style();
+    let cursor = if data.cursor_hidden {
+        Some("none")
+    } else {
+        data.cursor.as_deref()
+    };
+
+    let _ = match cursor {
+        Some(cursor) => style.set_property("cursor", cursor),
+        None => style.remove_property("cursor").map(|_| ()),
+    };
+}
+
+/// Encodes an RGBA image as a PNG data URL.
+fn image_data_url(rgba: &[u8], width: u32, height: u32) -> Result<String, JsValue> {
+    let document = web_sys::window().unwrap().document().unwrap();
+    let canvas = document
+        .create_element("canvas")?
+        .dyn_into::<HtmlCanvasElement>()
+        .map_err(JsValue::from)?;
+    canvas.set_width(width);
+    canvas.set_height(height);
+
+    let context = canvas
+        .get_context("2d")?
+        .ok_or_else(|| JsValue::from_str("2D context not available"))?
+        .dyn_into::<CanvasRenderingContext2d>()
+        .map_err(JsValue::from)?;
+    let image = ImageData::new_with_u8_clamped_array(Clamped(rgba), width)?;
+    context.put_image_data(&image, 0.0, 0.0)?;
+
+    canvas.to_data_url()
+}
+
+fn cursor_icon_css(icon: CursorIcon) -> &'static str {
+    match icon {
+        CursorIcon::Default | CursorIcon::Arrow => "default",
+        CursorIcon::Crosshair => "crosshair",
+        CursorIcon::Hand => "pointer",
+        CursorIcon::Move => "move",
+        CursorIcon::Text => "text",
+        CursorIcon::Wait => "wait",
+        CursorIcon::Help => "help",
+        CursorIcon::Progress => "progress",
+        CursorIcon::NotAllowed => "not-allowed",
+        CursorIcon::ContextMenu => "context-menu",
+        CursorIcon::Cell => "cell",
+        CursorIcon::VerticalText => "vertical-text",
+        CursorIcon::Alias => "alias",
+        CursorIcon::Copy => "copy",
+        CursorIcon::NoDrop => "no-drop",
+        CursorIcon::Grab => "grab",
+        CursorIcon::Grabbing => "grabbing",
+        CursorIcon::AllScroll => "all-scroll",
+        CursorIcon::ZoomIn => "zoom-in",
+        CursorIcon::ZoomOut => "zoom-out",
+        CursorIcon::EResize => "e-resize",
+        CursorIcon::NResize => "n-resize",
+        CursorIcon::NeResize => "ne-resize",
+        CursorIcon::NwResize => "nw-resize",
+        CursorIcon::SResize => "s-resize",
+        CursorIcon::SeResize => "se-resize",
+        CursorIcon::SwResize => "sw-resize",
+        CursorIcon::WResize => "w-resize",
+        CursorIcon::EwResize => "ew-resize",
+        CursorIcon::NsResize => "ns-resize",
+        CursorIcon::NeswResize => "nesw-resize",
+        CursorIcon::NwseResize => "nwse-resize",
+        CursorIcon::ColResize => "col-resize",
+        CursorIcon::RowResize => "row-resize",
+    }
+}
+
+/// Reads the content of a dropped file, and sends it to the window once it is loaded.
+fn read_dropped_file(file: File, data: Rc<RefCell<WebGLCanvasData>>) {
+    let reader = match FileReader::new() {
+        Ok(reader) => reader,
+        Err(e) => {
+            web_sys::console::error_2(&"Failed to create a file reader".into(), &e);
+            return;
+        }
+    };
+
+    let name = file.name();
+    let result_reader = reader.clone();
+    let callback = Closure::once_into_js(move |_: JsValue| match result_reader.result() {
+        Ok(result) if !result.is_null() => {
+            let content = js_sys::Uint8Array::new(&result).to_vec();
+            let mut data = data.borrow_mut();
+            data.pending_dropped_files
+                .push(DroppedFile::Data(name, content));
+            data.pending_events.push(WindowEvent::DroppedFile);
+        }
+        _ => web_sys::console::error_1(&format!("Failed to read the file {}", name).into()),
+    });
+
+    reader.set_onloadend(Some(callback.unchecked_ref()));
+
+    if let Err(e) = reader.read_as_array_buffer(&file) {
+        web_sys::console::error_2(&"Failed to read a dropped file".into(), &e);
+    }
+}
+
 fn translate_mouse_modifiers(event: &MouseEvent) -> Modifiers {
     let mut res = Modifiers::empty();
     if event.shift_key() {
@@ -743,3 +1090,130 @@ fn translate_key(event: &KeyboardEvent) -> Key {
         _ => Key::Unknown,
     }
 }
+
+#[cfg(feature = "gamepad")]
+fn poll_gamepads(snapshots: &mut Vec<Option<GamepadSnapshot>>, out_events: &Sender<WindowEvent>) {
+    let gamepads = match web_sys::window().unwrap().navigator().get_gamepads() {
+        Ok(gamepads) => gamepads,
+        Err(_) => return,
+    };
+
+    for (id, gamepad) in gamepads.iter().enumerate() {
+        if snapshots.len() <= id {
+            snapshots.resize_with(id + 1, || None);
+        }
+
+        let gamepad = match gamepad.dyn_into::<Gamepad>() {
+            Ok(gamepad) if gamepad.connected() => gamepad,
+            _ => {
+                if snapshots[id].take().is_some() {
+                    let _ = out_events.send(WindowEvent::GamepadDisconnected(id));
+                }
+                continue;
+            }
+        };
+
+        let snapshot = snapshots[id].get_or_insert_with(|| {
+            let _ = out_events.send(WindowEvent::GamepadConnected(id));
+            GamepadSnapshot {
+                axes: Vec::new(),
+                buttons: Vec::new(),
+            }
+        });
+
+        let standard = gamepad.mapping() == GamepadMappingType::Standard;
+        let axes: Vec<f64> = gamepad
+            .axes()
+            .iter()
+            .map(|a| a.as_f64().unwrap_or(0.0))
+            .collect();
+        let buttons: Vec<(bool, f64)> = gamepad
+            .buttons()
+            .iter()
+            .filter_map(|b| b.dyn_into::<web_sys::GamepadButton>().ok())
+            .map(|b| (b.pressed(), b.value()))
+            .collect();
+
+        for (i, &value) in axes.iter().enumerate() {
+            if snapshot.axes.get(i).cloned().unwrap_or(0.0) != value {
+                let (axis, value) = translate_gamepad_axis(i, value, standard);
+                let _ = out_events.send(WindowEvent::GamepadAxis(id, axis, value));
+            }
+        }
+
+        for (i, &(pressed, value)) in buttons.iter().enumerate() {
+            let (was_pressed, old_value) = snapshot.buttons.get(i).cloned().unwrap_or((false, 0.0));
+
+            if pressed != was_pressed {
+                let button = translate_gamepad_button(i, standard);
+                let action = if pressed {
+                    Action::Press
+                } else {
+                    Action::Release
+                };
+                let _ = out_events.send(WindowEvent::GamepadButton(id, button, action));
+            }
+
+            // Analog triggers are reported as buttons with a value by the standard mapping.
+            if standard && value != old_value {
+                let axis = match i {
+                    6 => Some(GamepadAxis::LeftTrigger),
+                    7 => Some(GamepadAxis::RightTrigger),
+                    _ => None,
+                };
+
+                if let Some(axis) = axis {
+                    let _ = out_events.send(WindowEvent::GamepadAxis(id, axis, value));
+                }
+            }
+        }
+
+        snapshot.axes = axes;
+        snapshot.buttons = buttons;
+    }
+}
+
+// See https://w3c.github.io/gamepad/#remapping for the standard mapping.
+#[cfg(feature = "gamepad")]
+fn translate_gamepad_axis(index: usize, value: f64, standard: bool) -> (GamepadAxis, f64) {
+    if !standard {
+        return (GamepadAxis::Unknown, value);
+    }
+
+    // The vertical axes point down with the standard mapping.
+    match index {
+        0 => (GamepadAxis::LeftStickX, value),
+        1 => (GamepadAxis::LeftStickY, -value),
+        2 => (GamepadAxis::RightStickX, value),
+        3 => (GamepadAxis::RightStickY, -value),
+        _ => (GamepadAxis::Unknown, value),
+    }
+}
+
+#[cfg(feature = "gamepad")]
+fn translate_gamepad_button(index: usize, standard: bool) -> GamepadButton {
+    if !standard {
+        return GamepadButton::Unknown;
+    }
+
+    match index {
+        0 => GamepadButton::South,
+        1 => GamepadButton::East,
+        2 => GamepadButton::West,
+        3 => GamepadButton::North,
+        4 => GamepadButton::LeftTrigger,
+        5 => GamepadButton::RightTrigger,
+        6 => GamepadButton::LeftTrigger2,
+        7 => GamepadButton::RightTrigger2,
+        8 => GamepadButton::Select,
+        9 => GamepadButton::Start,
+        10 => GamepadButton::LeftThumb,
+        11 => GamepadButton::RightThumb,
+        12 => GamepadButton::DPadUp,
+        13 => GamepadButton::DPadDown,
+        14 => GamepadButton::DPadLeft,
+        15 => GamepadButton::DPadRight,
+        16 => GamepadButton::Mode,
+        _ => GamepadButton::Unknown,
+    }
+}
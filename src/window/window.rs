@@ -3,10 +3,12 @@
  * FIXME: this file is too big. Some heavy refactoring need to be done here.
  */
 use std::cell::RefCell;
+use std::io::Result as IoResult;
 use std::iter::repeat;
-use std::path::Path;
+use std::mem;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
-use std::sync::mpsc::{self, Receiver};
+use std::sync::mpsc::{self, Receiver, Sender};
 use std::thread;
 use std::time::Duration;
 
@@ -15,21 +17,28 @@ use na::{Point2, Point3, Vector2, Vector3};
 
 use crate::camera::{ArcBall, Camera};
 use crate::context::Context;
-use crate::event::{Action, EventManager, Key, WindowEvent};
+use crate::event::{
+    Action, ActionMap, DroppedFile, EventManager, EventRecorder, EventRecording, EventReplayer,
+    Key, MouseButton, WindowEvent,
+};
 use crate::light::Light;
+use crate::loader::obj::ParseWarning;
 use crate::planar_camera::{FixedView, PlanarCamera};
 use crate::planar_line_renderer::PlanarLineRenderer;
 use crate::post_processing::PostProcessingEffect;
 #[cfg(feature = "conrod")]
 use crate::renderer::ConrodRenderer;
 use crate::renderer::{LineRenderer, PlanarRenderer, PointRenderer, Renderer};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::resource::{AsyncLoader, HotReloader};
 use crate::resource::{
     FramebufferManager, Mesh, PlanarMesh, RenderTarget, Texture, TextureManager,
 };
 use crate::scene::{PlanarSceneNode, SceneNode};
 use crate::text::{Font, TextRenderer};
 use crate::window::canvas::CanvasSetup;
-use crate::window::{Canvas, State};
+use crate::window::flipped_camera::FlippedCamera;
+use crate::window::{Canvas, CursorIcon, Fullscreen, Monitor, State, Viewport};
 use image::imageops;
 use image::{GenericImage, Pixel};
 use image::{ImageBuffer, Rgb};
@@ -62,8 +71,17 @@ impl ConrodContext {
 /// Structure representing a window and a 3D scene.
 ///
 /// This is the main interface with the 3d engine.
+///
+/// Several windows can be open at the same time on native platforms, each with its own scene,
+/// by calling the `render` method of each window in the same loop. They share their GPU
+/// resources, e.g., the meshes, textures and materials of the global managers, as long as at
+/// least one of them stays open.
 pub struct Window {
     events: Rc<Receiver<WindowEvent>>,
+    event_sender: Sender<WindowEvent>,
+    frame: u64,
+    recorder: Option<EventRecorder>,
+    replayer: Option<EventReplayer>,
     unhandled_events: Rc<RefCell<Vec<WindowEvent>>>,
     max_dur_per_frame: Option<Duration>,
     scene: SceneNode,
@@ -80,6 +98,8 @@ pub struct Window {
     curr_time: Instant,
     planar_camera: Rc<RefCell<FixedView>>,
     camera: Rc<RefCell<ArcBall>>,
+    viewports: Vec<Viewport>,
+    captured_viewport: Option<usize>,
     should_close: bool,
     #[cfg(feature = "conrod")]
     conrod_context: ConrodContext,
@@ -161,6 +181,26 @@ impl Window {
         self.canvas.hide_cursor(hide);
     }
 
+    /// Sets the icon of the cursor while it is over the window.
+    #[inline]
+    pub fn set_cursor_icon(&self, icon: CursorIcon) {
+        self.canvas.set_cursor_icon(icon);
+    }
+
+    /// Sets a custom image as the cursor while it is over the window.
+    ///
+    /// The `hotspot` is the pixel of the image pointing at the cursor position. Use
+    /// `Window::set_cursor_icon` to go back to a standard icon. Browsers may ignore images larger
+    /// than 128x128 pixels. This does nothing on native platforms, where custom cursors are not
+    /// supported by glutin.
+    pub fn set_cursor_image(
+        &self,
+        image: impl GenericImage<Pixel = impl Pixel<Subpixel = u8>>,
+        hotspot: (u32, u32),
+    ) {
+        self.canvas.set_cursor_image(image, hotspot);
+    }
+
     /// Closes the window.
     #[inline]
     pub fn close(&mut self) {
@@ -179,6 +219,136 @@ impl Window {
         self.canvas.show()
     }
 
+    /// The monitors connected to the computer.
+    ///
+    /// Only the screen of the browser is given on web platforms.
+    pub fn monitors(&self) -> Vec<Monitor> {
+        self.canvas.monitors()
+    }
+
+    /// Makes the window fullscreen, or windowed if `fullscreen` is `None`.
+    ///
+    /// Both fullscreen modes use the Fullscreen API on web platforms, which only allows it in
+    /// response to a user input, e.g., when a key is pressed.
+    ///
+    /// Returns `false` if the mode could not be set, e.g., if the requested monitor does not
+    /// exist or the browser refused the request. The window is left unchanged in this case.
+    pub fn set_fullscreen(&mut self, fullscreen: Option<Fullscreen>) -> bool {
+        self.canvas.set_fullscreen(fullscreen)
+    }
+
+    /// The fullscreen mode of the window, or `None` if it is windowed.
+    pub fn fullscreen(&self) -> Option<Fullscreen> {
+        self.canvas.fullscreen()
+    }
+
+    /// Sets whether the window can be resized by the user.
+    ///
+    /// Does nothing on web platforms.
+    pub fn set_resizable(&mut self, resizable: bool) {
+        self.canvas.set_resizable(resizable)
+    }
+
+    /// Whether the window can be resized by the user.
+    pub fn is_resizable(&self) -> bool {
+        self.canvas.is_resizable()
+    }
+
+    /// Sets whether the window has a title bar and borders.
+    ///
+    /// Does nothing on web platforms.
+    pub fn set_decorations(&mut self, decorations: bool) {
+        self.canvas.set_decorations(decorations)
+    }
+
+    /// Whether the window has a title bar and borders.
+    pub fn has_decorations(&self) -> bool {
+        self.canvas.has_decorations()
+    }
+
+    /// Moves the top-left corner of the window to the given position on the desktop, in pixels.
+    ///
+    /// Does nothing on web platforms.
+    pub fn set_position(&mut self, x: i32, y: i32) {
+        self.canvas.set_position(x, y)
+    }
+
+    /// The position of the top-left corner of the window on the desktop, in pixels.
+    ///
+    /// Returns `None` if the platform does not provide it, e.g., on Wayland, or the position of
+    /// the canvas in the page on web platforms.
+    pub fn position(&self) -> Option<(i32, i32)> {
+        self.canvas.position()
+    }
+
+    /// Maximizes the window, or restores its previous size if `maximized` is `false`.
+    ///
+    /// Does nothing on web platforms.
+    pub fn set_maximized(&mut self, maximized: bool) {
+        self.canvas.set_maximized(maximized)
+    }
+
+    /// Minimizes the window, or restores it if `minimized` is `false`.
+    ///
+    /// Does nothing on web platforms.
+    pub fn set_minimized(&mut self, minimized: bool) {
+        self.canvas.set_minimized(minimized)
+    }
+
+    /// Sets the minimum size of the window, in pixels, or removes it if `size` is `None`.
+    ///
+    /// Does nothing on web platforms.
+    pub fn set_min_size(&mut self, size: Option<(u32, u32)>) {
+        self.canvas.set_min_size(size)
+    }
+
+    /// Sets the maximum size of the window, in pixels, or removes it if `size` is `None`.
+    ///
+    /// Does nothing on web platforms.
+    pub fn set_max_size(&mut self, size: Option<(u32, u32)>) {
+        self.canvas.set_max_size(size)
+    }
+
+    /// Sets whether the window stays on top of the other windows.
+    ///
+    /// Does nothing on web platforms.
+    pub fn set_always_on_top(&mut self, always_on_top: bool) {
+        self.canvas.set_always_on_top(always_on_top)
+    }
+
+    /// Removes and returns the files dropped onto the window during the last frame.
+    ///
+    /// A `WindowEvent::DroppedFile` event is sent when files are dropped, and the files not taken
+    /// are discarded at the next frame. On web platforms, their content is returned instead of
+    /// their path. Recorded and injected events do not carry any file.
+    pub fn take_dropped_files(&mut self) -> Vec<DroppedFile> {
+        self.canvas.take_dropped_files()
+    }
+
+    /// The paths of the files currently dragged over the window.
+    ///
+    /// This is always empty on web platforms.
+    pub fn hovered_files(&self) -> Vec<PathBuf> {
+        self.canvas.hovered_files()
+    }
+
+    /// The text contained by the clipboard, if any.
+    ///
+    /// On native platforms, this requires the `clipboard` feature. On web platforms, the clipboard
+    /// cannot be read synchronously, so this returns the last text set with
+    /// `Window::set_clipboard_text` or pasted by the user into the page.
+    pub fn clipboard_text(&self) -> Option<String> {
+        self.canvas.clipboard_text()
+    }
+
+    /// Replaces the content of the clipboard with some text.
+    ///
+    /// On native platforms, this requires the `clipboard` feature. On web platforms, the
+    /// clipboard of the browser is only written in secure contexts.
+    pub fn set_clipboard_text(&self, text: &str) {
+        self.canvas.set_clipboard_text(text)
+    }
+
     /// Sets the background color.
     #[inline]
     pub fn set_background_color(&mut self, r: f32, g: f32, b: f32) {
@@ -187,18 +357,28 @@ impl Window {
         self.background.z = b;
     }
 
-    /// Set the size of all subsequent points to be drawn until the next time this function is envoked.
+    /// Set the size, in pixels, of the points drawn with `draw_point`.
+    ///
+    /// The sizes given to `draw_point_with_size` are multiplied by this factor.
     #[inline]
     pub fn set_point_size(&mut self, pt_size: f32) {
         self.point_renderer.set_point_size(pt_size);
     }
 
-    /// Set the width of all subsequent lines to be drawn until the next time this function is envoked.
+    /// Set the width, in pixels, of all subsequent lines to be drawn until the next time this
+    /// function is envoked.
     #[inline]
     pub fn set_line_width(&mut self, line_width: f32) {
         self.line_renderer.set_line_width(line_width);
     }
 
+    /// Set the width, in pixels, of all subsequent 2D lines to be drawn until the next time this
+    /// function is envoked.
+    #[inline]
+    pub fn set_planar_line_width(&mut self, line_width: f32) {
+        self.planar_line_renderer.set_line_width(line_width);
+    }
+
     /// Adds a 3D line to be drawn during the next render.
     ///
     /// The line is being drawn only during the next frame after this call.
@@ -217,12 +397,72 @@ impl Window {
         self.planar_line_renderer.draw_line(*a, *b, *color);
     }
 
+    /// Adds a 3D polyline to be drawn during the next render.
+    ///
+    /// `colors` and `widths` contain either a single element, or one element per point. If
+    /// `widths` is empty, the width set by `set_line_width` is used.
+    #[inline]
+    pub fn draw_polyline(
+        &mut self,
+        points: &[Point3<f32>],
+        colors: &[Point3<f32>],
+        widths: &[f32],
+        closed: bool,
+    ) {
+        self.line_renderer
+            .draw_polyline(points, colors, widths, closed);
+    }
+
+    /// Adds a 2D polyline to be drawn during the next render.
+    ///
+    /// `colors` and `widths` contain either a single element, or one element per point. If
+    /// `widths` is empty, the width set by `set_planar_line_width` is used.
+    #[inline]
+    pub fn draw_planar_polyline(
+        &mut self,
+        points: &[Point2<f32>],
+        colors: &[Point3<f32>],
+        widths: &[f32],
+        closed: bool,
+    ) {
+        self.planar_line_renderer
+            .draw_polyline(points, colors, widths, closed);
+    }
+
+    /// The renderer of the lines added with `draw_line` and `draw_polyline`.
+    ///
+    /// Use it to change the caps, joins, dash pattern, or depth test of those lines.
+    #[inline]
+    pub fn line_renderer_mut(&mut self) -> &mut LineRenderer {
+        &mut self.line_renderer
+    }
+
+    /// The renderer of the lines added with `draw_planar_line` and `draw_planar_polyline`.
+    #[inline]
+    pub fn planar_line_renderer_mut(&mut self) -> &mut PlanarLineRenderer {
+        &mut self.planar_line_renderer
+    }
+
     /// Adds a point to be drawn during the next frame.
     #[inline]
     pub fn draw_point(&mut self, pt: &Point3<f32>, color: &Point3<f32>) {
         self.point_renderer.draw_point(*pt, *color);
     }
 
+    /// Adds a point with the given size to be drawn during the next frame.
+    #[inline]
+    pub fn draw_point_with_size(&mut self, pt: &Point3<f32>, color: &Point3<f32>, size: f32) {
+        self.point_renderer.draw_point_with_size(*pt, *color, size);
+    }
+
+    /// The renderer of the points added with `draw_point` and `draw_point_with_size`.
+    ///
+    /// Use it to change the shape of those points, or to attenuate their size with the distance.
+    #[inline]
+    pub fn point_renderer_mut(&mut self) -> &mut PointRenderer {
+        &mut self.point_renderer
+    }
+
     /// Adds a string to be drawn during the next frame.
     #[inline]
     pub fn draw_text(
@@ -275,6 +515,55 @@ impl Window {
         self.scene.add_obj(path, mtl_dir, scale)
     }
 
+    /// Adds an obj model to the scene, returning an error if it cannot be loaded.
+    ///
+    /// The warnings emitted while parsing the file are returned with the created node.
+    ///
+    /// # Arguments
+    /// * `path`  - relative path to the obj file.
+    /// * `scale` - scale to apply to the model.
+    pub fn try_add_obj(
+        &mut self,
+        path: &Path,
+        mtl_dir: &Path,
+        scale: Vector3<f32>,
+    ) -> IoResult<(SceneNode, Vec<ParseWarning>)> {
+        self.scene.try_add_obj(path, mtl_dir, scale)
+    }
+
+    /// Adds an obj model to the scene, loaded on a worker thread.
+    ///
+    /// The returned node is empty until the model is loaded. See `SceneNode::add_obj_async`.
+    ///
+    /// # Arguments
+    /// * `path`  - relative path to the obj file.
+    /// * `scale` - scale to apply to the model.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn add_obj_async(&mut self, path: &Path, mtl_dir: &Path, scale: Vector3<f32>) -> SceneNode {
+        self.scene.add_obj_async(path, mtl_dir, scale)
+    }
+
+    /// Adds a PLY point cloud or mesh to the scene.
+    ///
+    /// The vertex colors of the file are used. If it has no face, its points are displayed.
+    ///
+    /// # Arguments
+    /// * `path`  - relative path to the ply file.
+    /// * `scale` - scale to apply to the model.
+    pub fn add_ply(&mut self, path: &Path, scale: Vector3<f32>) -> IoResult<SceneNode> {
+        self.scene.add_ply(path, scale)
+    }
+
+    /// Adds a STL mesh to the scene.
+    ///
+    /// # Arguments
+    /// * `path`  - relative path to the stl file.
+    /// * `weld`  - whether vertices sharing the same position are merged for smooth shading.
+    /// * `scale` - scale to apply to the model.
+    pub fn add_stl(&mut self, path: &Path, weld: bool, scale: Vector3<f32>) -> IoResult<SceneNode> {
+        self.scene.add_stl(path, weld, scale)
+    }
+
     /// Adds an unnamed mesh to the scene.
     pub fn add_mesh(&mut self, mesh: Rc<RefCell<Mesh>>, scale: Vector3<f32>) -> SceneNode {
         self.scene.add_mesh(mesh, scale)
@@ -438,6 +727,49 @@ impl Window {
         self.light_mode = pos;
     }
 
+    /// Adds a viewport, rendering the 3D scene in a region of this window with its own camera.
+    ///
+    /// Once a viewport is added, the 3D scene is only rendered in the viewports, and the cameras
+    /// given to `render_with_camera` and the like are ignored. The events are sent to the camera
+    /// of the viewport under the cursor, or of the viewport where a mouse button was pressed
+    /// until it is released. The 2D scene and the text are still rendered on the whole window.
+    /// Returns the index of the viewport.
+    pub fn add_viewport(&mut self, viewport: Viewport) -> usize {
+        self.viewports.push(viewport);
+        self.viewports.len() - 1
+    }
+
+    /// Removes the viewport with the given index.
+    pub fn remove_viewport(&mut self, i: usize) -> Viewport {
+        self.captured_viewport = None;
+        self.viewports.remove(i)
+    }
+
+    /// Removes every viewport, so that the 3D scene is rendered on the whole window again.
+    pub fn clear_viewports(&mut self) {
+        self.captured_viewport = None;
+        self.viewports.clear()
+    }
+
+    /// The viewports of this window.
+    pub fn viewports(&self) -> &[Viewport] {
+        &self.viewports
+    }
+
+    /// The mutable viewports of this window.
+    pub fn viewports_mut(&mut self) -> &mut [Viewport] {
+        &mut self.viewports
+    }
+
+    /// The index of the viewport containing the point `(x, y)`, in pixels from the top-left
+    /// corner of the window.
+    ///
+    /// The last viewport added wins if several viewports overlap.
+    pub fn viewport_at(&self, x: f64, y: f64) -> Option<usize> {
+        let (w, h) = self.canvas.size();
+        self.viewports.iter().rposition(|v| v.contains(x, y, w, h))
+    }
+
     /// Retrieve a mutable reference to the UI based on Conrod.
     #[cfg(feature = "conrod")]
     pub fn conrod_ui_mut(&mut self) -> &mut conrod::Ui {
@@ -524,7 +856,7 @@ impl Window {
         setup: Option<CanvasSetup>,
     ) -> Window {
         let (event_send, event_receive) = mpsc::channel();
-        let canvas = Canvas::open(title, hide, width, height, setup, event_send);
+        let canvas = Canvas::open(title, hide, width, height, setup, event_send.clone());
 
         init_gl();
 
@@ -533,6 +865,10 @@ impl Window {
             max_dur_per_frame: None,
             canvas: canvas,
             events: Rc::new(event_receive),
+            event_sender: event_send,
+            frame: 0,
+            recorder: None,
+            replayer: None,
             unhandled_events: Rc::new(RefCell::new(Vec::new())),
             scene: SceneNode::new_empty(),
             scene2: PlanarSceneNode::new_empty(),
@@ -557,6 +893,8 @@ impl Window {
                 Point3::new(0.0f32, 0.0, -1.0),
                 Point3::origin(),
             ))),
+            viewports: Vec::new(),
+            captured_viewport: None,
         };
 
         if hide {
@@ -640,6 +978,11 @@ impl Window {
         self.canvas.get_key(key)
     }
 
+    /// Gets the status of a mouse button.
+    pub fn get_mouse_button(&self, button: MouseButton) -> Action {
+        self.canvas.get_mouse_button(button)
+    }
+
     /// Gets the last known position of the mouse.
     ///
     /// The position of the mouse is automatically updated when the mouse moves over the canvas.
@@ -647,6 +990,132 @@ impl Window {
         self.canvas.cursor_pos()
     }
 
+    /// The action map of this window, updated at each frame by the events it handles.
+    ///
+    /// The builtin cameras use the actions and axes listed in `camera_actions` when they are
+    /// bound.
+    pub fn action_map(&self) -> &ActionMap {
+        self.canvas.action_map()
+    }
+
+    /// The mutable action map of this window.
+    pub fn action_map_mut(&mut self) -> &mut ActionMap {
+        self.canvas.action_map_mut()
+    }
+
+    /// Replaces the action map of this window, e.g., by one loaded from a configuration file.
+    pub fn set_action_map(&mut self, action_map: ActionMap) {
+        *self.canvas.action_map_mut() = action_map
+    }
+
+    /// Sends an event to this window as if it had been received from the platform.
+    ///
+    /// The event is given by `Window::events` and handled by the window and its camera at the
+    /// next call to `render`, like the real events. The key, mouse button and cursor position
+    /// it sets override the real ones immediately, as returned by `Window::get_key`,
+    /// `Window::get_mouse_button` and `Window::cursor_pos`, until `clear_input_overrides` is
+    /// called. This makes it possible to drive an application from automated tests, e.g., with
+    /// a hidden window.
+    pub fn inject_event(&mut self, event: WindowEvent) {
+        self.canvas.inject_event(&event);
+        let _ = self.event_sender.send(event);
+    }
+
+    /// Overrides the state of a key, or reverts it to the real one if `action` is `None`.
+    ///
+    /// No event is sent, see `Window::inject_event` to also send one.
+    pub fn override_key(&mut self, key: Key, action: Option<Action>) {
+        self.canvas.set_injected_key(key, action)
+    }
+
+    /// Overrides the state of a mouse button, or reverts it to the real one if `action` is
+    /// `None`.
+    ///
+    /// No event is sent, see `Window::inject_event` to also send one.
+    pub fn override_mouse_button(&mut self, button: MouseButton, action: Option<Action>) {
+        self.canvas.set_injected_mouse_button(button, action)
+    }
+
+    /// Overrides the position of the cursor, or reverts it to the real one if `pos` is `None`.
+    ///
+    /// No event is sent, see `Window::inject_event` to also send one.
+    pub fn override_cursor_pos(&mut self, pos: Option<(f64, f64)>) {
+        self.canvas.set_injected_cursor_pos(pos)
+    }
+
+    /// Reverts the state of every key and mouse button, and the cursor position, to the real
+    /// ones.
+    pub fn clear_input_overrides(&mut self) {
+        self.canvas.clear_injected_state()
+    }
+
+    /// Starts recording the events received by this window, as given by `Window::events`.
+    ///
+    /// Any recording in progress is discarded.
+    pub fn start_recording(&mut self) {
+        self.recorder = Some(EventRecorder::new(self.frame))
+    }
+
+    /// Stops recording events, and returns the recording if one was in progress.
+    pub fn stop_recording(&mut self) -> Option<EventRecording> {
+        self.recorder.take().map(|r| r.finish())
+    }
+
+    /// Whether the events received by this window are being recorded.
+    pub fn is_recording(&self) -> bool {
+        self.recorder.is_some()
+    }
+
+    /// Replays a recording, starting at the next frame.
+    ///
+    /// Each event is sent to this window at the frame it was recorded at, counted from the start
+    /// of the replay, and goes through the same channel as the events received from the
+    /// platform. The state of the keys, mouse buttons and cursor follows the replayed events
+    /// until the replay ends, so that replaying on a hidden window reproduces the camera moves
+    /// and interactions exactly. Any replay in progress is stopped.
+    pub fn replay(&mut self, recording: EventRecording) {
+        self.canvas.clear_injected_state();
+        self.replayer = Some(EventReplayer::new(recording, self.frame))
+    }
+
+    /// Stops the replay in progress, if any.
+    pub fn stop_replay(&mut self) {
+        if self.replayer.take().is_some() {
+            self.canvas.clear_injected_state();
+        }
+    }
+
+    /// Whether a recording is being replayed.
+    ///
+    /// A replay ends one frame after its last event, so that this event is handled with the
+    /// replayed input state.
+    pub fn is_replaying(&self) -> bool {
+        self.replayer.is_some()
+    }
+
+    /// Sends the events of the replay due at this frame, and records the new events.
+    fn record_and_replay_events(&mut self) {
+        if let Some(replayer) = &mut self.replayer {
+            if replayer.is_finished() {
+                self.stop_replay();
+            } else {
+                for event in replayer.due_events(self.frame) {
+                    self.canvas.inject_event(&event);
+                    let _ = self.event_sender.send(event);
+                }
+            }
+        }
+
+        if let Some(recorder) = &mut self.recorder {
+            let events: Vec<_> = self.events.try_iter().collect();
+
+            for event in events {
+                recorder.record(self.frame, event);
+                let _ = self.event_sender.send(event);
+            }
+        }
+    }
+
     #[inline]
     fn handle_events(
         &mut self,
@@ -656,6 +1125,8 @@ impl Window {
         let unhandled_events = self.unhandled_events.clone(); // FIXME: could we avoid the clone?
         let events = self.events.clone(); // FIXME: could we avoid the clone?
 
+        self.canvas.action_map_mut().begin_frame();
+
         for event in unhandled_events.borrow().iter() {
             self.handle_event(camera, planar_camera, event)
         }
@@ -666,6 +1137,9 @@ impl Window {
 
         unhandled_events.borrow_mut().clear();
         self.canvas.poll_events();
+
+        self.frame += 1;
+        self.record_and_replay_events();
     }
 
     fn handle_event(
@@ -674,6 +1148,8 @@ impl Window {
         planar_camera: &mut Option<&mut dyn PlanarCamera>,
         event: &WindowEvent,
     ) {
+        self.canvas.action_map_mut().handle_event(event);
+
         match *event {
             WindowEvent::Key(Key::Escape, Action::Release, _) | WindowEvent::Close => {
                 self.close();
@@ -903,12 +1379,52 @@ impl Window {
             None => self.camera.borrow_mut().handle_event(&self.canvas, event),
         }
 
+        if !self.viewports.is_empty() {
+            self.handle_viewport_event(event);
+            return;
+        }
+
         match *camera {
             Some(ref mut cam) => cam.handle_event(&self.canvas, event),
             None => self.camera.borrow_mut().handle_event(&self.canvas, event),
         }
     }
 
+    /// Sends an event to the camera of the viewport it targets.
+    fn handle_viewport_event(&mut self, event: &WindowEvent) {
+        let cursor = match *event {
+            WindowEvent::CursorPos(x, y, _) => Some((x, y)),
+            _ => self.canvas.cursor_pos(),
+        };
+        let under_cursor = cursor.and_then(|(x, y)| self.viewport_at(x, y));
+
+        let target = match *event {
+            // The size of each viewport is given to its camera when it is rendered.
+            WindowEvent::FramebufferSize(..) => None,
+            WindowEvent::MouseButton(_, Action::Press, _) => {
+                if self.captured_viewport.is_none() {
+                    self.captured_viewport = under_cursor;
+                }
+                self.captured_viewport
+            }
+            WindowEvent::MouseButton(_, Action::Release, _) => {
+                self.captured_viewport.take().or(under_cursor)
+            }
+            _ => self.captured_viewport.or(under_cursor),
+        };
+
+        // Gamepads are not attached to a viewport, and each camera may be bound to its own.
+        if event.is_gamepad_event() {
+            for viewport in &mut self.viewports {
+                viewport.camera_mut().handle_event(&self.canvas, event)
+            }
+        } else if let Some(i) = target {
+            self.viewports[i]
+                .camera_mut()
+                .handle_event(&self.canvas, event)
+        }
+    }
+
     /// Runs the render and event loop until the window is closed.
     pub fn render_loop<S: State>(mut self, mut state: S) {
         Canvas::render_loop(move |_| self.do_render_with_state(&mut state))
@@ -1008,6 +1524,70 @@ impl Window {
         self.do_render_with(camera, planar_camera, None, post_processing)
     }
 
+    /// Renders the 3D scene seen by `camera` into an off-screen render target.
+    ///
+    /// The target is created by `FramebufferManager::new_render_target`, and its texture,
+    /// obtained with `RenderTarget::texture`, can then be applied to scene nodes with
+    /// `SceneNode::set_texture`, e.g., for mirrors or monitors. Call this before `Window::render`
+    /// to update the texture for the next frame. Only the scene nodes are rendered: the lines
+    /// and points drawn with `Window::draw_line` and `Window::draw_point`, the 2D scene and the
+    /// text are not. The image is rendered with its origin at the top-left corner, like images
+    /// loaded from files, so the texture can be used as-is on nodes. The aspect ratio of `camera`
+    /// is only changed to the one of the target during this call.
+    ///
+    /// A node textured with `target` should not be visible from `camera`, since a texture cannot
+    /// be sampled while it is rendered to.
+    pub fn render_to_texture(&mut self, camera: &mut dyn Camera, target: &RenderTarget) {
+        let (width, height) = match target.size() {
+            Some(size) => size,
+            None => panic!("Only off-screen render targets can be rendered to a texture."),
+        };
+        let ctxt = Context::get();
+        let (fb_w, fb_h) = self.canvas.size();
+
+        camera.handle_event(
+            &self.canvas,
+            &WindowEvent::FramebufferSize(width as u32, height as u32),
+        );
+
+        self.framebuffer_manager.select(target);
+        verify!(ctxt.viewport(0, 0, width as i32, height as i32));
+        verify!(ctxt.scissor(0, 0, width as i32, height as i32));
+
+        {
+            // Flipping the image vertically also flips the winding of the triangles.
+            let mut flipped = FlippedCamera::new(camera);
+            verify!(ctxt.front_face(Context::CW));
+
+            for pass in 0usize..flipped.num_passes() {
+                flipped.start_pass(pass, &self.canvas);
+                verify!(ctxt.active_texture(Context::TEXTURE0));
+                verify!(ctxt.clear_color(
+                    self.background.x,
+                    self.background.y,
+                    self.background.z,
+                    1.0
+                ));
+                verify!(ctxt.clear(Context::COLOR_BUFFER_BIT));
+                verify!(ctxt.clear(Context::DEPTH_BUFFER_BIT));
+                self.scene
+                    .data_mut()
+                    .render(pass, &mut flipped, &self.light_mode);
+            }
+
+            flipped.render_complete(&self.canvas);
+            verify!(ctxt.front_face(Context::CCW));
+        }
+
+        camera.handle_event(&self.canvas, &WindowEvent::FramebufferSize(fb_w, fb_h));
+
+        let (w, h) = (self.width() as i32, self.height() as i32);
+        self.framebuffer_manager
+            .select(&FramebufferManager::screen());
+        verify!(ctxt.viewport(0, 0, w, h));
+        verify!(ctxt.scissor(0, 0, w, h));
+    }
+
     fn do_render_with(
         &mut self,
         camera: Option<&mut dyn Camera>,
@@ -1017,8 +1597,22 @@ impl Window {
     ) -> bool {
         let mut camera = camera;
         let mut planar_camera = planar_camera;
+
+        // Other windows may have been rendered since the last frame.
+        self.canvas.make_current();
         self.handle_events(&mut camera, &mut planar_camera);
 
+        let (w, h) = self.canvas.size();
+        verify!(Context::get().viewport(0, 0, w as i32, h as i32));
+
+        // Upload the resources loaded in the background since the last frame, and reload the
+        // ones that changed on disk.
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            AsyncLoader::finish_loads();
+            HotReloader::get_global_reloader(|reloader| reloader.poll());
+        }
+
         let self_cam2 = self.planar_camera.clone(); // FIXME: this is ugly.
         let mut bself_cam2 = self_cam2.borrow_mut();
 
@@ -1065,46 +1659,55 @@ impl Window {
             _ => {}
         }
 
-        if post_processing.is_some() {
-            // if we need post-processing, render to our own frame buffer
-            self.framebuffer_manager
-                .select(&self.post_process_render_target);
+        if !self.viewports.is_empty() {
+            self.render_viewports(renderer);
+            self.render_planar_scene(planar_camera);
         } else {
-            self.framebuffer_manager
-                .select(&FramebufferManager::screen());
-        }
+            if post_processing.is_some() {
+                // if we need post-processing, render to our own frame buffer
+                self.framebuffer_manager
+                    .select(&self.post_process_render_target);
+            } else {
+                self.framebuffer_manager
+                    .select(&FramebufferManager::screen());
+            }
 
-        for pass in 0usize..camera.num_passes() {
-            camera.start_pass(pass, &self.canvas);
-            self.render_scene(camera, pass);
+            for pass in 0usize..camera.num_passes() {
+                camera.start_pass(pass, &self.canvas);
+                self.render_scene(camera, pass, self.background);
 
-            if let Some(ref mut renderer) = renderer {
-                renderer.render(pass, camera)
+                if let Some(ref mut renderer) = renderer {
+                    renderer.render(pass, camera)
+                }
             }
-        }
 
-        camera.render_complete(&self.canvas);
+            camera.render_complete(&self.canvas);
 
-        self.render_planar_scene(planar_camera);
+            self.render_planar_scene(planar_camera);
 
-        let (znear, zfar) = camera.clip_planes();
+            let (znear, zfar) = camera.clip_planes();
 
-        // FIXME: remove this completely?
-        // swatch off the wireframe mode for post processing and text rendering.
-        // if self.wireframe_mode {
-        //     verify!(gl::PolygonMode(Context::FRONT_AND_BACK, Context::FILL));
-        // }
+            // FIXME: remove this completely?
+            // swatch off the wireframe mode for post processing and text rendering.
+            // if self.wireframe_mode {
+            //     verify!(gl::PolygonMode(Context::FRONT_AND_BACK, Context::FILL));
+            // }
 
-        if let Some(ref mut p) = post_processing {
-            // switch back to the screen framebuffer …
-            self.framebuffer_manager
-                .select(&FramebufferManager::screen());
-            // … and execute the post-process
-            // FIXME: use the real time value instead of 0.016!
-            p.update(0.016, w as f32, h as f32, znear, zfar);
-            p.draw(&self.post_process_render_target);
+            if let Some(ref mut p) = post_processing {
+                // switch back to the screen framebuffer …
+                self.framebuffer_manager
+                    .select(&FramebufferManager::screen());
+                // … and execute the post-process
+                // FIXME: use the real time value instead of 0.016!
+                p.update(0.016, w as f32, h as f32, znear, zfar);
+                p.draw(&self.post_process_render_target);
+            }
         }
 
+        // The lines and points are drawn during one frame, by every pass and viewport.
+        self.line_renderer.clear();
+        self.point_renderer.clear();
+
         self.text_renderer.render(w as f32, h as f32);
         #[cfg(feature = "conrod")]
         self.conrod_context.renderer.render(
@@ -1136,20 +1739,98 @@ impl Window {
         !self.should_close()
     }
 
-    fn render_scene(&mut self, camera: &mut dyn Camera, pass: usize) {
+    fn render_scene(&mut self, camera: &mut dyn Camera, pass: usize, background: Vector3<f32>) {
         let ctxt = Context::get();
         // Activate the default texture
         verify!(ctxt.active_texture(Context::TEXTURE0));
-        // Clear the screen to black
-        verify!(ctxt.clear_color(self.background.x, self.background.y, self.background.z, 1.0));
+        // Clear the screen to the background color
+        verify!(ctxt.clear_color(background.x, background.y, background.z, 1.0));
         verify!(ctxt.clear(Context::COLOR_BUFFER_BIT));
         verify!(ctxt.clear(Context::DEPTH_BUFFER_BIT));
 
-        self.line_renderer.render(pass, camera);
-        self.point_renderer.render(pass, camera);
+        self.line_renderer.draw(pass, camera);
+        self.point_renderer.draw(pass, camera);
         self.scene.data_mut().render(pass, camera, &self.light_mode);
     }
 
+    /// Renders the 3D scene in each viewport.
+    ///
+    /// Only the camera of the viewport under the cursor is updated, so that cameras moved with
+    /// the keyboard move one at a time.
+    fn render_viewports(&mut self, mut renderer: Option<&mut dyn Renderer>) {
+        let ctxt = Context::get();
+        let (w, h) = (self.width(), self.height());
+        let active = self.captured_viewport.or_else(|| {
+            self.canvas
+                .cursor_pos()
+                .and_then(|(x, y)| self.viewport_at(x, y))
+        });
+        let mut viewports = mem::replace(&mut self.viewports, Vec::new());
+
+        // Clear the regions of the window that are not covered by any viewport.
+        self.framebuffer_manager
+            .select(&FramebufferManager::screen());
+        verify!(ctxt.clear_color(self.background.x, self.background.y, self.background.z, 1.0));
+        verify!(ctxt.clear(Context::COLOR_BUFFER_BIT));
+
+        for (i, viewport) in viewports.iter_mut().enumerate() {
+            let (x, y, vw, vh) = viewport.pixel_rect(w, h);
+            let background = viewport.background().unwrap_or(self.background);
+            let (camera, mut post_processing) = viewport.parts_mut(vw, vh);
+
+            camera.handle_event(
+                &self.canvas,
+                &WindowEvent::FramebufferSize(vw as u32, vh as u32),
+            );
+
+            if active == Some(i) {
+                camera.update(&self.canvas);
+            }
+
+            match post_processing {
+                Some((_, ref target)) => {
+                    self.framebuffer_manager.select(target);
+                    verify!(ctxt.viewport(0, 0, vw, vh));
+                    verify!(ctxt.scissor(0, 0, vw, vh));
+                }
+                None => {
+                    self.framebuffer_manager
+                        .select(&FramebufferManager::screen());
+                    verify!(ctxt.viewport(x, y, vw, vh));
+                    verify!(ctxt.scissor(x, y, vw, vh));
+                }
+            }
+
+            for pass in 0usize..camera.num_passes() {
+                camera.start_pass(pass, &self.canvas);
+                self.render_scene(camera, pass, background);
+
+                if let Some(ref mut renderer) = renderer {
+                    renderer.render(pass, camera)
+                }
+            }
+
+            camera.render_complete(&self.canvas);
+
+            if let Some((ref mut p, ref target)) = post_processing {
+                let (znear, zfar) = camera.clip_planes();
+
+                self.framebuffer_manager
+                    .select(&FramebufferManager::screen());
+                verify!(ctxt.viewport(x, y, vw, vh));
+                verify!(ctxt.scissor(x, y, vw, vh));
+                // FIXME: use the real time value instead of 0.016!
+                p.update(0.016, vw as f32, vh as f32, znear, zfar);
+                p.draw(target);
+            }
+        }
+
+        self.viewports = viewports;
+
+        verify!(ctxt.viewport(0, 0, w as i32, h as i32));
+        verify!(ctxt.scissor(0, 0, w as i32, h as i32));
+    }
+
     fn render_planar_scene(&mut self, camera: &mut dyn PlanarCamera) {
         let ctxt = Context::get();
         // Activate the default texture
@@ -1173,6 +1854,14 @@ impl Window {
     }
 }
 
+impl Drop for Window {
+    fn drop(&mut self) {
+        // The framebuffers are not shared with the other windows, so they must be deleted from
+        // the context of this window.
+        self.canvas.make_current();
+    }
+}
+
 fn init_gl() {
     /*
      * Misc configurations
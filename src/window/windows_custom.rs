@@ -414,6 +414,8 @@ impl CustomWindow {
         let unhandled_events = self.unhandled_events.clone(); // FIXME: could we avoid the clone?
         let events = self.events.clone(); // FIXME: could we avoid the clone?
 
+        self.canvas.action_map_mut().begin_frame();
+
         for event in unhandled_events.borrow().iter() {
             self.handle_event(camera, planar_camera, event)
         }
@@ -432,6 +434,8 @@ impl CustomWindow {
         planar_camera: &mut Option<&mut dyn PlanarCamera>,
         event: &WindowEvent,
     ) {
+        self.canvas.action_map_mut().handle_event(event);
+
         match *event {
             WindowEvent::Key(Key::Escape, Action::Release, _) | WindowEvent::Close => {
                 self.close();
@@ -692,6 +696,9 @@ impl CustomWindow {
     ) -> bool {
         let mut camera = camera;
         let mut planar_camera = planar_camera;
+
+        // Other windows may have been rendered since the last frame.
+        self.canvas.make_current();
         self.handle_events(&mut camera, &mut planar_camera);
 
          // Panic if there is no camera available for the current rendering mode.